@@ -446,7 +446,8 @@ class SimulatorController{
     }
 
     fillNeighbors() {
-        this.gridManager.cells.floodfill();
+        const cells = this.gridManager.cells;
+        cells.morphology("dilate", cells.get_neighbor_type(), this.rangeValue || 1, 1);
         this.gridManager.syncCellsToTexture();
         this.gridManager.drawGrid();
     }
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::CellManager;
+use crate::cell_manager::CycleState;
+
+/// Longest period (and settling time) looked for when classifying objects.
+pub const DEFAULT_MAX_PERIOD: u64 = 64;
+
+// Digits of the extended Wechsler format
+const WECHSLER_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Lattice symmetry acting on `(q, r)`
+type Symmetry = fn(i32, i32) -> (i32, i32);
+
+/// What an object turned out to be when evolved on its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ObjectClass {
+    StillLife,
+    Oscillator { period: u64 },
+    /// Moves by `(dq, dr)` every `period` generations
+    Spaceship { period: u64, dq: i32, dr: i32 },
+    /// Dies out when left alone
+    Dies,
+    /// Changes before settling into a cycle, i.e. it was not a standalone
+    /// object (usually part of a constellation that was split apart)
+    Unstable,
+    /// No cycle found within the period limit (long oscillators, growth)
+    Unknown,
+}
+
+impl fmt::Display for ObjectClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::StillLife => write!(f, "still life"),
+            Self::Oscillator { period } => write!(f, "period {period} oscillator"),
+            Self::Spaceship { period, dq, dr } => write!(f, "period {period} spaceship moving ({dq},{dr})"),
+            Self::Dies => write!(f, "dies"),
+            Self::Unstable => write!(f, "unstable"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// One separated object of the grid together with its classification.
+#[derive(Clone, Debug)]
+pub struct SoupObject {
+    pub cells: Vec<(i32, i32, i32, u32)>,
+    pub class: ObjectClass,
+    /// apgcode-style id: `xs<population>_`, `xp<period>_` or `xq<period>_`
+    /// followed by the canonical extended Wechsler code of the object
+    pub apgcode: String,
+}
+
+/// Splits the live cells into groups that are connected through the
+/// current neighborhood (in either direction).
+pub fn split_objects(cm: &CellManager) -> Vec<Vec<(i32, i32, i32, u32)>> {
+    // Only empty regions can be too large to label
+    let map = cm.label_components(None).unwrap_or_default();
+    let mut objects = vec![Vec::new(); map.components.len()];
+    for (&(q, r, s), &id) in &map.labels {
+        objects[id as usize - 1].push((q, r, s, cm.get_cell(q, r, s)));
+    }
+    for object in &mut objects {
+        object.sort_unstable();
+    }
+    objects
+}
+
+/// Splits the grid into objects and classifies each of them. Objects that
+/// do not survive on their own are merged with everything within two
+/// neighborhood steps and classified again, so debris that only lives as
+/// part of a constellation (common with `B2` rules) is reported as a whole.
+pub fn classify_objects(cm: &CellManager, max_period: u64) -> Vec<SoupObject> {
+    let classify = |cells: Vec<(i32, i32, i32, u32)>| {
+        let (class, apgcode) = classify_object(cm, &cells, max_period);
+        SoupObject { cells, class, apgcode }
+    };
+    let mut pending: Vec<SoupObject> = split_objects(cm).into_iter().map(classify).collect();
+    let mut done: Vec<SoupObject> = Vec::new();
+
+    while let Some(object) = pending.pop() {
+        if !matches!(object.class, ObjectClass::Dies | ObjectClass::Unstable | ObjectClass::Unknown) {
+            done.push(object);
+            continue;
+        }
+
+        let mut reach: HashSet<(i32, i32, i32)> = HashSet::new();
+        for &(q, r, s, _) in &object.cells {
+            for (q, r, s) in cm.adjacent_cells(q, r, s) {
+                reach.insert((q, r, s));
+                reach.extend(cm.adjacent_cells(q, r, s));
+            }
+        }
+        let is_near = |o: &SoupObject| o.cells.iter().any(|&(q, r, s, _)| reach.contains(&(q, r, s)));
+
+        let mut cells = object.cells;
+        let before = cells.len();
+        for list in [&mut pending, &mut done] {
+            let (near, far): (Vec<_>, Vec<_>) = list.drain(..).partition(|o| is_near(o));
+            *list = far;
+            cells.extend(near.into_iter().flat_map(|o| o.cells));
+        }
+
+        if cells.len() == before {
+            // Nothing close by: it really dies or never settles on its own
+            done.push(SoupObject { cells, class: object.class, apgcode: object.apgcode });
+        } else {
+            cells.sort_unstable();
+            pending.push(classify(cells));
+        }
+    }
+
+    done.sort_by(|a, b| a.cells.cmp(&b.cells));
+    done
+}
+
+/// Evolves `cells` alone on an empty grid with the shape, neighborhood and
+/// rule of `cm`, and returns the class with its apgcode.
+pub fn classify_object(
+    cm: &CellManager,
+    cells: &[(i32, i32, i32, u32)],
+    max_period: u64,
+) -> (ObjectClass, String) {
+    if cells.is_empty() {
+        return (ObjectClass::Dies, "xs0_0".to_string());
+    }
+
+    let min_q = cells.iter().map(|c| c.0).min().unwrap();
+    let max_q = cells.iter().map(|c| c.0).max().unwrap();
+    let min_r = cells.iter().map(|c| c.1).min().unwrap();
+    let max_r = cells.iter().map(|c| c.1).max().unwrap();
+
+    // Enough room for a speed-of-light ship to travel for max_period generations
+    let margin = (max_period as i32 + 2) * cm.config.range.max(1) + 2;
+    let width = (max_q - min_q + 1 + 2 * margin) as usize;
+    let height = (max_r - min_r + 1 + 2 * margin) as usize;
+    let (cq, cr) = ((min_q + max_q) / 2, (min_r + max_r) / 2);
+
+    let mut iso = cm.blank_copy(width, height, None);
+    iso.set_stats_capacity(1);
+    iso.set_hash_history_capacity(max_period as usize + 2);
+    for &(q, r, s, v) in cells {
+        iso.set_cell(q - cq, r - cr, s, v);
+    }
+    iso.record_stats();
+
+    let mut phases = vec![iso.for_each_cell()];
+    let mut state = CycleState::Running;
+    for _ in 0..max_period {
+        iso.step();
+        state = iso.cycle_state();
+        if state != CycleState::Running {
+            break;
+        }
+        phases.push(iso.for_each_cell());
+    }
+
+    let (class, prefix) = match state {
+        CycleState::Extinct { .. } => return (ObjectClass::Dies, "xs0_0".to_string()),
+        CycleState::Still { since: 0 } => (ObjectClass::StillLife, format!("xs{}", cells.len())),
+        CycleState::Oscillating { start: 0, period } => {
+            (ObjectClass::Oscillator { period }, format!("xp{period}"))
+        }
+        CycleState::Moving { start: 0, period, dq, dr } => {
+            (ObjectClass::Spaceship { period, dq, dr }, format!("xq{period}"))
+        }
+        CycleState::Running => (ObjectClass::Unknown, "zz".to_string()),
+        _ => (ObjectClass::Unstable, "zz".to_string()),
+    };
+
+    // Cycles are canonicalised over every phase, anything else over its initial state
+    let period = match class {
+        ObjectClass::Oscillator { period } | ObjectClass::Spaceship { period, .. } => period as usize,
+        _ => 1,
+    };
+    let code = canonical_code(&cm.config.shape, cm.config.depth, &phases[..period.min(phases.len())]);
+    (class, format!("{prefix}_{code}"))
+}
+
+/// Shortest (then alphabetically first) Wechsler code over every phase
+/// and every symmetry of the lattice.
+fn canonical_code(shape: &str, depth: usize, phases: &[Vec<i32>]) -> String {
+    let symmetries: &[Symmetry] = match (shape, depth) {
+        ("square", 1) => &[
+            |q, r| (q, r),
+            |q, r| (-q, r),
+            |q, r| (q, -r),
+            |q, r| (-q, -r),
+            |q, r| (r, q),
+            |q, r| (-r, q),
+            |q, r| (r, -q),
+            |q, r| (-r, -q),
+        ],
+        // Axial rotations by 60° and their reflections
+        ("hexagon", 1) => &[
+            |q, r| (q, r),
+            |q, r| (-r, q + r),
+            |q, r| (-q - r, q),
+            |q, r| (-q, -r),
+            |q, r| (r, -q - r),
+            |q, r| (q + r, -q),
+            |q, r| (r, q),
+            |q, r| (-q, q + r),
+            |q, r| (-q - r, r),
+            |q, r| (-r, -q),
+            |q, r| (q, -q - r),
+            |q, r| (q + r, -r),
+        ],
+        _ => &[|q, r| (q, r)],
+    };
+    let layers = depth.max(1) as i32;
+
+    let mut best: Option<String> = None;
+    for phase in phases {
+        for transform in symmetries {
+            // Multi-layer tiles stack their sub-cells as extra rows
+            let cells: Vec<(i32, i32)> = phase
+                .chunks(4)
+                .map(|c| {
+                    let (q, r) = transform(c[0], c[1]);
+                    (q, r * layers + c[2])
+                })
+                .collect();
+            let code = wechsler(&cells);
+            let better = match &best {
+                None => true,
+                Some(b) => (code.len(), &code) < (b.len(), b),
+            };
+            if better {
+                best = Some(code);
+            }
+        }
+    }
+    best.unwrap_or_else(|| "0".to_string())
+}
+
+// Extended Wechsler format: strips of 5 rows, one digit per column, `w`/`x`
+// for runs of 2/3 blank columns, `y` + digit for longer runs, `z` between strips
+fn wechsler(cells: &[(i32, i32)]) -> String {
+    let Some(min_x) = cells.iter().map(|c| c.0).min() else {
+        return "0".to_string();
+    };
+    let min_y = cells.iter().map(|c| c.1).min().unwrap();
+    let width = (cells.iter().map(|c| c.0).max().unwrap() - min_x + 1) as usize;
+    let height = (cells.iter().map(|c| c.1).max().unwrap() - min_y + 1) as usize;
+    let strips = height.div_ceil(5);
+
+    let mut columns = vec![0u8; width * strips];
+    for &(x, y) in cells {
+        let (x, y) = ((x - min_x) as usize, (y - min_y) as usize);
+        columns[(y / 5) * width + x] |= 1 << (y % 5);
+    }
+
+    let mut out = String::new();
+    for strip in 0..strips {
+        if strip > 0 {
+            out.push('z');
+        }
+        let row = &columns[strip * width..(strip + 1) * width];
+        let end = row.iter().rposition(|&v| v != 0).map_or(0, |i| i + 1);
+        let mut zeros = 0;
+        for &v in &row[..end] {
+            if v == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut out, zeros);
+            zeros = 0;
+            out.push(WECHSLER_DIGITS[v as usize] as char);
+        }
+    }
+    out
+}
+
+fn push_zeros(out: &mut String, mut n: usize) {
+    while n >= 4 {
+        let run = n.min(39);
+        out.push('y');
+        out.push(WECHSLER_DIGITS[run - 4] as char);
+        n -= run;
+    }
+    match n {
+        1 => out.push('0'),
+        2 => out.push('w'),
+        3 => out.push('x'),
+        _ => {}
+    }
+}
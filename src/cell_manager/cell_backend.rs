@@ -30,6 +30,14 @@ impl CellBackend {
         }
     }
 
+    /// Whether a write to `(q, r, s)` is stored rather than dropped.
+    pub fn contains(&self, q: i32, r: i32, s: i32) -> bool {
+        match self {
+            CellBackend::Flat(fm) => fm.contains(q, r, s),
+            CellBackend::Chunked(_) => true,
+        }
+    }
+
     pub fn for_each_cell(&self) -> Vec<i32> {
         match self {
             CellBackend::Flat(fm) => fm.for_each_cell(),
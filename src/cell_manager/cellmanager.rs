@@ -1,5 +1,14 @@
+use std::collections::HashSet;
+
 use crate::cell_manager::{
-    CellBackend, FlatCellManager, ChunkedCellManager, Neighborhood
+    CellBackend, FlatCellManager, ChunkedCellManager, Kernel, Neighborhood, Rule, Topology,
+    stats::{GenerationStats, StatsHistory},
+    hashing::{CycleState, GridHash, HashHistory},
+    history::{DEFAULT_HISTORY_BUDGET, EditHistory},
+    timeline::Timeline,
+    selection::{Clipboard, Selection},
+    coords::is_volumetric,
+    worlds::{Coupling, MAIN_WORLD, World},
 };
 use fastrand;
 // CONFIG STRUCT
@@ -17,13 +26,39 @@ pub struct CellConfig {
     pub range: i32,
 
     pub topology_type: String,
+    pub rule: String,
+    /// Neighbor weights, see [`Kernel::parse`]
+    pub kernel: String,
+}
+
+// A single cell update produced by the stepper
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellChange {
+    pub q: i32,
+    pub r: i32,
+    pub s: i32,
+    pub old: u32,
+    pub new: u32,
 }
 
 // CELL MANAGER
 pub struct CellManager {
     pub config: CellConfig,
+    pub generation: u64,
     inner: CellBackend,
     neighbor_manager: Neighborhood,
+    topology: Topology,
+    rule: Rule,
+    stats: StatsHistory,
+    hash: GridHash,
+    hash_history: HashHistory,
+    history: EditHistory,
+    timeline: Timeline,
+    pub(crate) selection: Option<Selection>,
+    pub(crate) clipboard: Option<Clipboard>,
+    // Worlds stacked on this grid (see `worlds`), numbered from 1
+    worlds: Vec<World>,
+    couplings: Vec<Coupling>,
 }
 
 impl CellManager {
@@ -53,6 +88,8 @@ impl CellManager {
             range: 1,
 
             topology_type: "none".to_string(),
+            rule: Rule::default().rulestring,
+            kernel: Kernel::Uniform.name(),
         };
 
         let neighbor_manager = Neighborhood::new(
@@ -61,16 +98,45 @@ impl CellManager {
             config.range,
         );
 
-        Self {
+        let mut cm = Self {
             config,
+            generation: 0,
             inner,
             neighbor_manager,
-        }
+            topology: Topology::new("none", [0; 6]),
+            rule: Rule::default(),
+            stats: StatsHistory::new(1024),
+            hash: GridHash::default(),
+            hash_history: HashHistory::new(1024),
+            history: EditHistory::new(DEFAULT_HISTORY_BUDGET),
+            timeline: Timeline::new(64, 0),
+            selection: None,
+            clipboard: None,
+            worlds: Vec::new(),
+            couplings: Vec::new(),
+        };
+        cm.topology = Topology::new(&cm.config.topology_type, cm.get_bounds());
+        cm
     }
 
     // BASIC OPERATIONS
     pub fn set_cell(&mut self, q: i32, r: i32, s: i32, value: u32) {
+        self.history.begin("set_cell");
+        let old = self.inner.get_cell(q, r, s);
+        let new = self.write_cell(q, r, s, value);
+        self.history.record(CellChange { q, r, s, old, new });
+        self.history.commit();
+    }
+
+    // Raw write that keeps the hash in sync; returns the value actually stored
+    fn write_cell(&mut self, q: i32, r: i32, s: i32, value: u32) -> u32 {
+        let old = self.inner.get_cell(q, r, s);
         self.inner.set_cell(q, r, s, value);
+        let new = self.inner.get_cell(q, r, s);
+        self.hash.update(q, r, s, old, new);
+        self.hash_history.invalidate();
+        self.timeline.invalidate();
+        new
     }
 
     pub fn get_cell(&self, q: i32, r: i32, s: i32) -> u32 {
@@ -78,7 +144,15 @@ impl CellManager {
     }
 
     pub fn clear(&mut self) {
+        self.history.begin("clear");
+        for c in self.inner.for_each_cell().chunks(4) {
+            self.history.record(CellChange { q: c[0], r: c[1], s: c[2], old: c[3] as u32, new: 0 });
+        }
+        self.history.commit();
         self.inner.clear();
+        self.hash = GridHash::default();
+        self.hash_history.invalidate();
+        self.timeline.invalidate();
     }
 
     pub fn batch_update(
@@ -86,10 +160,12 @@ impl CellManager {
         cell_data: Vec<(i32, i32, i32, u32)>,
         overwrite: Option<u32>,
     ) {
+        self.history.begin("batch_update");
         for (q, r, s, val) in cell_data {
             let write_val = overwrite.unwrap_or(val);
             self.set_cell(q, r, s, write_val);
         }
+        self.history.commit();
     }
 
     pub fn for_each_cell(&self) -> Vec<i32> {
@@ -101,41 +177,500 @@ impl CellManager {
         self.neighbor_manager.get_neighbors(q, r, s)
     }
 
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighbor_manager
+    }
+
     pub fn count_live_neighbors(&self, q: i32, r: i32, s: i32) -> u32 {
         let mut count = 0;
         for &(dq, dr, ds) in self.neighbor_manager.get_neighbor_offsets(s) {
-            count += self.get_cell(q + dq, r + dr, s + ds);
+            if let Some((nq, nr, ns)) = self.topology.wrap(q + dq, r + dr, s + ds) {
+                count += self.get_cell(nq, nr, ns);
+            }
         }
         count
     }
 
-    // RESIZING
-    pub fn resize(&mut self, new_width: usize, new_height: usize, new_depth: usize) {
-        let use_chunked = new_width > self.config.threshold || new_height > self.config.threshold;
+    /// Live neighbors of each species of the rule (species 1 first), which
+    /// is what species rules count; empty for other rules.
+    pub fn count_species_neighbors(&self, q: i32, r: i32, s: i32) -> Vec<u32> {
+        self.count_world_species(0, q, r, s)
+    }
 
-        let old_cells = self.inner.for_each_cell();
+    fn count_world_species(&self, world: usize, q: i32, r: i32, s: i32) -> Vec<u32> {
+        let Some(species) = &self.world_rule(world).species else { return Vec::new() };
+        let mut counts = vec![0; species.count()];
+        for &(dq, dr, ds) in self.neighbor_manager.get_neighbor_offsets(s) {
+            if let Some((nq, nr, ns)) = self.topology.wrap(q + dq, r + dr, s + ds)
+                && let Some(count) = counts.get_mut((self.world_cell(world, nq, nr, ns) as usize).wrapping_sub(1))
+            {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    /// Sum of the weights of the live neighbors (see [`Rule::is_alive`]),
+    /// plus what coupled worlds add, which is what weighted rules threshold on.
+    pub fn count_weighted_neighbors(&self, q: i32, r: i32, s: i32) -> f32 {
+        self.weigh_world_neighbors(0, q, r, s)
+    }
 
-        self.inner = if use_chunked {
-            let mut cm = ChunkedCellManager::new(self.config.chunk_size, new_depth);
-            for chunk in old_cells.chunks(4) {
-                if let &[q, r, s, v] = chunk {
-                    cm.set_cell(q, r, s, v as u32);
+    fn weigh_world_neighbors(&self, world: usize, q: i32, r: i32, s: i32) -> f32 {
+        let rule = self.world_rule(world);
+        let offsets = self.neighbor_manager.get_neighbor_offsets(s);
+        let weights = self.neighbor_manager.get_neighbor_weights(s);
+        let mut sum = 0.0;
+        for (&(dq, dr, ds), &weight) in offsets.iter().zip(weights) {
+            if let Some((nq, nr, ns)) = self.topology.wrap(q + dq, r + dr, s + ds)
+                && rule.is_alive(self.world_cell(world, nq, nr, ns))
+            {
+                sum += weight;
+            }
+        }
+        sum + self.coupled_sum(world, q, r, s)
+    }
+
+    /// Sets the neighbor weights from a kernel name such as `gaussian:1.5`.
+    pub fn set_kernel(&mut self, kernel: &str) -> bool {
+        match Kernel::parse(kernel) {
+            Some(kernel) => {
+                self.config.kernel = kernel.name();
+                self.neighbor_manager.set_kernel(kernel);
+                for coupling in &mut self.couplings {
+                    coupling.neighborhood.set_kernel(kernel);
                 }
+                self.hash_history.invalidate();
+                true
             }
-            CellBackend::Chunked(cm)
-        } else {
-            let mut fm = FlatCellManager::new(new_width , new_height , new_depth);
-            for chunk in old_cells.chunks(4) {
-                if let &[q, r, s, v] = chunk {
-                    fm.set_cell(q, r, s, v as u32);
+            None => false,
+        }
+    }
+
+    /// Where `(q, r, s)` ends up after topology wrapping, or `None` when it
+    /// falls off the grid.
+    pub fn wrap_cell(&self, q: i32, r: i32, s: i32) -> Option<(i32, i32, i32)> {
+        self.topology.wrap(q, r, s).filter(|&(q, r, s)| self.inner.contains(q, r, s))
+    }
+
+    /// Number of live neighbors (see [`Rule::is_alive`]) in a world, plus
+    /// what coupled worlds add, which is what rules count.
+    fn count_world_neighbors(&self, world: usize, q: i32, r: i32, s: i32) -> u32 {
+        let rule = self.world_rule(world);
+        let mut count = 0;
+        for &(dq, dr, ds) in self.neighbor_manager.get_neighbor_offsets(s) {
+            if let Some((nq, nr, ns)) = self.topology.wrap(q + dq, r + dr, s + ds)
+                && rule.is_alive(self.world_cell(world, nq, nr, ns))
+            {
+                count += 1;
+            }
+        }
+        match self.couplings.iter().any(|c| c.target == world) {
+            true => (count as f32 + self.coupled_sum(world, q, r, s)).round().max(0.0) as u32,
+            false => count,
+        }
+    }
+
+    // Weighted live cells that the couplings into `world` see from (q, r, s)
+    fn coupled_sum(&self, world: usize, q: i32, r: i32, s: i32) -> f32 {
+        let mut sum = 0.0;
+        for coupling in self.couplings.iter().filter(|c| c.target == world) {
+            let rule = self.world_rule(coupling.source);
+            let offsets = coupling.neighborhood.get_neighbor_offsets(s);
+            let weights = coupling.neighborhood.get_neighbor_weights(s);
+            for (&(dq, dr, ds), &weight) in offsets.iter().zip(weights) {
+                if let Some((nq, nr, ns)) = self.topology.wrap(q + dq, r + dr, s + ds)
+                    && rule.is_alive(self.world_cell(coupling.source, nq, nr, ns))
+                {
+                    sum += coupling.weight * weight;
                 }
             }
-            CellBackend::Flat(fm)
+        }
+        sum
+    }
+
+    // RULES & STEPPING
+    pub fn set_rule(&mut self, rulestring: &str) -> bool {
+        match Rule::parse(rulestring) {
+            Some(rule) => {
+                self.config.rule = rule.rulestring.clone();
+                // `S/B/C/N` rules name the neighborhood they were made for
+                if let Some(kind) = rule.neighborhood
+                    && kind != self.config.neighbor_type
+                    && Neighborhood::kinds_for_shape(&self.config.shape).contains(&kind)
+                {
+                    let (shape, topology) = (self.config.shape.clone(), self.config.topology_type.clone());
+                    self.change_grid_properties(shape, kind.to_string(), self.config.range, topology);
+                }
+                self.rule = rule;
+                self.hash_history.invalidate();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Advances every world one generation and returns how many cells of
+    /// the main grid changed. Stats cover the main grid.
+    pub fn step(&mut self) -> usize {
+        let mut live = vec![self.for_each_cell()];
+        live.extend(self.worlds.iter().map(|w| w.cells.for_each_cell()));
+        let mut changes = (0..live.len()).map(|world| self.compute_next_generation(world, &live));
+        let main_changes = changes.next().unwrap_or_default();
+        let world_changes: Vec<Vec<CellChange>> = changes.collect();
+
+        self.apply_changes(&main_changes);
+        // Stepping is not undoable, and older edits no longer match the grid
+        self.history.clear();
+        for (world, changes) in self.worlds.iter_mut().zip(world_changes) {
+            for c in changes {
+                world.set_cell(c.q, c.r, c.s, c.new);
+            }
+        }
+        self.generation += 1;
+
+        let stats = GenerationStats::from_step(
+            self.generation,
+            &live[0],
+            &main_changes,
+            self.config.chunk_size,
+            self.config.depth,
+        );
+        self.record_hash(stats.bounds, stats.population);
+        self.stats.push(stats);
+
+        let changed = main_changes.len();
+        // The timeline only holds the main grid, which cannot be rewound alone
+        match self.worlds.is_empty() {
+            true => self.timeline.record_step(self.generation - 1, &live[0], main_changes),
+            false => self.timeline.invalidate(),
+        }
+        changed
+    }
+
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Cells of `world` whose state changes in the next generation, given
+    /// the live cells of every world.
+    ///
+    /// Only live cells and the cells that can see them are evaluated: every
+    /// offset is walked backwards from each live cell, so asymmetric
+    /// neighborhoods (e.g. hexagon `tripod`) still find all candidates.
+    /// Walking back only inverts wrapping that translates; on topologies
+    /// that mirror or turn at a seam the cells near the edges are checked
+    /// forwards as well. Live cells of coupled worlds are walked back
+    /// through the coupling.
+    fn compute_next_generation(&self, world: usize, live: &[Vec<i32>]) -> Vec<CellChange> {
+        let mut candidates: HashSet<(i32, i32, i32)> = HashSet::new();
+        self.add_candidates(&mut candidates, &live[world], &self.neighbor_manager);
+        for coupling in self.couplings.iter().filter(|c| c.target == world) {
+            self.add_candidates(&mut candidates, &live[coupling.source], &coupling.neighborhood);
+        }
+
+        let rule = self.world_rule(world);
+        let cells = self.world_backend(world);
+        let mut changes = Vec::new();
+        for (q, r, s) in candidates {
+            if !cells.contains(q, r, s) {
+                continue;
+            }
+            let current = cells.get_cell(q, r, s);
+            let next = if rule.species.is_some() {
+                let counts = self.count_world_species(world, q, r, s);
+                rule.next_state_species(current, &counts, self.coupled_sum(world, q, r, s))
+            } else if rule.weighted {
+                rule.next_state_weighted(current, self.weigh_world_neighbors(world, q, r, s))
+            } else {
+                rule.next_state(current, self.count_world_neighbors(world, q, r, s))
+            };
+            if next != current {
+                changes.push(CellChange { q, r, s, old: current, new: next });
+            }
+        }
+        changes
+    }
+
+    // Every cell of `live` and every cell that sees one through `neighborhood`
+    fn add_candidates(&self, candidates: &mut HashSet<(i32, i32, i32)>, live: &[i32], neighborhood: &Neighborhood) {
+        let layers = neighborhood.adj_neighbors.len();
+        for chunk in live.chunks(4) {
+            let (q, r, s) = (chunk[0], chunk[1], chunk[2]);
+            candidates.insert((q, r, s));
+
+            for (layer, offsets) in neighborhood.adj_neighbors.iter().enumerate() {
+                for &(dq, dr, ds) in offsets {
+                    let ns = s - ds;
+                    if layers > 1 && ns != layer as i32 {
+                        continue;
+                    }
+                    if let Some(cell) = self.topology.wrap(q - dq, r - dr, ns) {
+                        candidates.insert(cell);
+                    }
+                }
+            }
+        }
+        if live.is_empty() || !self.topology.reorients() {
+            return;
+        }
+
+        // Only cells within reach of an edge have offsets that cross a seam
+        let live: HashSet<(i32, i32, i32)> = live.chunks(4).map(|c| (c[0], c[1], c[2])).collect();
+        let reach = neighborhood.reach();
+        let [min_q, max_q, min_r, max_r, min_s, max_s] = self.get_bounds();
+        for s in min_s..=max_s {
+            for r in min_r..=max_r {
+                let columns: Vec<i32> = match r < min_r + reach || r > max_r - reach {
+                    true => (min_q..=max_q).collect(),
+                    false => (min_q..(min_q + reach).min(max_q + 1))
+                        .chain((max_q - reach + 1).max(min_q + reach)..=max_q)
+                        .collect(),
+                };
+                for q in columns {
+                    let sees_live = neighborhood.get_neighbor_offsets(s).iter().any(|&(dq, dr, ds)| {
+                        self.topology.wrap(q + dq, r + dr, s + ds).is_some_and(|cell| live.contains(&cell))
+                    });
+                    if sees_live {
+                        candidates.insert((q, r, s));
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_changes(&mut self, changes: &[CellChange]) {
+        for c in changes {
+            self.inner.set_cell(c.q, c.r, c.s, c.new);
+            self.hash.update(c.q, c.r, c.s, c.old, c.new);
+        }
+    }
+
+    // STATE HASHING & CYCLES
+    /// Zobrist hash of the current grid, kept up to date by every write.
+    pub fn state_hash(&self) -> u64 {
+        self.hash.zobrist
+    }
+
+    /// Whether the run has died out, frozen, or entered a (moving) cycle,
+    /// based on the hashes of the generations stepped since the last edit.
+    pub fn cycle_state(&self) -> CycleState {
+        self.hash_history.state()
+    }
+
+    pub fn set_hash_history_capacity(&mut self, capacity: usize) {
+        self.hash_history.set_capacity(capacity);
+    }
+
+    // STATISTICS
+    pub fn stats(&self) -> &StatsHistory {
+        &self.stats
+    }
+
+    pub fn latest_stats(&self) -> Option<&GenerationStats> {
+        self.stats.latest()
+    }
+
+    pub fn set_stats_capacity(&mut self, capacity: usize) {
+        self.stats.set_capacity(capacity);
+    }
+
+    /// Records stats (and the state hash) for the current state without
+    /// stepping, e.g. the starting generation of a run or after manual edits.
+    pub fn record_stats(&mut self) {
+        let live = self.for_each_cell();
+        let stats = GenerationStats::from_cells(
+            self.generation,
+            &live,
+            self.config.chunk_size,
+            self.config.depth,
+        );
+        self.record_hash(stats.bounds, stats.population);
+        self.stats.push(stats);
+    }
+
+    // With worlds the hash and population cover all of them, and shifted
+    // repeats are not looked for since each world would have to move alike
+    fn record_hash(&mut self, bounds: Option<[i32; 4]>, population: usize) {
+        if self.worlds.is_empty() {
+            self.hash_history.record(self.generation, self.hash, bounds, population);
+            return;
+        }
+        let mut hash = self.hash;
+        let mut population = population;
+        for (i, world) in self.worlds.iter().enumerate() {
+            hash.zobrist ^= world.hash.zobrist.rotate_left(i as u32 + 1);
+            population += world.cells.for_each_cell().len() / 4;
+        }
+        self.hash_history.record(self.generation, hash, None, population);
+    }
+
+    pub fn stats_csv(&self) -> String {
+        self.stats.to_csv()
+    }
+
+    // EDIT HISTORY
+    /// Groups every edit until the matching `commit_transaction` into one
+    /// undo step. Transactions nest; the outermost label is kept.
+    pub fn begin_transaction(&mut self, label: &str) {
+        self.history.begin(label);
+    }
+
+    pub fn commit_transaction(&mut self) {
+        self.history.commit();
+    }
+
+    /// Reverts the last transaction. Stepping and timeline jumps are not
+    /// recorded and clear the history, so only edits made since the last
+    /// step can be undone.
+    pub fn undo(&mut self) -> bool {
+        if self.history.in_transaction() {
+            return false;
+        }
+        let Some(transaction) = self.history.pop_undo() else {
+            return false;
         };
+        for c in transaction.changes.iter().rev() {
+            self.write_cell(c.q, c.r, c.s, c.old);
+        }
+        self.history.push_redo(transaction);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        if self.history.in_transaction() {
+            return false;
+        }
+        let Some(transaction) = self.history.pop_redo() else {
+            return false;
+        };
+        for c in &transaction.changes {
+            self.write_cell(c.q, c.r, c.s, c.new);
+        }
+        self.history.push_undo(transaction);
+        true
+    }
+
+    pub fn history(&self) -> &EditHistory {
+        &self.history
+    }
+
+    /// Memory budget of the undo/redo stacks in bytes; the oldest
+    /// transactions are dropped to stay under it.
+    pub fn set_history_budget(&mut self, bytes: usize) {
+        self.history.set_budget(bytes);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    // TIMELINE
+    /// Keyframe every `keyframe_interval` generations and keep the last
+    /// `retention` generations restorable; a retention of 0 (the default)
+    /// stops recording.
+    pub fn set_timeline(&mut self, keyframe_interval: u64, retention: u64) {
+        self.timeline.configure(keyframe_interval, retention);
+    }
+
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Restores a recorded generation from the nearest keyframe before it
+    /// plus the recorded deltas. Returns false outside the timeline range.
+    pub fn jump_to_generation(&mut self, generation: u64) -> bool {
+        match self.timeline.range() {
+            Some((first, last)) if (first..=last).contains(&generation) => {}
+            _ => return false,
+        }
+        let Some(keyframe) = self.timeline.keyframe_for(generation) else {
+            return false;
+        };
+
+        self.inner.clear();
+        for c in keyframe.cells.chunks(4) {
+            self.inner.set_cell(c[0], c[1], c[2], c[3] as u32);
+        }
+        for c in self.timeline.deltas_between(keyframe.generation, generation) {
+            self.inner.set_cell(c.q, c.r, c.s, c.new);
+        }
+
+        self.generation = generation;
+        self.hash = GridHash::from_cells(&self.inner.for_each_cell());
+        self.hash_history.invalidate();
+        self.stats.truncate_after(generation);
+        // Recorded edits belong to another generation
+        self.history.clear();
+        true
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        self.generation > 0 && self.jump_to_generation(self.generation - 1)
+    }
+
+    // RESIZING
+    pub fn resize(&mut self, new_width: usize, new_height: usize, new_depth: usize) {
+        let old_cells = self.inner.for_each_cell();
 
         self.config.width = new_width;
         self.config.height = new_height;
         self.config.depth = new_depth;
+        self.inner = self.resized_backend(&old_cells);
+        for i in 0..self.worlds.len() {
+            let cells = self.resized_backend(&self.worlds[i].cells.for_each_cell());
+            self.worlds[i].hash = GridHash::from_cells(&cells.for_each_cell());
+            self.worlds[i].cells = cells;
+        }
+        self.topology.set_bounds(self.get_bounds());
+        self.hash = GridHash::from_cells(&self.inner.for_each_cell());
+        self.hash_history.invalidate();
+        // Recorded coordinates may no longer exist
+        self.history.clear();
+        self.timeline.clear();
+        self.selection = None;
+    }
+
+    // Backend of the configured size holding `cells` (`for_each_cell` output)
+    fn resized_backend(&self, cells: &[i32]) -> CellBackend {
+        let mut backend = self.empty_backend();
+        for chunk in cells.chunks(4) {
+            if let &[q, r, s, v] = chunk {
+                backend.set_cell(q, r, s, v as u32);
+            }
+        }
+        backend
+    }
+
+    fn empty_backend(&self) -> CellBackend {
+        let CellConfig { width, height, depth, threshold, chunk_size, .. } = self.config;
+        if width > threshold || height > threshold {
+            CellBackend::Chunked(ChunkedCellManager::new(chunk_size, depth))
+        } else {
+            CellBackend::Flat(FlatCellManager::new(width, height, depth))
+        }
+    }
+
+    /// Empty manager of the given size with the same shape, neighborhood
+    /// and rule, no wrapping and no worlds. Used to evolve patterns in isolation.
+    /// `chunk_size` defaults to this manager's.
+    pub fn blank_copy(&self, width: usize, height: usize, chunk_size: Option<usize>) -> CellManager {
+        let chunk_size = chunk_size.unwrap_or(self.config.chunk_size);
+        let mut cm = CellManager::new(width, height, self.config.depth, Some(chunk_size));
+        cm.config.shape = self.config.shape.clone();
+        cm.config.neighbor_type = self.config.neighbor_type.clone();
+        cm.config.range = self.config.range;
+        cm.config.rule = self.config.rule.clone();
+        cm.config.kernel = self.config.kernel.clone();
+        cm.neighbor_manager = self.neighbor_manager.clone();
+        cm.rule = self.rule.clone();
+        cm
     }
 
     // BOUNDS
@@ -157,47 +692,33 @@ impl CellManager {
 
     // RANDOM FILL
     pub fn random_cells(&mut self) {
-        let [min_q, max_q, min_r, max_r, min_s, max_s] = self.get_bounds();
-        let density: f32 = 0.42;
+        let [min_q, max_q, min_r, max_r, _, _] = self.get_bounds();
+        self.history.begin("random_cells");
+        self.fill_random(&mut fastrand::Rng::new(), 0.42, [min_q, max_q, min_r, max_r]);
+        self.history.commit();
+    }
+
+    /// Reproducible random fill of `[min_q, max_q, min_r, max_r]` (every
+    /// layer): the same seed, density and region always give the same soup.
+    pub fn random_cells_seeded(&mut self, seed: u64, density: f32, region: [i32; 4]) {
+        self.history.begin("random_cells");
+        self.fill_random(&mut fastrand::Rng::with_seed(seed), density, region);
+        self.history.commit();
+    }
+
+    fn fill_random(&mut self, rng: &mut fastrand::Rng, density: f32, [min_q, max_q, min_r, max_r]: [i32; 4]) {
+        let [_, _, _, _, min_s, max_s] = self.get_bounds();
 
         for s in min_s..=max_s {
             for q in min_q..=max_q {
                 for r in min_r..=max_r {
-                    let status = if fastrand::f32() < density { 1 } else { 0 };
+                    let status = self.rule.random_state(rng, density);
                     self.set_cell(q, r, s, status);
                 }
             }
         }
     }
 
-    // FLOOD FILL
-    pub fn floodfill(&mut self) {
-        let arr = self.for_each_cell();
-        let mut neighbors_to_activate = Vec::new();
-
-        let mut i = 0;
-        while i + 3 < arr.len() {
-            let q = arr[i];
-            let r = arr[i + 1];
-            let s = arr[i + 2];
-            let state = arr[i + 3];
-
-            if state == 1 {
-                let nb_cells = self.get_neighbors(q, r, s);
-                let mut j = 0;
-                while j + 2 < nb_cells.len() {
-                    neighbors_to_activate.push((nb_cells[j], nb_cells[j + 1], nb_cells[j + 2]));
-                    j += 3;
-                }
-            }
-            i += 4;
-        }
-
-        for (nq, nr, ns) in neighbors_to_activate {
-            self.set_cell(nq, nr, ns, 1);
-        }
-    }
-
     // CHANGE GRID PROPERTIES
     pub fn change_grid_properties(
         &mut self,
@@ -206,11 +727,198 @@ impl CellManager {
         range: i32,
         topology_type: String
     ) {
+        if shape != self.config.shape {
+            self.selection = None;
+        }
         self.config.shape = shape.clone();
         self.config.range = range;
         self.config.neighbor_type = neighbor_type.clone();
         self.config.topology_type = topology_type.clone();
 
         self.neighbor_manager.change_cell_properties(&shape, &neighbor_type, range);
+        // Repeated offsets would count the same neighbor twice
+        self.neighbor_manager.dedup();
+        // Custom specs are stored without whitespace so they save in headers
+        self.config.neighbor_type = self.neighbor_manager.chosen_type.clone();
+        self.topology.set_type(&topology_type);
+        self.topology.set_volumetric(is_volumetric(&shape));
+        // Coupling offsets are read on the new shape as they were given
+        for coupling in &mut self.couplings {
+            let spec = coupling.neighborhood.chosen_type.clone();
+            coupling.neighborhood.change_cell_properties(&shape, &spec, range);
+        }
+        self.hash_history.invalidate();
+    }
+
+    // WORLDS
+    /// Adds an empty world on this grid running `rule`. Fails when the name
+    /// is taken (`main` is the grid itself) or the rule does not parse.
+    pub fn add_world(&mut self, name: &str, rule: &str) -> bool {
+        let Some(rule) = Rule::parse(rule) else { return false };
+        if name.is_empty() || self.world_index(name).is_some() {
+            return false;
+        }
+        self.worlds.push(World::new(name, rule, self.empty_backend()));
+        // The timeline only rewinds the main grid
+        self.timeline.clear();
+        self.hash_history.invalidate();
+        true
+    }
+
+    /// Removes a world and every coupling to or from it.
+    pub fn remove_world(&mut self, name: &str) -> bool {
+        let Some(index) = self.world_index(name).filter(|&i| i > 0) else { return false };
+        self.worlds.remove(index - 1);
+        self.couplings.retain(|c| c.target != index && c.source != index);
+        for coupling in &mut self.couplings {
+            coupling.target -= (coupling.target > index) as usize;
+            coupling.source -= (coupling.source > index) as usize;
+        }
+        self.timeline.invalidate();
+        self.hash_history.invalidate();
+        true
+    }
+
+    /// `main` followed by the added worlds.
+    pub fn world_names(&self) -> Vec<&str> {
+        let mut names = vec![MAIN_WORLD];
+        names.extend(self.worlds.iter().map(|w| w.name.as_str()));
+        names
+    }
+
+    /// Index used by [`Coupling`], 0 being `main`.
+    pub fn world_index(&self, name: &str) -> Option<usize> {
+        match name {
+            MAIN_WORLD => Some(0),
+            _ => self.worlds.iter().position(|w| w.name == name).map(|i| i + 1),
+        }
+    }
+
+    pub fn set_world_rule(&mut self, name: &str, rulestring: &str) -> bool {
+        match self.world_index(name) {
+            Some(0) => self.set_rule(rulestring),
+            Some(i) => match Rule::parse(rulestring) {
+                Some(rule) => {
+                    self.worlds[i - 1].rule = rule;
+                    self.hash_history.invalidate();
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    pub fn get_world_rule(&self, name: &str) -> Option<&Rule> {
+        self.world_index(name).map(|i| self.world_rule(i))
+    }
+
+    /// Sets a cell of a world; `main` goes through [`CellManager::set_cell`]
+    /// and its edit history, other worlds are not recorded.
+    pub fn set_world_cell(&mut self, name: &str, q: i32, r: i32, s: i32, value: u32) -> bool {
+        match self.world_index(name) {
+            Some(0) => self.set_cell(q, r, s, value),
+            Some(i) => {
+                self.worlds[i - 1].set_cell(q, r, s, value);
+                self.hash_history.invalidate();
+            }
+            None => return false,
+        }
+        true
+    }
+
+    pub fn get_world_cell(&self, name: &str, q: i32, r: i32, s: i32) -> Option<u32> {
+        self.world_index(name).map(|i| self.world_cell(i, q, r, s))
+    }
+
+    /// Live cells of a world, laid out like [`CellManager::for_each_cell`].
+    pub fn world_cells(&self, name: &str) -> Option<Vec<i32>> {
+        self.world_index(name).map(|i| self.world_backend(i).for_each_cell())
+    }
+
+    /// Reproducible random fill of a world, see [`CellManager::random_cells_seeded`].
+    pub fn random_world_cells_seeded(&mut self, name: &str, seed: u64, density: f32, region: [i32; 4]) -> bool {
+        match self.world_index(name) {
+            Some(0) => self.random_cells_seeded(seed, density, region),
+            Some(i) => {
+                let [min_q, max_q, min_r, max_r] = region;
+                let [_, _, _, _, min_s, max_s] = self.get_bounds();
+                let mut rng = fastrand::Rng::with_seed(seed);
+                let world = &mut self.worlds[i - 1];
+                for s in min_s..=max_s {
+                    for q in min_q..=max_q {
+                        for r in min_r..=max_r {
+                            let status = world.rule.random_state(&mut rng, density);
+                            world.set_cell(q, r, s, status);
+                        }
+                    }
+                }
+                self.hash_history.invalidate();
+            }
+            None => return false,
+        }
+        true
+    }
+
+    pub fn clear_world(&mut self, name: &str) -> bool {
+        match self.world_index(name) {
+            Some(0) => self.clear(),
+            Some(i) => {
+                let world = &mut self.worlds[i - 1];
+                world.cells.clear();
+                world.hash = GridHash::default();
+                self.hash_history.invalidate();
+            }
+            None => return false,
+        }
+        true
+    }
+
+    /// Lets the rule of world `target` count the live cells of world
+    /// `source` at the offsets of `neighbor_type` (a named kind or custom
+    /// spec for the current shape, see [`Neighborhood::parse`]), each
+    /// weighing `weight`. `offsets:0,0` reads the cell underneath.
+    pub fn couple(&mut self, target: &str, source: &str, neighbor_type: &str, weight: f32) -> Result<(), String> {
+        let index = |name: &str| self.world_index(name).ok_or_else(|| format!("no world named '{name}'"));
+        let (target, source) = (index(target)?, index(source)?);
+        if !weight.is_finite() {
+            return Err(format!("coupling weight {weight} is not a number"));
+        }
+        let mut neighborhood = Neighborhood::parse(&self.config.shape, neighbor_type, self.config.range)?;
+        neighborhood.dedup();
+        neighborhood.set_kernel(self.neighbor_manager.kernel);
+        self.couplings.push(Coupling { target, source, neighborhood, weight });
+        self.hash_history.invalidate();
+        Ok(())
+    }
+
+    pub fn couplings(&self) -> &[Coupling] {
+        &self.couplings
+    }
+
+    /// Drops the couplings into `target`.
+    pub fn clear_couplings(&mut self, target: &str) -> bool {
+        let Some(index) = self.world_index(target) else { return false };
+        self.couplings.retain(|c| c.target != index);
+        self.hash_history.invalidate();
+        true
+    }
+
+    fn world_backend(&self, world: usize) -> &CellBackend {
+        match world {
+            0 => &self.inner,
+            _ => &self.worlds[world - 1].cells,
+        }
+    }
+
+    fn world_cell(&self, world: usize, q: i32, r: i32, s: i32) -> u32 {
+        self.world_backend(world).get_cell(q, r, s)
+    }
+
+    fn world_rule(&self, world: usize) -> &Rule {
+        match world {
+            0 => &self.rule,
+            _ => &self.worlds[world - 1].rule,
+        }
     }
 }
@@ -4,6 +4,8 @@ pub struct ChunkedCellManager {
     chunk_size: usize,
     depth: usize,
     chunks: HashMap<(i32, i32, i32), Vec<u32>>,
+    // Live cells per chunk, so chunks that empty out can be dropped
+    live_counts: HashMap<(i32, i32, i32), usize>,
 }
 
 fn local_index(chunk_size: usize, lx: usize, ly: usize, lz: usize) -> usize {
@@ -16,6 +18,7 @@ impl ChunkedCellManager {
             chunk_size,
             depth,
             chunks: HashMap::new(),
+            live_counts: HashMap::new(),
         }
     }
 
@@ -48,7 +51,22 @@ impl ChunkedCellManager {
     pub fn set_cell(&mut self, q: i32, r: i32, s: i32, value: u32) {
         let ((cx, cy, cz), (lx, ly, lz)) = self.world_to_chunk_local(q, r, s);
         let idx = local_index(self.chunk_size, lx, ly, lz);
-        self.get_chunk_mut(cx, cy, cz)[idx] = value;
+        if value == 0 && !self.chunks.contains_key(&(cx, cy, cz)) {
+            return;
+        }
+
+        let cell = &mut self.get_chunk_mut(cx, cy, cz)[idx];
+        let old = std::mem::replace(cell, value);
+        let count = self.live_counts.entry((cx, cy, cz)).or_insert(0);
+        match (old, value) {
+            (0, v) if v != 0 => *count += 1,
+            (o, 0) if o != 0 => *count -= 1,
+            _ => {}
+        }
+        if *count == 0 {
+            self.chunks.remove(&(cx, cy, cz));
+            self.live_counts.remove(&(cx, cy, cz));
+        }
     }
 
     pub fn get_cell(&self, q: i32, r: i32, s: i32) -> u32 {
@@ -62,6 +80,7 @@ impl ChunkedCellManager {
 
     pub fn clear(&mut self) {
         self.chunks.clear();
+        self.live_counts.clear();
     }
 
     pub fn for_each_cell(&self) -> Vec<i32> {
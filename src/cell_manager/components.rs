@@ -0,0 +1,236 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::CellManager;
+
+// Most cells an empty (state 0) region is searched over. Grids up to this
+// size search their whole extent, larger ones (chunked grids, the census
+// plane) only the live cells' bounding box plus a margin.
+const EMPTY_REGION_LIMIT: usize = 1 << 22;
+
+/// One connected region of equal cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Component {
+    /// Label in the [`ComponentMap`], starting at 1
+    pub id: u32,
+    /// State of the region's first cell (regions of mixed live states are
+    /// possible when every live cell was labelled)
+    pub state: u32,
+    pub size: usize,
+    /// `[min_q, max_q, min_r, max_r]` of the cells (in wrapped coordinates)
+    pub bounds: [i32; 4],
+}
+
+/// Result of [`CellManager::label_components`]: a label for every cell
+/// that belongs to a component, plus a summary per component.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentMap {
+    pub labels: HashMap<(i32, i32, i32), u32>,
+    /// Indexed by `id - 1`, ordered by each component's smallest cell
+    pub components: Vec<Component>,
+}
+
+impl ComponentMap {
+    pub fn label(&self, q: i32, r: i32, s: i32) -> Option<u32> {
+        self.labels.get(&(q, r, s)).copied()
+    }
+
+    pub fn component(&self, id: u32) -> Option<&Component> {
+        self.components.get((id as usize).checked_sub(1)?)
+    }
+
+    /// Cells of component `id`, sorted.
+    pub fn cells(&self, id: u32) -> Vec<(i32, i32, i32)> {
+        let mut cells: Vec<_> = self
+            .labels
+            .iter()
+            .filter(|&(_, &label)| label == id)
+            .map(|(&cell, _)| cell)
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// `[q, r, s, id]` per labelled cell, the same layout as `for_each_cell`.
+    pub fn to_flat(&self) -> Vec<i32> {
+        let mut cells: Vec<_> = self.labels.iter().collect();
+        cells.sort_unstable();
+        cells
+            .into_iter()
+            .flat_map(|(&(q, r, s), &id)| [q, r, s, id as i32])
+            .collect()
+    }
+}
+
+impl CellManager {
+    /// Cells this cell sees and cells that see it under the current
+    /// neighborhood, after topology wrapping. Two cells are connected when
+    /// either one is in the other's neighborhood.
+    pub fn adjacent_cells(&self, q: i32, r: i32, s: i32) -> Vec<(i32, i32, i32)> {
+        let neighborhood = self.neighborhood();
+        let layers = neighborhood.adj_neighbors.len();
+        let mut adjacent = Vec::new();
+
+        for &(dq, dr, ds) in neighborhood.get_neighbor_offsets(s) {
+            adjacent.extend(self.wrap_cell(q + dq, r + dr, s + ds));
+        }
+        for (layer, offsets) in neighborhood.adj_neighbors.iter().enumerate() {
+            for &(dq, dr, ds) in offsets {
+                if layers > 1 && s - ds != layer as i32 {
+                    continue;
+                }
+                adjacent.extend(self.wrap_cell(q - dq, r - dr, s - ds));
+            }
+        }
+        adjacent.retain(|&cell| cell != (q, r, s));
+        adjacent.sort_unstable();
+        adjacent.dedup();
+        adjacent
+    }
+
+    /// Labels the connected regions of cells in `state`, or of every live
+    /// cell (whatever its state) when `state` is `None`. Regions of state 0
+    /// are limited to [`CellManager::empty_region`]; fails when that is too
+    /// large to search.
+    pub fn label_components(&self, state: Option<u32>) -> Result<ComponentMap, String> {
+        let region = match state {
+            Some(0) => Some(self.empty_region(None)?),
+            _ => None,
+        };
+        let mut seeds: Vec<(i32, i32, i32)> = if let Some([min_q, max_q, min_r, max_r, min_s, max_s]) = region {
+            (min_s..=max_s)
+                .flat_map(|s| (min_r..=max_r).flat_map(move |r| (min_q..=max_q).map(move |q| (q, r, s))))
+                .filter(|&(q, r, s)| self.get_cell(q, r, s) == 0)
+                .collect()
+        } else {
+            self.for_each_cell()
+                .chunks(4)
+                .filter(|c| state.is_none_or(|v| c[3] as u32 == v))
+                .map(|c| (c[0], c[1], c[2]))
+                .collect()
+        };
+        seeds.sort_unstable();
+
+        let mut map = ComponentMap::default();
+        for seed in seeds {
+            if map.labels.contains_key(&seed) {
+                continue;
+            }
+            let id = map.components.len() as u32 + 1;
+            let cells = self.collect_region(seed, state, region, &mut map.labels, id);
+
+            let mut bounds = [seed.0, seed.0, seed.1, seed.1];
+            for &(q, r, _) in &cells {
+                bounds = [bounds[0].min(q), bounds[1].max(q), bounds[2].min(r), bounds[3].max(r)];
+            }
+            map.components.push(Component {
+                id,
+                state: state.unwrap_or_else(|| self.get_cell(seed.0, seed.1, seed.2)),
+                size: cells.len(),
+                bounds,
+            });
+        }
+        Ok(map)
+    }
+
+    /// Cells connected to `(q, r, s)` that share its state, the seed
+    /// included. Empty regions are limited to [`CellManager::empty_region`].
+    pub fn component_at(&self, q: i32, r: i32, s: i32) -> Result<Vec<(i32, i32, i32)>, String> {
+        let Some(seed) = self.wrap_cell(q, r, s) else {
+            return Ok(Vec::new());
+        };
+        let state = self.get_cell(seed.0, seed.1, seed.2);
+        let region = match state {
+            0 => Some(self.empty_region(Some(seed))?),
+            _ => None,
+        };
+        let mut cells = self.collect_region(seed, Some(state), region, &mut HashMap::new(), 1);
+        cells.sort_unstable();
+        Ok(cells)
+    }
+
+    /// Bucket fill: sets the whole region containing `(q, r, s)` to `value`,
+    /// following the neighborhood and topology. Returns the cells changed.
+    pub fn bucket_fill(&mut self, q: i32, r: i32, s: i32, value: u32) -> Result<usize, String> {
+        let Some((q, r, s)) = self.wrap_cell(q, r, s) else {
+            return Ok(0);
+        };
+        if self.get_cell(q, r, s) == value {
+            return Ok(0);
+        }
+        let cells = self.component_at(q, r, s)?;
+        let changed = cells.len();
+        self.begin_transaction("bucket_fill");
+        self.batch_update(cells.into_iter().map(|(q, r, s)| (q, r, s, value)).collect(), None);
+        self.commit_transaction();
+        Ok(changed)
+    }
+
+    /// `[min_q, max_q, min_r, max_r, min_s, max_s]` searched for empty
+    /// regions: the whole grid when it has at most 4M cells, otherwise the
+    /// bounding box of the live cells and `seed`, grown by the neighborhood
+    /// reach and clipped to the grid. Fails when even that is too large.
+    pub fn empty_region(&self, seed: Option<(i32, i32, i32)>) -> Result<[i32; 6], String> {
+        let bounds = self.get_bounds();
+        let [min_q, max_q, min_r, max_r, min_s, max_s] = bounds;
+        let area = |[a, b, c, d]: [i32; 4]| (b - a + 1).max(0) as usize * (d - c + 1).max(0) as usize;
+        let layers = (max_s - min_s + 1).max(1) as usize;
+        if area([min_q, max_q, min_r, max_r]).saturating_mul(layers) <= EMPTY_REGION_LIMIT {
+            return Ok(bounds);
+        }
+
+        let live = self.for_each_cell();
+        let cells = live.chunks(4).map(|c| (c[0], c[1])).chain(seed.map(|(q, r, _)| (q, r)));
+        let Some(live_box) = cells.fold(None, |acc: Option<[i32; 4]>, (q, r)| {
+            let [a, b, c, d] = acc.unwrap_or([q, q, r, r]);
+            Some([a.min(q), b.max(q), c.min(r), d.max(r)])
+        }) else {
+            return Ok([0, -1, 0, -1, min_s, max_s]);
+        };
+        let reach = self.neighborhood().reach().max(1);
+        let region = [
+            (live_box[0] - reach).max(min_q),
+            (live_box[1] + reach).min(max_q),
+            (live_box[2] - reach).max(min_r),
+            (live_box[3] + reach).min(max_r),
+        ];
+        let cells = area(region).saturating_mul(layers);
+        if cells > EMPTY_REGION_LIMIT {
+            return Err(format!("empty region spans {cells} cells, more than the {EMPTY_REGION_LIMIT} searched"));
+        }
+        Ok([region[0], region[1], region[2], region[3], min_s, max_s])
+    }
+
+    // Breadth-first walk from `seed` over cells matching `state`, labelling them `id`
+    fn collect_region(
+        &self,
+        seed: (i32, i32, i32),
+        state: Option<u32>,
+        region: Option<[i32; 6]>,
+        labels: &mut HashMap<(i32, i32, i32), u32>,
+        id: u32,
+    ) -> Vec<(i32, i32, i32)> {
+        let [min_q, max_q, min_r, max_r, _, _] = region.unwrap_or_else(|| self.get_bounds());
+        let matches = |(q, r, s): (i32, i32, i32)| {
+            let v = self.get_cell(q, r, s);
+            match state {
+                Some(0) => v == 0 && (min_q..=max_q).contains(&q) && (min_r..=max_r).contains(&r),
+                Some(target) => v == target,
+                None => v != 0,
+            }
+        };
+
+        let mut cells = Vec::new();
+        let mut queue = VecDeque::from([seed]);
+        labels.insert(seed, id);
+        while let Some(cell) = queue.pop_front() {
+            cells.push(cell);
+            for next in self.adjacent_cells(cell.0, cell.1, cell.2) {
+                if !labels.contains_key(&next) && matches(next) {
+                    labels.insert(next, id);
+                    queue.push_back(next);
+                }
+            }
+        }
+        cells
+    }
+}
@@ -0,0 +1,243 @@
+// Conversions between cell coordinates of the different shapes and world
+// (pixel) space. World space uses a cell size of 1, which is the
+// `cellSize`/`radius` of `grids/tiles/*.js`: scale by it on the way in and out.
+//
+// Layouts match the tiles: squares are centred on integer points, hexagons
+// are pointy-topped with corners at distance 1, triangles split each unit
+// square along its `x = y` diagonal (`s = 1` above it), and rhombi split
+// each hexagon into three 120° sectors starting at -30°. The periodic
+// tilings of `tilings` (4.8.8, 3.6.3.6, snub square, Cairo) use their own
+// unit cells. Cubes are squares stacked along `s`, which is a real z axis
+// rather than a tile layer; in the plane they draw as squares.
+
+use crate::cell_manager::tilings::tiling;
+
+pub const SQRT_3: f32 = 1.732_050_8;
+
+type Cell = (i32, i32, i32);
+
+/// Hex offset layouts: which rows (`R`) or columns (`Q`) are pushed out
+/// by half a cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OffsetKind {
+    OddR,
+    EvenR,
+    OddQ,
+    EvenQ,
+}
+
+impl OffsetKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_ascii_lowercase().replace('_', "-").as_str() {
+            "odd-r" => Some(OffsetKind::OddR),
+            "even-r" => Some(OffsetKind::EvenR),
+            "odd-q" => Some(OffsetKind::OddQ),
+            "even-q" => Some(OffsetKind::EvenQ),
+            _ => None,
+        }
+    }
+}
+
+/// Doubled hex layouts: `Width` doubles columns (pointy-top, what the TUI
+/// draws), `Height` doubles rows (flat-top).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DoubledKind {
+    Width,
+    Height,
+}
+
+impl DoubledKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_ascii_lowercase().as_str() {
+            "width" | "doubled-width" => Some(DoubledKind::Width),
+            "height" | "doubled-height" => Some(DoubledKind::Height),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `s` is a spatial axis (`cube`) rather than a tile layer.
+pub fn is_volumetric(shape: &str) -> bool {
+    shape == "cube"
+}
+
+/// Tile layers (`s` values) per cell position of a shape. Volumetric shapes
+/// have one: their neighborhoods are the same on every `s`.
+pub fn shape_layers(shape: &str) -> i32 {
+    match shape {
+        "triangle" => 2,
+        "rhombus" => 3,
+        _ => tiling(shape).map_or(1, |t| t.layers()),
+    }
+}
+
+// HEX COORDINATES
+/// Cube coordinates `(x, y, z)` of axial `(q, r)`, with `x + y + z = 0`.
+pub fn axial_to_cube(q: i32, r: i32) -> (i32, i32, i32) {
+    (q, -q - r, r)
+}
+
+pub fn cube_to_axial(x: i32, _y: i32, z: i32) -> (i32, i32) {
+    (x, z)
+}
+
+pub fn axial_to_offset(q: i32, r: i32, kind: OffsetKind) -> (i32, i32) {
+    match kind {
+        OffsetKind::OddR => (q + (r - (r & 1)) / 2, r),
+        OffsetKind::EvenR => (q + (r + (r & 1)) / 2, r),
+        OffsetKind::OddQ => (q, r + (q - (q & 1)) / 2),
+        OffsetKind::EvenQ => (q, r + (q + (q & 1)) / 2),
+    }
+}
+
+pub fn offset_to_axial(col: i32, row: i32, kind: OffsetKind) -> (i32, i32) {
+    match kind {
+        OffsetKind::OddR => (col - (row - (row & 1)) / 2, row),
+        OffsetKind::EvenR => (col - (row + (row & 1)) / 2, row),
+        OffsetKind::OddQ => (col, row - (col - (col & 1)) / 2),
+        OffsetKind::EvenQ => (col, row - (col + (col & 1)) / 2),
+    }
+}
+
+pub fn axial_to_doubled(q: i32, r: i32, kind: DoubledKind) -> (i32, i32) {
+    match kind {
+        DoubledKind::Width => (2 * q + r, r),
+        DoubledKind::Height => (q, 2 * r + q),
+    }
+}
+
+/// Inverse of [`axial_to_doubled`]; `col + row` must be even.
+pub fn doubled_to_axial(col: i32, row: i32, kind: DoubledKind) -> (i32, i32) {
+    match kind {
+        DoubledKind::Width => ((col - row) / 2, row),
+        DoubledKind::Height => (col, (row - col) / 2),
+    }
+}
+
+/// Steps between two hexes `(dq, dr)` apart.
+pub fn hex_distance(dq: i32, dr: i32) -> i32 {
+    (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+}
+
+/// Nearest hex to fractional axial coordinates.
+pub fn hex_round(q: f32, r: f32) -> (i32, i32) {
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    (rq as i32, rr as i32)
+}
+
+// WORLD SPACE
+/// Centre of a cell in world space (the centroid for triangles and rhombi).
+pub fn cell_center(shape: &str, q: i32, r: i32, s: i32) -> (f32, f32) {
+    let (qf, rf) = (q as f32, r as f32);
+    match shape {
+        "hexagon" => hex_center(qf, rf),
+        "rhombus" => {
+            let (x, y) = hex_center(qf, rf);
+            let (dx, dy) = [(SQRT_3 / 4.0, 0.25), (-SQRT_3 / 4.0, 0.25), (0.0, -0.5)][s.rem_euclid(3) as usize];
+            (x + dx, y + dy)
+        }
+        "triangle" if s == 0 => (qf + 1.0 / 3.0, rf + 2.0 / 3.0),
+        "triangle" => (qf + 2.0 / 3.0, rf + 1.0 / 3.0),
+        _ => tiling(shape).map_or((qf, rf), |t| t.center(q, r, s)),
+    }
+}
+
+/// Corners of a cell in world space, in drawing order.
+pub fn cell_polygon(shape: &str, q: i32, r: i32, s: i32) -> Vec<(f32, f32)> {
+    let (qf, rf) = (q as f32, r as f32);
+    match shape {
+        "hexagon" => {
+            let center = hex_center(qf, rf);
+            (0..6).map(|i| hex_corner(center, 60 * i - 30)).collect()
+        }
+        "rhombus" => {
+            let center = hex_center(qf, rf);
+            let start = 120 * s.rem_euclid(3) - 30;
+            vec![center, hex_corner(center, start), hex_corner(center, start + 60), hex_corner(center, start + 120)]
+        }
+        "triangle" => triangle_vertices(q, r, s).iter().map(|&(x, y)| (x as f32, y as f32)).collect(),
+        _ if let Some(t) = tiling(shape) => t.polygon(q, r, s),
+        _ => vec![(qf - 0.5, rf - 0.5), (qf + 0.5, rf - 0.5), (qf + 0.5, rf + 0.5), (qf - 0.5, rf + 0.5)],
+    }
+}
+
+/// Corners of a triangle, which all lie on integer points.
+pub fn triangle_vertices(q: i32, r: i32, s: i32) -> [(i32, i32); 3] {
+    if s == 0 {
+        [(q, r), (q, r + 1), (q + 1, r + 1)]
+    } else {
+        [(q, r), (q + 1, r), (q + 1, r + 1)]
+    }
+}
+
+/// Fractional grid coordinates of a world point: axial for hexagons and
+/// rhombi, unit cells for the `tilings`, the world point itself for squares
+/// and triangles.
+pub fn pixel_to_fractional(shape: &str, x: f32, y: f32) -> (f32, f32) {
+    match shape {
+        "hexagon" | "rhombus" => (SQRT_3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+        _ if let Some(t) = tiling(shape) => t.fractional(x, y),
+        _ => (x, y),
+    }
+}
+
+/// Cell containing fractional grid coordinates (see [`pixel_to_fractional`]).
+pub fn round_cell(shape: &str, q: f32, r: f32) -> Cell {
+    match shape {
+        "hexagon" => {
+            let (q, r) = hex_round(q, r);
+            (q, r, 0)
+        }
+        "rhombus" => {
+            let (hq, hr) = hex_round(q, r);
+            let (dq, dr) = (q - hq as f32, r - hr as f32);
+            let (lx, ly) = (SQRT_3 * (dq + dr * 0.5), 1.5 * dr);
+            // Sectors of 120° starting at -30°, matching getRhombusIndex
+            let angle = (ly.atan2(lx).to_degrees() + 30.0).rem_euclid(360.0);
+            (hq, hr, (angle / 120.0) as i32 % 3)
+        }
+        "triangle" => {
+            let (fq, fr) = (q.floor(), r.floor());
+            let s = if r - fr < q - fq { 1 } else { 0 };
+            (fq as i32, fr as i32, s)
+        }
+        _ if let Some(t) = tiling(shape) => t.pixel_to_cell(q * t.a.0 + r * t.b.0, q * t.a.1 + r * t.b.1),
+        _ => (q.round() as i32, r.round() as i32, 0),
+    }
+}
+
+/// Cell under a world point, the same picking as `worldToCell` in
+/// `grids/tiles/*.js` with a cell size of 1.
+pub fn pixel_to_cell(shape: &str, x: f32, y: f32) -> Cell {
+    let (q, r) = pixel_to_fractional(shape, x, y);
+    round_cell(shape, q, r)
+}
+
+/// Whether a world point lies inside a polygon (even-odd rule).
+pub fn polygon_contains(points: &[(f32, f32)], (x, y): (f32, f32)) -> bool {
+    let mut inside = false;
+    for (i, &(x1, y1)) in points.iter().enumerate() {
+        let (x2, y2) = points[(i + 1) % points.len()];
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn hex_center(q: f32, r: f32) -> (f32, f32) {
+    (SQRT_3 * (q + r * 0.5), 1.5 * r)
+}
+
+fn hex_corner((x, y): (f32, f32), degrees: i32) -> (f32, f32) {
+    let angle = (degrees as f32).to_radians();
+    (x + angle.cos(), y + angle.sin())
+}
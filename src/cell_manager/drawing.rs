@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use crate::CellManager;
+use crate::cell_manager::coords::{
+    SQRT_3, cell_center, hex_distance, hex_round, pixel_to_cell, polygon_contains, shape_layers,
+};
+use crate::cell_manager::tilings::tiling;
+
+type Cell = (i32, i32, i32);
+
+// Samples per world unit when walking a line across tilings
+const WALK_SAMPLES: f32 = 16.0;
+
+/// Cells on the line from `from` to `to`, both included, in walking order.
+///
+/// Squares use Bresenham, hexagons a lerp in cube coordinates, and
+/// triangles, rhombi and the `tilings` walk the straight segment between
+/// the two cell centres, listing every cell it passes through. Only the
+/// tilings sample the segment, so there a cell clipped by a sliver may be
+/// missed; elsewhere consecutive cells always share an edge.
+pub fn line_cells(shape: &str, from: Cell, to: Cell) -> Vec<Cell> {
+    let mut cells = match shape {
+        "hexagon" => hex_line(from, to),
+        "square" => bresenham(from, to),
+        _ => {
+            let a = cell_center(shape, from.0, from.1, from.2);
+            let b = cell_center(shape, to.0, to.1, to.2);
+            let mut cells = match shape {
+                "triangle" => triangle_walk(a, b),
+                "rhombus" => rhombus_walk(a, b),
+                _ => sampled_walk(shape, a, b),
+            };
+            // Guard against rounding at the ends
+            cells.insert(0, from);
+            cells.push(to);
+            cells
+        }
+    };
+    cells.dedup();
+    cells
+}
+
+/// Cells of a circle around `center`: within `radius` hex steps on
+/// hexagons, otherwise with their centre within `radius + 0.5` world units
+/// (cell size 1) of the centre cell's. Radius 0 is the centre cell alone.
+/// `filled` false keeps only the cells with an edge on the outside.
+pub fn circle_cells(shape: &str, center: Cell, radius: i32, filled: bool) -> Vec<Cell> {
+    if radius <= 0 {
+        return vec![center];
+    }
+    let (cx, cy) = cell_center(shape, center.0, center.1, center.2);
+    let limit = (radius as f32 + 0.5).powi(2);
+    let inside = |&(q, r, s): &Cell| match shape {
+        "hexagon" => hex_distance(q - center.0, r - center.1) <= radius,
+        _ => {
+            let (x, y) = cell_center(shape, q, r, s);
+            (x - cx).powi(2) + (y - cy).powi(2) <= limit
+        }
+    };
+
+    let span = radius + 2;
+    let disk: HashSet<Cell> = (center.1 - span..=center.1 + span)
+        .flat_map(|r| (center.0 - span..=center.0 + span).map(move |q| (q, r)))
+        .flat_map(|(q, r)| (0..shape_layers(shape)).map(move |s| (q, r, s)))
+        .filter(inside)
+        .collect();
+    sorted(outline(shape, disk, filled))
+}
+
+/// Cells of the polygon through the given vertex cells. The outline joins
+/// consecutive vertices (and the last to the first) with [`line_cells`];
+/// `filled` adds every cell whose centre lies inside.
+pub fn polygon_cells(shape: &str, vertices: &[Cell], filled: bool) -> Vec<Cell> {
+    let mut cells: HashSet<Cell> = HashSet::new();
+    for (i, &from) in vertices.iter().enumerate() {
+        cells.extend(line_cells(shape, from, vertices[(i + 1) % vertices.len()]));
+    }
+
+    if filled && vertices.len() >= 3 {
+        let points: Vec<(f32, f32)> = vertices.iter().map(|&(q, r, s)| cell_center(shape, q, r, s)).collect();
+        let min_q = vertices.iter().map(|v| v.0).min().unwrap_or(0);
+        let max_q = vertices.iter().map(|v| v.0).max().unwrap_or(0);
+        let min_r = vertices.iter().map(|v| v.1).min().unwrap_or(0);
+        let max_r = vertices.iter().map(|v| v.1).max().unwrap_or(0);
+        cells.extend(
+            (min_r..=max_r)
+                .flat_map(|r| (min_q..=max_q).map(move |q| (q, r)))
+                .flat_map(|(q, r)| (0..shape_layers(shape)).map(move |s| (q, r, s)))
+                .filter(|&(q, r, s)| polygon_contains(&points, cell_center(shape, q, r, s))),
+        );
+    }
+    sorted(cells)
+}
+
+impl CellManager {
+    /// Draws a line in `state`; returns the number of cells changed.
+    pub fn draw_line(&mut self, from: Cell, to: Cell, state: u32) -> usize {
+        let cells = line_cells(&self.config.shape, from, to);
+        self.draw_cells("line", cells, state)
+    }
+
+    /// Draws a circle in `state`, see [`circle_cells`].
+    pub fn draw_circle(&mut self, center: Cell, radius: i32, filled: bool, state: u32) -> usize {
+        let cells = circle_cells(&self.config.shape, center, radius, filled);
+        self.draw_cells("circle", cells, state)
+    }
+
+    /// Draws a polygon in `state`, see [`polygon_cells`].
+    pub fn draw_polygon(&mut self, vertices: &[Cell], filled: bool, state: u32) -> usize {
+        let cells = polygon_cells(&self.config.shape, vertices, filled);
+        self.draw_cells("polygon", cells, state)
+    }
+
+    // Writes the cells that exist after wrapping as one transaction
+    fn draw_cells(&mut self, label: &str, cells: Vec<Cell>, state: u32) -> usize {
+        let writes: HashSet<Cell> = cells
+            .into_iter()
+            .filter_map(|(q, r, s)| self.wrap_cell(q, r, s))
+            .filter(|&(q, r, s)| self.get_cell(q, r, s) != state)
+            .collect();
+        let changed = writes.len();
+        self.begin_transaction(label);
+        self.batch_update(writes.into_iter().map(|(q, r, s)| (q, r, s, state)).collect(), None);
+        self.commit_transaction();
+        changed
+    }
+}
+
+fn sorted(cells: HashSet<Cell>) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = cells.into_iter().collect();
+    cells.sort_unstable();
+    cells
+}
+
+// Cells sharing an edge with `cell`
+fn edge_neighbors(shape: &str, (q, r, s): Cell) -> Vec<Cell> {
+    match shape {
+        "hexagon" => [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)]
+            .iter()
+            .map(|&(dq, dr)| (q + dq, r + dr, s))
+            .collect(),
+        "triangle" if s == 0 => vec![(q, r, 1), (q - 1, r, 1), (q, r + 1, 1)],
+        "triangle" => vec![(q, r, 0), (q + 1, r, 0), (q, r - 1, 0)],
+        "rhombus" => {
+            let others = [(q, r, (s + 1) % 3), (q, r, (s + 2) % 3)];
+            let outer = match s {
+                0 => [(q + 1, r, 1), (q, r + 1, 2)],
+                1 => [(q - 1, r + 1, 2), (q - 1, r, 0)],
+                _ => [(q, r - 1, 0), (q + 1, r - 1, 1)],
+            };
+            others.into_iter().chain(outer).collect()
+        }
+        _ if let Some(t) = tiling(shape) => {
+            t.edge_offsets(s).iter().map(|&(dq, dr, ds)| (q + dq, r + dr, s + ds)).collect()
+        }
+        _ => vec![(q + 1, r, s), (q - 1, r, s), (q, r + 1, s), (q, r - 1, s)],
+    }
+}
+
+fn outline(shape: &str, disk: HashSet<Cell>, filled: bool) -> HashSet<Cell> {
+    if filled {
+        return disk;
+    }
+    disk.iter()
+        .filter(|&&cell| edge_neighbors(shape, cell).iter().any(|n| !disk.contains(n)))
+        .copied()
+        .collect()
+}
+
+fn bresenham((q0, r0, s): Cell, (q1, r1, _): Cell) -> Vec<Cell> {
+    let (dq, dr) = ((q1 - q0).abs(), -(r1 - r0).abs());
+    let (step_q, step_r) = ((q1 - q0).signum(), (r1 - r0).signum());
+    let (mut q, mut r, mut err) = (q0, r0, dq + dr);
+    let mut cells = vec![(q, r, s)];
+    while (q, r) != (q1, r1) {
+        let e2 = 2 * err;
+        if e2 >= dr {
+            err += dr;
+            q += step_q;
+        }
+        if e2 <= dq {
+            err += dq;
+            r += step_r;
+        }
+        cells.push((q, r, s));
+    }
+    cells
+}
+
+fn hex_line((q0, r0, s): Cell, (q1, r1, _): Cell) -> Vec<Cell> {
+    let n = hex_distance(q1 - q0, r1 - r0).max(1);
+    // Nudged off the exact cube centre so ties between two hexes always
+    // break the same way
+    let (q0, r0) = (q0 as f32 + 1e-4, r0 as f32 + 1e-4);
+    (0..=n)
+        .map(|i| {
+            let t = i as f32 / n as f32;
+            let (q, r) = hex_round(q0 + (q1 as f32 - q0) * t, r0 + (r1 as f32 - r0) * t);
+            (q, r, s)
+        })
+        .collect()
+}
+
+// Triangle edges lie on the lines x = k, y = k and x - y = k. The segment
+// is cut wherever it crosses one, and each piece names one cell. Nudging
+// the segment keeps it off vertices, so consecutive cells share an edge;
+// the nudge is at an irrational slope so that no segment runs along it.
+fn triangle_walk(a: (f32, f32), b: (f32, f32)) -> Vec<Cell> {
+    let (nx, ny) = (std::f64::consts::SQRT_2 * 1e-6, 1e-6);
+    let (x0, y0) = (a.0 as f64 + nx, a.1 as f64 + ny);
+    let (x1, y1) = (b.0 as f64 + nx, b.1 as f64 + ny);
+    let mut cuts = vec![0.0, 1.0];
+    for (a, b) in [(x0, x1), (y0, y1), (x0 - y0, x1 - y1)] {
+        let (lo, hi) = (a.min(b), a.max(b));
+        for k in (lo.floor() as i32 + 1)..=(hi.ceil() as i32 - 1) {
+            cuts.push((k as f64 - a) / (b - a));
+        }
+    }
+    cuts.sort_by(f64::total_cmp);
+
+    cuts.windows(2)
+        .filter(|w| w[1] > w[0])
+        .map(|w| {
+            let t = (w[0] + w[1]) / 2.0;
+            let (x, y) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+            let (q, r) = (x.floor(), y.floor());
+            let s = if y - r < x - q { 1 } else { 0 };
+            (q as i32, r as i32, s)
+        })
+        .collect()
+}
+
+// Each rhombus is two triangles of a lattice with unit sides, so the walk
+// is `triangle_walk` in that lattice's coordinates, naming the rhombus
+// around each triangle's centre. Triangles of one rhombus are adjacent on
+// a straight segment and `line_cells` merges them.
+fn rhombus_walk(a: (f32, f32), b: (f32, f32)) -> Vec<Cell> {
+    let to_lattice = |(x, y): (f32, f32)| (y + x / SQRT_3, y - x / SQRT_3);
+    triangle_walk(to_lattice(a), to_lattice(b))
+        .into_iter()
+        .map(|(q, r, s)| {
+            let (u, v) = cell_center("triangle", q, r, s);
+            pixel_to_cell("rhombus", (u - v) * SQRT_3 / 2.0, (u + v) / 2.0)
+        })
+        .collect()
+}
+
+// Tiling edges follow no common lattice, so the segment is sampled finely
+// instead
+fn sampled_walk(shape: &str, (x0, y0): (f32, f32), (x1, y1): (f32, f32)) -> Vec<Cell> {
+    let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+    let samples = (length * WALK_SAMPLES).ceil().max(1.0) as i32;
+    let mut cells: Vec<Cell> = (0..=samples)
+        .map(|i| {
+            let t = i as f32 / samples as f32;
+            pixel_to_cell(shape, x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+        })
+        .collect();
+    cells.dedup();
+    cells
+}
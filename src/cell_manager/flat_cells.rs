@@ -34,7 +34,7 @@ impl FlatCellManager {
         let origin = (
             (width as i32) / 2,
             (height as i32) / 2,
-            (depth as i32) * 0,
+            0,
         );
         FlatCellManager {
             width,
@@ -51,6 +51,10 @@ impl FlatCellManager {
         }
     }
 
+    pub fn contains(&self, q: i32, r: i32, s: i32) -> bool {
+        self.index_internal(q, r, s).is_some()
+    }
+
     pub fn get_cell(&self, q: i32, r: i32, s: i32) -> u32 {
         self.index_internal(q, r, s).map(|i| self.cells[i]).unwrap_or(0)
     }
@@ -82,7 +86,7 @@ impl FlatCellManager {
         let new_origin = (
             (new_width as i32) / 2,
             (new_height as i32) / 2,
-            (new_depth as i32) * 0,
+            0,
         );
 
         let mut new_cells = vec![0u32; new_width * new_height * new_depth];
@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::CellManager;
+use crate::cell_manager::coords::{SQRT_3, pixel_to_cell};
+use crate::cell_manager::tilings::tiling;
+
+/// Which part of the grid ends up in the image.
+#[derive(Clone, Debug)]
+pub enum GifCrop {
+    /// The whole grid as reported by `get_bounds`
+    Full,
+    /// A fixed cell rectangle `[min_q, max_q, min_r, max_r]`
+    Region([i32; 4]),
+    /// The bounding box of every live cell seen during the run, plus a margin
+    LiveBounds(i32),
+}
+
+#[derive(Clone, Debug)]
+pub struct GifOptions {
+    /// Number of generations to step after the initial frame
+    pub generations: usize,
+    /// Delay between frames, in hundredths of a second
+    pub frame_delay: u16,
+    /// Generations skipped between two recorded frames (0 keeps every one)
+    pub frame_skip: usize,
+    /// Pixels per cell unit
+    pub cell_size: usize,
+    pub crop: GifCrop,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            generations: 100,
+            frame_delay: 10,
+            frame_skip: 0,
+            cell_size: 8,
+            crop: GifCrop::Full,
+        }
+    }
+}
+
+/// Most pixels in one rendered frame (4096 x 4096).
+pub const MAX_FRAME_PIXELS: usize = 1 << 24;
+
+// GIF palettes hold 256 colours, so states past 255 share the last one
+const MAX_PALETTE_STATE: usize = 255;
+
+/// Fixed RGB palette with one entry per state: index 0 is the background,
+/// state 1 uses the same green as the web renderer and the remaining states
+/// (up to 255) are spread around the hue wheel.
+pub fn palette_for_depth(depth: usize) -> Vec<u8> {
+    let states = depth.clamp(1, MAX_PALETTE_STATE);
+    let mut palette = vec![0, 0, 0, 0x32, 0xcd, 0x32];
+
+    for i in 1..states {
+        let hue = (120.0 + 360.0 * i as f32 / states as f32) % 360.0;
+        palette.extend_from_slice(&hsv_to_rgb(hue, 0.8, 0.9));
+    }
+    palette
+}
+
+/// Rasterizes cell snapshots (as returned by `for_each_cell`) into indexed
+/// pixel buffers for a fixed cell region.
+pub struct FrameRenderer {
+    pub width: usize,
+    pub height: usize,
+    max_index: u32,
+    pixel_cells: Vec<(i32, i32, i32)>,
+}
+
+impl FrameRenderer {
+    /// Fails when the frame would be wider or taller than a GIF allows
+    /// (65535) or hold more than [`MAX_FRAME_PIXELS`]; crop to the live
+    /// cells or lower the cell size for large grids.
+    pub fn new(shape: &str, depth: usize, region: [i32; 4], cell_size: usize) -> io::Result<Self> {
+        let (x0, y0, x1, y1) = world_rect(shape, region);
+        let scale = cell_size.max(1) as f32;
+        let (w, h) = (((x1 - x0) as f64 * scale as f64).ceil(), ((y1 - y0) as f64 * scale as f64).ceil());
+        if w > u16::MAX as f64 || h > u16::MAX as f64 || w * h > MAX_FRAME_PIXELS as f64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("a {w}x{h} frame is too large, at most {MAX_FRAME_PIXELS} pixels and 65535 per side"),
+            ));
+        }
+        let (width, height) = ((w as usize).max(1), (h as usize).max(1));
+
+        // Pixel → cell lookup is the same for every frame
+        let pixel_cells = (0..height)
+            .flat_map(|py| (0..width).map(move |px| (px, py)))
+            .map(|(px, py)| {
+                let wx = x0 + (px as f32 + 0.5) / scale;
+                let wy = y0 + (py as f32 + 0.5) / scale;
+                pixel_to_cell(shape, wx, wy)
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            max_index: depth.clamp(1, MAX_PALETTE_STATE) as u32,
+            pixel_cells,
+        })
+    }
+
+    /// Palette indices, row-major, one byte per pixel.
+    pub fn render(&self, cells: &[i32]) -> Vec<u8> {
+        let states: HashMap<(i32, i32, i32), u32> = cells
+            .chunks(4)
+            .map(|c| ((c[0], c[1], c[2]), c[3] as u32))
+            .collect();
+
+        self.pixel_cells
+            .iter()
+            .map(|key| states.get(key).map_or(0, |&v| v.min(self.max_index) as u8))
+            .collect()
+    }
+
+    /// Binary PPM (P6) image of one frame.
+    pub fn write_ppm<W: Write>(&self, cells: &[i32], palette: &[u8], mut out: W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let rgb: Vec<u8> = self
+            .render(cells)
+            .into_iter()
+            .flat_map(|i| {
+                let i = i as usize * 3;
+                [palette[i], palette[i + 1], palette[i + 2]]
+            })
+            .collect();
+        out.write_all(&rgb)
+    }
+}
+
+impl CellManager {
+    /// Highest state the images tell apart: the layer count, or the last
+    /// state of the rule (e.g. the last species) when that is larger.
+    pub fn palette_states(&self) -> usize {
+        self.config.depth.max(self.get_rule().states as usize - 1)
+    }
+
+    /// Steps this manager `options.generations` times and writes every
+    /// recorded generation as a frame of a looping GIF.
+    pub fn export_gif<W: Write>(&mut self, options: &GifOptions, out: W) -> io::Result<()> {
+        let stride = options.frame_skip + 1;
+        let mut snapshots = vec![self.for_each_cell()];
+
+        for g in 1..=options.generations {
+            self.step();
+            if g % stride == 0 {
+                snapshots.push(self.for_each_cell());
+            }
+        }
+
+        self.write_gif_frames(&snapshots, options, out)
+    }
+
+    /// Encodes already captured snapshots; `generations` and `frame_skip`
+    /// in `options` are ignored.
+    pub fn write_gif_frames<W: Write>(
+        &self,
+        snapshots: &[Vec<i32>],
+        options: &GifOptions,
+        out: W,
+    ) -> io::Result<()> {
+        let region = self.gif_region(&options.crop, snapshots);
+        let palette = palette_for_depth(self.palette_states());
+        let renderer = FrameRenderer::new(&self.config.shape, self.palette_states(), region, options.cell_size)?;
+        let (width, height) = (renderer.width as u16, renderer.height as u16);
+
+        let mut encoder = Encoder::new(out, width, height, &palette).map_err(io::Error::other)?;
+        encoder.set_repeat(Repeat::Infinite).map_err(io::Error::other)?;
+
+        for cells in snapshots {
+            let frame = Frame {
+                width,
+                height,
+                delay: options.frame_delay,
+                buffer: Cow::Owned(renderer.render(cells)),
+                ..Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    pub fn gif_region(&self, crop: &GifCrop, snapshots: &[Vec<i32>]) -> [i32; 4] {
+        let [min_q, max_q, min_r, max_r, _, _] = self.get_bounds();
+        match crop {
+            GifCrop::Full => [min_q, max_q, min_r, max_r],
+            GifCrop::Region(region) => *region,
+            GifCrop::LiveBounds(margin) => {
+                let mut bbox: Option<[i32; 4]> = None;
+                for chunk in snapshots.iter().flat_map(|cells| cells.chunks(4)) {
+                    let (q, r) = (chunk[0], chunk[1]);
+                    let b = bbox.get_or_insert([q, q, r, r]);
+                    b[0] = b[0].min(q);
+                    b[1] = b[1].max(q);
+                    b[2] = b[2].min(r);
+                    b[3] = b[3].max(r);
+                }
+                match bbox {
+                    Some([a, b, c, d]) => [a - margin, b + margin, c - margin, d + margin],
+                    None => [min_q, max_q, min_r, max_r],
+                }
+            }
+        }
+    }
+}
+
+// World-space rectangle (cell units) covering a `[min_q, max_q, min_r, max_r]` region
+fn world_rect(shape: &str, [min_q, max_q, min_r, max_r]: [i32; 4]) -> (f32, f32, f32, f32) {
+    let corners = [(min_q, min_r), (max_q, min_r), (min_q, max_r), (max_q, max_r)];
+    let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+
+    for (q, r) in corners {
+        let (cx, cy, extent) = match shape {
+            "hexagon" | "rhombus" => (SQRT_3 * (q as f32 + r as f32 * 0.5), 1.5 * r as f32, 1.0),
+            "triangle" => (q as f32 + 0.5, r as f32 + 0.5, 0.5),
+            _ if let Some(t) = tiling(shape) => {
+                let (x, y) = t.origin(q, r);
+                let extent = t.tiles.iter().flatten().fold(0.0f32, |e, &(px, py)| e.max(px.abs()).max(py.abs()));
+                (x, y, extent)
+            }
+            _ => (q as f32, r as f32, 0.5),
+        };
+        x0 = x0.min(cx - extent);
+        y0 = y0.min(cy - extent);
+        x1 = x1.max(cx + extent);
+        y1 = y1.max(cy + extent);
+    }
+    (x0, y0, x1, y1)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [u8; 3] {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [
+        ((r + m) * 255.0) as u8,
+        ((g + m) * 255.0) as u8,
+        ((b + m) * 255.0) as u8,
+    ]
+}
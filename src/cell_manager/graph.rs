@@ -0,0 +1,527 @@
+// Automata on arbitrary graphs: cells are nodes and each node's neighborhood
+// is its adjacency list. Stepping, weighted sums, stats and cycle detection
+// are the lattice ones; node `i` stands in for cell `(i, 0, 0)` wherever
+// they expect coordinates, so stats bounds span live node indices and every
+// node is its own chunk. Edges are undirected.
+//
+// Graphs come from an edge list (see [`GraphManager::from_edge_list`]) or a
+// generator (see [`GraphManager::generate`]). Generated graphs place nodes so
+// that typical edges are about 1 long, like lattice neighbors, and kernels
+// weigh each edge by its length.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::cell_manager::cellmanager::CellChange;
+use crate::cell_manager::hashing::{CycleState, GridHash, HashHistory};
+use crate::cell_manager::stats::{GenerationStats, StatsHistory};
+use crate::cell_manager::{Kernel, Rule};
+
+type Point = (f32, f32);
+// Two nodes and the length of the edge between them
+type Edge = (usize, usize, f32);
+// Half of a thin or thick Penrose rhombus: (thin, apex, base corners)
+type Triangle = (bool, (f64, f64), (f64, f64), (f64, f64));
+
+/// Largest graph [`GraphManager::generate`] builds, in nodes and (expected) edges.
+pub const MAX_GRAPH_NODES: usize = 1 << 20;
+pub const MAX_GRAPH_EDGES: usize = 1 << 24;
+/// Deepest Penrose tiling `generate` builds, about a million nodes.
+pub const MAX_PENROSE_GENERATIONS: usize = 13;
+
+pub struct GraphManager {
+    pub generation: u64,
+    labels: Vec<String>,
+    index: HashMap<String, usize>,
+    positions: Vec<Point>,
+    adjacency: Vec<Vec<usize>>,
+    // Edge lengths and their kernel weights, parallel to `adjacency`
+    lengths: Vec<Vec<f32>>,
+    weights: Vec<Vec<f32>>,
+    states: Vec<u32>,
+    rule: Rule,
+    kernel: Kernel,
+    stats: StatsHistory,
+    hash: GridHash,
+    hash_history: HashHistory,
+}
+
+impl GraphManager {
+    // CONSTRUCTORS
+    fn build(labels: Vec<String>, index: HashMap<String, usize>, positions: Vec<Point>, edges: &[Edge]) -> Self {
+        let n = labels.len();
+        let mut adjacency = vec![Vec::new(); n];
+        let mut lengths = vec![Vec::new(); n];
+        let mut seen = HashSet::new();
+        for &(a, b, length) in edges {
+            if a == b || !seen.insert((a.min(b), a.max(b))) {
+                continue;
+            }
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+            lengths[a].push(length);
+            lengths[b].push(length);
+        }
+        let weights = lengths.iter().map(|l| vec![1.0; l.len()]).collect();
+
+        Self {
+            generation: 0,
+            labels,
+            index,
+            positions,
+            adjacency,
+            lengths,
+            weights,
+            states: vec![0; n],
+            rule: Rule::default(),
+            kernel: Kernel::Uniform,
+            stats: StatsHistory::new(1024),
+            hash: GridHash::default(),
+            hash_history: HashHistory::new(1024),
+        }
+    }
+
+    /// Parses one edge per line, `a b` or `a b length` (length defaults to
+    /// 1); a line with a single label adds an isolated node. Labels are any
+    /// whitespace-free text and nodes are numbered in order of appearance.
+    /// Blank lines and `#` comments are skipped; repeated edges and
+    /// self-loops are dropped. Nodes are laid out on a circle.
+    pub fn from_edge_list(text: &str) -> Result<Self, String> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut node = |label: &str| {
+            *index.entry(label.to_string()).or_insert_with(|| {
+                labels.push(label.to_string());
+                labels.len() - 1
+            })
+        };
+
+        let mut edges = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let length = match fields.len() {
+                0 => continue,
+                1 => {
+                    node(fields[0]);
+                    continue;
+                }
+                2 => 1.0,
+                3 => match fields[2].parse::<f32>() {
+                    Ok(length) if length.is_finite() && length >= 0.0 => length,
+                    _ => return Err(format!("line {}: bad edge length '{}'", i + 1, fields[2])),
+                },
+                _ => return Err(format!("line {}: expected 'a b [length]', got '{line}'", i + 1)),
+            };
+            edges.push((node(fields[0]), node(fields[1]), length));
+        }
+
+        let positions = circle(labels.len());
+        Ok(Self::build(labels, index, positions, &edges))
+    }
+
+    /// Builds a graph from `geometric:n,radius`, `watts_strogatz:n,k,p` (or
+    /// `ws:...`) or `penrose:generations`; `seed` drives the random ones.
+    /// Specs past [`MAX_GRAPH_NODES`], [`MAX_GRAPH_EDGES`] or
+    /// [`MAX_PENROSE_GENERATIONS`] are refused.
+    pub fn generate(spec: &str, seed: u64) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (name, params) = spec.split_once(':').unwrap_or((spec, ""));
+        let params: Vec<f64> = params
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| p.trim().parse().map_err(|_| format!("bad number '{p}' in '{spec}'")))
+            .collect::<Result<_, _>>()?;
+        let count = |i: usize| params[i] as usize;
+
+        match (name.to_ascii_lowercase().as_str(), params.len()) {
+            ("geometric", 2) => {
+                // Mean degree is about π radius², see `random_geometric`
+                let degree = std::f64::consts::PI * params[1] * params[1];
+                check_size(spec, params[0], params[0] * degree / 2.0)?;
+                Ok(Self::random_geometric(count(0), params[1] as f32, seed))
+            }
+            ("watts_strogatz" | "ws", 3) => {
+                check_size(spec, params[0], params[0] * params[1].min(params[0]) / 2.0)?;
+                Ok(Self::watts_strogatz(count(0), count(1), params[2] as f32, seed))
+            }
+            ("penrose", 1) if params[0] <= MAX_PENROSE_GENERATIONS as f64 => Ok(Self::penrose(count(0))),
+            ("penrose", 1) => Err(format!("graph '{spec}' too large, at most {MAX_PENROSE_GENERATIONS} generations")),
+            _ => Err(format!(
+                "unknown graph '{spec}', expected geometric:n,radius | watts_strogatz:n,k,p | penrose:generations"
+            )),
+        }
+    }
+
+    /// `n` nodes scattered over a square of area `n` (about one node per
+    /// unit area), joined when at most `radius` apart. The mean degree is
+    /// close to `π radius²`.
+    pub fn random_geometric(n: usize, radius: f32, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let side = (n as f32).sqrt();
+        let positions: Vec<Point> = (0..n).map(|_| (rng.f32() * side, rng.f32() * side)).collect();
+
+        // Bucket nodes into radius-sized squares so only nearby pairs are tested
+        let size = radius.max(1e-3);
+        let bucket = |(x, y): Point| ((x / size) as i32, (y / size) as i32);
+        let mut buckets: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (i, &p) in positions.iter().enumerate() {
+            buckets.entry(bucket(p)).or_default().push(i);
+        }
+
+        let mut edges = Vec::new();
+        for (i, &p) in positions.iter().enumerate() {
+            let (bx, by) = bucket(p);
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                for &j in buckets.get(&(bx + dx, by + dy)).into_iter().flatten() {
+                    let length = distance(p, positions[j]);
+                    if j > i && length <= radius {
+                        edges.push((i, j, length));
+                    }
+                }
+            }
+        }
+        let (labels, index) = numbered(n);
+        Self::build(labels, index, positions, &edges)
+    }
+
+    /// Ring of `n` nodes each joined to its `k / 2` nearest neighbors on
+    /// either side, then every edge rewired to a random node with
+    /// probability `p`. Nodes sit on a circle with neighbors 1 apart.
+    pub fn watts_strogatz(n: usize, k: usize, p: f32, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let half = (k / 2).min(n.saturating_sub(1) / 2);
+        let mut ring: Vec<(usize, usize)> = (0..n).flat_map(|i| (1..=half).map(move |d| (i, (i + d) % n))).collect();
+        let mut present: HashSet<(usize, usize)> = ring.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        let mut degree = vec![2 * half; n];
+
+        for (a, b) in ring.iter_mut() {
+            if rng.f32() >= p {
+                continue;
+            }
+            // A node already joined to every other one keeps its edge
+            if degree[*a] + 1 >= n {
+                continue;
+            }
+            let c = loop {
+                let c = rng.usize(0..n);
+                if c != *a && !present.contains(&((*a).min(c), (*a).max(c))) {
+                    break c;
+                }
+            };
+            present.remove(&((*a).min(*b), (*a).max(*b)));
+            present.insert(((*a).min(c), (*a).max(c)));
+            degree[*b] -= 1;
+            degree[c] += 1;
+            *b = c;
+        }
+
+        let positions = circle(n);
+        let edges: Vec<Edge> = ring.iter().map(|&(a, b)| (a, b, distance(positions[a], positions[b]))).collect();
+        let (labels, index) = numbered(n);
+        Self::build(labels, index, positions, &edges)
+    }
+
+    /// Corners and sides of a Penrose rhombus tiling grown from a wheel of
+    /// ten triangles by `generations` deflations, with unit sides. After the
+    /// first generation nodes have 2 to 7 neighbors; the node count grows
+    /// about 2.6 times per generation.
+    pub fn penrose(generations: usize) -> Self {
+        let phi = (1.0 + 5f64.sqrt()) / 2.0;
+        // The rhombus sides run from each triangle's apex to its base corners
+        let corner = |i: i32| {
+            let angle = (2 * i - 1) as f64 * std::f64::consts::PI / 10.0;
+            (angle.cos(), angle.sin())
+        };
+        let mut triangles: Vec<Triangle> = (0..10)
+            .map(|i| match i % 2 {
+                0 => (true, (0.0, 0.0), corner(i + 1), corner(i)),
+                _ => (true, (0.0, 0.0), corner(i), corner(i + 1)),
+            })
+            .collect();
+
+        let between = |a: (f64, f64), b: (f64, f64)| (a.0 + (b.0 - a.0) / phi, a.1 + (b.1 - a.1) / phi);
+        for _ in 0..generations {
+            triangles = triangles
+                .into_iter()
+                .flat_map(|(thin, a, b, c)| match thin {
+                    true => {
+                        let p = between(a, b);
+                        vec![(true, c, p, b), (false, p, c, a)]
+                    }
+                    false => {
+                        let q = between(b, a);
+                        let r = between(b, c);
+                        vec![(false, r, c, a), (false, q, r, b), (true, r, q, a)]
+                    }
+                })
+                .collect();
+        }
+
+        let scale = phi.powi(generations as i32);
+        let mut positions: Vec<Point> = Vec::new();
+        let mut index: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut node = |(x, y): (f64, f64)| {
+            let (x, y) = (x * scale, y * scale);
+            *index.entry(((x * 1e4).round() as i64, (y * 1e4).round() as i64)).or_insert_with(|| {
+                positions.push((x as f32, y as f32));
+                positions.len() - 1
+            })
+        };
+
+        let mut edges = Vec::new();
+        for &(_, a, b, c) in &triangles {
+            let (a, b, c) = (node(a), node(b), node(c));
+            edges.push((a, b, 1.0));
+            edges.push((a, c, 1.0));
+        }
+        let (labels, index) = numbered(positions.len());
+        Self::build(labels, index, positions, &edges)
+    }
+
+    /// Edge list that [`GraphManager::from_edge_list`] reads back, lengths
+    /// written only where they differ from 1.
+    pub fn to_edge_list(&self) -> String {
+        let mut out = format!("# {} nodes, {} edges\n", self.node_count(), self.edge_count());
+        for (a, neighbors) in self.adjacency.iter().enumerate() {
+            if neighbors.is_empty() {
+                out.push_str(&format!("{}\n", self.labels[a]));
+            }
+            for (&b, &length) in neighbors.iter().zip(&self.lengths[a]) {
+                match (a < b, length == 1.0) {
+                    (false, _) => {}
+                    (true, true) => out.push_str(&format!("{} {}\n", self.labels[a], self.labels[b])),
+                    (true, false) => out.push_str(&format!("{} {} {length}\n", self.labels[a], self.labels[b])),
+                }
+            }
+        }
+        out
+    }
+
+    // STRUCTURE
+    pub fn node_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    pub fn label(&self, node: usize) -> Option<&str> {
+        self.labels.get(node).map(String::as_str)
+    }
+
+    pub fn node_index(&self, label: &str) -> Option<usize> {
+        self.index.get(label).copied()
+    }
+
+    /// Layout position, for drawing and edge lengths.
+    pub fn position(&self, node: usize) -> Option<Point> {
+        self.positions.get(node).copied()
+    }
+
+    pub fn get_neighbors(&self, node: usize) -> &[usize] {
+        self.adjacency.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    /// Kernel weights of the edges to `get_neighbors(node)`, in the same order.
+    pub fn get_neighbor_weights(&self, node: usize) -> &[f32] {
+        self.weights.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    // CELLS
+    /// Sets a node's state; out of range nodes are ignored.
+    pub fn set_cell(&mut self, node: usize, value: u32) {
+        if let Some(state) = self.states.get_mut(node) {
+            self.hash.update(node as i32, 0, 0, *state, value);
+            *state = value;
+            self.hash_history.invalidate();
+        }
+    }
+
+    pub fn get_cell(&self, node: usize) -> u32 {
+        self.states.get(node).copied().unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        self.states.iter_mut().for_each(|s| *s = 0);
+        self.hash = GridHash::default();
+        self.hash_history.invalidate();
+    }
+
+    /// Live nodes as `[node, 0, 0, state]` quadruples, the lattice
+    /// `for_each_cell` layout.
+    pub fn for_each_cell(&self) -> Vec<i32> {
+        let live = self.states.iter().enumerate().filter(|(_, s)| **s != 0);
+        live.flat_map(|(node, &s)| [node as i32, 0, 0, s as i32]).collect()
+    }
+
+    /// Every node alive with probability `density`, reproducibly per seed.
+    pub fn random_cells_seeded(&mut self, seed: u64, density: f32) {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        for node in 0..self.node_count() {
+            let status = self.rule.random_state(&mut rng, density);
+            self.set_cell(node, status);
+        }
+    }
+
+    pub fn count_live_neighbors(&self, node: usize) -> u32 {
+        self.get_neighbors(node).iter().filter(|&&n| self.rule.is_alive(self.states[n])).count() as u32
+    }
+
+    /// Live neighbors of `node` of each species of the rule (species 1
+    /// first); empty for rules without species.
+    pub fn count_species_neighbors(&self, node: usize) -> Vec<u32> {
+        let Some(species) = &self.rule.species else { return Vec::new() };
+        let mut counts = vec![0; species.count()];
+        for &n in self.get_neighbors(node) {
+            if let Some(count) = counts.get_mut((self.states[n] as usize).wrapping_sub(1)) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    /// Kernel-weighted sum of the live neighbors of `node`.
+    pub fn count_weighted_neighbors(&self, node: usize) -> f32 {
+        let neighbors = self.get_neighbors(node).iter().zip(self.get_neighbor_weights(node));
+        neighbors.filter(|&(&n, _)| self.rule.is_alive(self.states[n])).map(|(_, w)| w).sum()
+    }
+
+    // RULES
+    pub fn set_rule(&mut self, rulestring: &str) -> bool {
+        match Rule::parse(rulestring) {
+            Some(rule) => {
+                self.rule = rule;
+                self.hash_history.invalidate();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Weighs every edge by its length, see [`Kernel::parse`].
+    pub fn set_kernel(&mut self, kernel: &str) -> bool {
+        let Some(kernel) = Kernel::parse(kernel) else {
+            return false;
+        };
+        self.kernel = kernel;
+        self.weights = self.lengths.iter().map(|l| l.iter().map(|&d| kernel.weight(d)).collect()).collect();
+        self.hash_history.invalidate();
+        true
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    /// Advances one generation and returns how many nodes changed.
+    pub fn step(&mut self) -> usize {
+        let live = self.for_each_cell();
+        let changes: Vec<CellChange> = (0..self.node_count())
+            .filter_map(|node| {
+                let current = self.states[node];
+                let next = if self.rule.species.is_some() {
+                    self.rule.next_state_species(current, &self.count_species_neighbors(node), 0.0)
+                } else if self.rule.weighted {
+                    self.rule.next_state_weighted(current, self.count_weighted_neighbors(node))
+                } else {
+                    self.rule.next_state(current, self.count_live_neighbors(node))
+                };
+                let q = node as i32;
+                (next != current).then_some(CellChange { q, r: 0, s: 0, old: current, new: next })
+            })
+            .collect();
+
+        for c in &changes {
+            self.states[c.q as usize] = c.new;
+            self.hash.update(c.q, 0, 0, c.old, c.new);
+        }
+        self.generation += 1;
+        let stats = GenerationStats::from_step(self.generation, &live, &changes, 1, 1);
+        self.record_hash(stats.population);
+        self.stats.push(stats);
+        changes.len()
+    }
+
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    // STATISTICS & CYCLES
+    pub fn stats(&self) -> &StatsHistory {
+        &self.stats
+    }
+
+    pub fn latest_stats(&self) -> Option<&GenerationStats> {
+        self.stats.latest()
+    }
+
+    pub fn set_stats_capacity(&mut self, capacity: usize) {
+        self.stats.set_capacity(capacity);
+    }
+
+    /// Records stats (and the state hash) for the current state without stepping.
+    pub fn record_stats(&mut self) {
+        let stats = GenerationStats::from_cells(self.generation, &self.for_each_cell(), 1, 1);
+        self.record_hash(stats.population);
+        self.stats.push(stats);
+    }
+
+    pub fn stats_csv(&self) -> String {
+        self.stats.to_csv()
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        self.hash.zobrist
+    }
+
+    /// Extinct, still or oscillating; nodes have no positions to move
+    /// between, so `Moving` is never reported.
+    pub fn cycle_state(&self) -> CycleState {
+        self.hash_history.state()
+    }
+
+    pub fn set_hash_history_capacity(&mut self, capacity: usize) {
+        self.hash_history.set_capacity(capacity);
+    }
+
+    fn record_hash(&mut self, population: usize) {
+        self.hash_history.record(self.generation, self.hash, None, population);
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+// `n` nodes evenly spaced 1 apart around a circle
+fn circle(n: usize) -> Vec<Point> {
+    let radius = n as f32 / std::f32::consts::TAU;
+    (0..n)
+        .map(|i| {
+            let angle = i as f32 / n as f32 * std::f32::consts::TAU;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect()
+}
+
+// Labels "0".."n-1" and the index from label to node
+fn numbered(n: usize) -> (Vec<String>, HashMap<String, usize>) {
+    let labels: Vec<String> = (0..n).map(|i| i.to_string()).collect();
+    let index = labels.iter().enumerate().map(|(i, l)| (l.clone(), i)).collect();
+    (labels, index)
+}
+
+// Refuses specs past the node or edge limit; NaN sizes are refused too
+fn check_size(spec: &str, nodes: f64, edges: f64) -> Result<(), String> {
+    match nodes <= MAX_GRAPH_NODES as f64 && edges <= MAX_GRAPH_EDGES as f64 {
+        true => Ok(()),
+        false => Err(format!("graph '{spec}' too large, at most {MAX_GRAPH_NODES} nodes and {MAX_GRAPH_EDGES} edges")),
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+
+// Mersenne prime 2^61 - 1 for the positional hash
+const P: u64 = (1 << 61) - 1;
+const BASE_Q: u64 = 0x0f2a_17c3_9b4d_5e61 % P;
+const BASE_R: u64 = 0x1b83_64e5_c0d7_a929 % P;
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// Mersenne reduction: x mod (2^61 - 1) = (x & P) + (x >> 61), folded once
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let x = a as u128 * b as u128;
+    let folded = (x as u64 & P) + (x >> 61) as u64;
+    if folded >= P { folded - P } else { folded }
+}
+
+fn pow_mod(mut base: u64, mut exp: u64) -> u64 {
+    let mut acc = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul_mod(acc, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+// base^e for any signed e (inverse via Fermat's little theorem)
+fn signed_pow(base: u64, e: i32) -> u64 {
+    if e >= 0 {
+        pow_mod(base, e as u64)
+    } else {
+        pow_mod(pow_mod(base, P - 2), e.unsigned_abs() as u64)
+    }
+}
+
+// Powers for |e| <= POW_TABLE_RANGE are cached, which covers every flat grid
+const POW_TABLE_RANGE: i32 = 4096;
+
+fn cached_pow(table: &'static OnceLock<Vec<u64>>, base: u64, e: i32) -> u64 {
+    if e.abs() > POW_TABLE_RANGE {
+        return signed_pow(base, e);
+    }
+    let powers = table.get_or_init(|| {
+        (-POW_TABLE_RANGE..=POW_TABLE_RANGE).map(|e| signed_pow(base, e)).collect()
+    });
+    powers[(e + POW_TABLE_RANGE) as usize]
+}
+
+fn pow_q(e: i32) -> u64 {
+    static TABLE: OnceLock<Vec<u64>> = OnceLock::new();
+    cached_pow(&TABLE, BASE_Q, e)
+}
+
+fn pow_r(e: i32) -> u64 {
+    static TABLE: OnceLock<Vec<u64>> = OnceLock::new();
+    cached_pow(&TABLE, BASE_R, e)
+}
+
+/// Zobrist key of one live cell. Computed on the fly instead of looked up in
+/// a table so it also works for the unbounded chunked backend.
+pub fn zobrist_key(q: i32, r: i32, s: i32, state: u32) -> u64 {
+    let mut h = splitmix64(q as u32 as u64);
+    h = splitmix64(h ^ r as u32 as u64);
+    h = splitmix64(h ^ s as u32 as u64);
+    splitmix64(h ^ state as u64)
+}
+
+/// Incrementally maintained hashes of the whole grid.
+///
+/// `zobrist` is the XOR of the keys of every live cell and identifies the
+/// exact state. `positional` is `Σ k(s, state) · Q^q · R^r (mod 2^61 - 1)`;
+/// translating a pattern by `(dq, dr)` multiplies it by `Q^dq · R^dr`, which
+/// lets [`GridHash::normalized`] compare patterns independent of position.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GridHash {
+    pub zobrist: u64,
+    pub positional: u64,
+}
+
+impl GridHash {
+    pub fn add(&mut self, q: i32, r: i32, s: i32, state: u32) {
+        if state == 0 {
+            return;
+        }
+        self.zobrist ^= zobrist_key(q, r, s, state);
+        self.positional = (self.positional + Self::term(q, r, s, state)) % P;
+    }
+
+    pub fn remove(&mut self, q: i32, r: i32, s: i32, state: u32) {
+        if state == 0 {
+            return;
+        }
+        self.zobrist ^= zobrist_key(q, r, s, state);
+        self.positional = (self.positional + P - Self::term(q, r, s, state)) % P;
+    }
+
+    pub fn update(&mut self, q: i32, r: i32, s: i32, old: u32, new: u32) {
+        if old != new {
+            self.remove(q, r, s, old);
+            self.add(q, r, s, new);
+        }
+    }
+
+    /// Positional hash of the pattern moved so `(min_q, min_r)` sits at the origin.
+    pub fn normalized(&self, min_q: i32, min_r: i32) -> u64 {
+        mul_mod(self.positional, mul_mod(pow_q(-min_q), pow_r(-min_r)))
+    }
+
+    /// Hash of a `for_each_cell` snapshot, built from scratch.
+    pub fn from_cells(cells: &[i32]) -> Self {
+        let mut hash = GridHash::default();
+        for c in cells.chunks(4) {
+            hash.add(c[0], c[1], c[2], c[3] as u32);
+        }
+        hash
+    }
+
+    fn term(q: i32, r: i32, s: i32, state: u32) -> u64 {
+        let k = splitmix64(splitmix64(s as u32 as u64) ^ state as u64) % (P - 1) + 1;
+        mul_mod(k, mul_mod(pow_q(q), pow_r(r)))
+    }
+}
+
+/// What the run has settled into, as far as the hash history can tell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CycleState {
+    /// No repeat inside the history window yet
+    Running,
+    /// Every cell died at `since`
+    Extinct { since: u64 },
+    /// Period-1 repeat without displacement
+    Still { since: u64 },
+    /// The state at `start` recurs every `period` generations
+    Oscillating { start: u64, period: u64 },
+    /// The pattern at `start` recurs every `period` generations shifted by `(dq, dr)`
+    Moving { start: u64, period: u64, dq: i32, dr: i32 },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct HashRecord {
+    generation: u64,
+    zobrist: u64,
+    normalized: u64,
+    anchor: Option<(i32, i32)>,
+    population: usize,
+}
+
+/// Rolling window of per-generation hashes used to spot cycles.
+pub struct HashHistory {
+    capacity: usize,
+    records: VecDeque<HashRecord>,
+    by_zobrist: HashMap<u64, u64>,
+    by_shape: HashMap<u64, u64>,
+    state: CycleState,
+    stale: bool,
+}
+
+impl HashHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::new(),
+            by_zobrist: HashMap::new(),
+            by_shape: HashMap::new(),
+            state: CycleState::Running,
+            stale: false,
+        }
+    }
+
+    pub fn state(&self) -> CycleState {
+        self.state
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.records.len() > capacity {
+            self.evict();
+        }
+    }
+
+    /// Marks the history as no longer describing one continuous run
+    /// (manual edits, rule or grid changes); it is dropped on the next record.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.by_zobrist.clear();
+        self.by_shape.clear();
+        self.state = CycleState::Running;
+        self.stale = false;
+    }
+
+    /// Adds the hash of `generation` and updates the cycle state. `bounds`
+    /// is the live bounding box (`[min_q, max_q, min_r, max_r]`).
+    pub fn record(&mut self, generation: u64, hash: GridHash, bounds: Option<[i32; 4]>, population: usize) {
+        if self.stale {
+            self.clear();
+        }
+        if self.capacity == 0 {
+            return;
+        }
+
+        let anchor = bounds.map(|b| (b[0], b[2]));
+        let normalized = anchor.map_or(0, |(q, r)| hash.normalized(q, r));
+        let record = HashRecord { generation, zobrist: hash.zobrist, normalized, anchor, population };
+
+        let next = if population == 0 {
+            CycleState::Extinct { since: generation }
+        } else if let Some(prev) = self.lookup(self.by_zobrist.get(&hash.zobrist), &record) {
+            let period = generation - prev.generation;
+            if period == 1 {
+                CycleState::Still { since: prev.generation }
+            } else {
+                CycleState::Oscillating { start: prev.generation, period }
+            }
+        } else if let Some(prev) = self.lookup(self.by_shape.get(&normalized), &record) {
+            let ((q1, r1), (q0, r0)) = (anchor.unwrap(), prev.anchor.unwrap());
+            CycleState::Moving {
+                start: prev.generation,
+                period: generation - prev.generation,
+                dq: q1 - q0,
+                dr: r1 - r0,
+            }
+        } else {
+            CycleState::Running
+        };
+        self.state = Self::continue_state(self.state, next);
+
+        if self.records.len() >= self.capacity {
+            self.evict();
+        }
+        // Latest occurrence wins, so the period found is the smallest one
+        self.by_zobrist.insert(record.zobrist, generation);
+        if anchor.is_some() {
+            self.by_shape.insert(record.normalized, generation);
+        }
+        self.records.push_back(record);
+    }
+
+    // Keeps the start generation of a cycle that is still going on
+    fn continue_state(current: CycleState, next: CycleState) -> CycleState {
+        use CycleState::*;
+        match (current, next) {
+            (Extinct { since }, Extinct { .. }) | (Still { since }, Still { .. }) => match current {
+                Extinct { .. } => Extinct { since },
+                _ => Still { since },
+            },
+            (Oscillating { start, period }, Oscillating { period: p, .. }) if p == period => {
+                Oscillating { start, period }
+            }
+            (Moving { start, period, dq, dr }, Moving { period: p, dq: q, dr: r, .. })
+                if (p, q, r) == (period, dq, dr) =>
+            {
+                Moving { start, period, dq, dr }
+            }
+            _ => next,
+        }
+    }
+
+    fn lookup(&self, generation: Option<&u64>, current: &HashRecord) -> Option<HashRecord> {
+        let generation = *generation?;
+        let idx = self.records.binary_search_by_key(&generation, |r| r.generation).ok()?;
+        let prev = self.records[idx];
+        // Guard against the rare hash collision
+        (prev.population == current.population && prev.generation < current.generation).then_some(prev)
+    }
+
+    fn evict(&mut self) {
+        if let Some(old) = self.records.pop_front() {
+            if self.by_zobrist.get(&old.zobrist) == Some(&old.generation) {
+                self.by_zobrist.remove(&old.zobrist);
+            }
+            if self.by_shape.get(&old.normalized) == Some(&old.generation) {
+                self.by_shape.remove(&old.normalized);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+
+use crate::cell_manager::cellmanager::CellChange;
+
+/// Default memory budget of the edit history, in bytes.
+pub const DEFAULT_HISTORY_BUDGET: usize = 16 << 20;
+
+/// Cells changed by one undoable step, in the order they were written.
+#[derive(Clone, Debug, Default)]
+pub struct Transaction {
+    pub label: String,
+    pub changes: Vec<CellChange>,
+}
+
+impl Transaction {
+    fn memory(&self) -> usize {
+        self.changes.len() * size_of::<CellChange>() + self.label.len()
+    }
+}
+
+/// Undo/redo stacks of cell diffs. Edits are collected into the open
+/// transaction; nested `begin`/`commit` pairs fold into the outermost one.
+pub struct EditHistory {
+    budget: usize,
+    used: usize,
+    undo: VecDeque<Transaction>,
+    redo: Vec<Transaction>,
+    open: Transaction,
+    // Position of each cell in `open.changes`, so repeated writes coalesce
+    open_index: HashMap<(i32, i32, i32), usize>,
+    depth: usize,
+}
+
+impl EditHistory {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            used: 0,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: Transaction::default(),
+            open_index: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    pub fn begin(&mut self, label: &str) {
+        if self.depth == 0 {
+            self.open.label = label.to_string();
+        }
+        self.depth += 1;
+    }
+
+    /// Adds a write to the open transaction, keeping the first old value
+    /// and the last new value of every cell.
+    pub fn record(&mut self, change: CellChange) {
+        if self.depth == 0 {
+            return;
+        }
+        match self.open_index.get(&(change.q, change.r, change.s)) {
+            Some(&i) => self.open.changes[i].new = change.new,
+            None if change.old == change.new => {}
+            None => {
+                self.open_index.insert((change.q, change.r, change.s), self.open.changes.len());
+                self.open.changes.push(change);
+            }
+        }
+    }
+
+    /// Closes one `begin`; the outermost commit pushes the transaction
+    /// (if it changed anything) and drops the redo stack.
+    pub fn commit(&mut self) {
+        if self.depth == 0 {
+            return;
+        }
+        self.depth -= 1;
+        if self.depth > 0 {
+            return;
+        }
+
+        let mut transaction = std::mem::take(&mut self.open);
+        self.open_index.clear();
+        transaction.changes.retain(|c| c.old != c.new);
+        if transaction.changes.is_empty() {
+            return;
+        }
+
+        self.used -= self.redo.drain(..).map(|t| t.memory()).sum::<usize>();
+        self.used += transaction.memory();
+        self.undo.push_back(transaction);
+        self.enforce_budget();
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Transaction> {
+        self.undo.pop_back()
+    }
+
+    pub fn push_redo(&mut self, transaction: Transaction) {
+        self.redo.push(transaction);
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Transaction> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, transaction: Transaction) {
+        self.undo.push_back(transaction);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Label of the transaction `undo` would revert.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.back().map(|t| t.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|t| t.label.as_str())
+    }
+
+    /// Approximate bytes held by both stacks.
+    pub fn memory_used(&self) -> usize {
+        self.undo.iter().chain(&self.redo).map(Transaction::memory).sum()
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.used = self.memory_used();
+        self.enforce_budget();
+    }
+
+    /// Drops both stacks and whatever the open transaction recorded so far.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open.changes.clear();
+        self.open_index.clear();
+        self.used = 0;
+    }
+
+    // Oldest transactions go first; a single one over budget empties the stack
+    fn enforce_budget(&mut self) {
+        while self.used > self.budget {
+            match self.undo.pop_front() {
+                Some(old) => self.used -= old.memory(),
+                None => {
+                    self.redo.clear();
+                    self.used = 0;
+                }
+            }
+        }
+    }
+}
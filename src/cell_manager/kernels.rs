@@ -0,0 +1,94 @@
+// Weights for neighborhood offsets by how far away the neighbor is.
+// Distances run between cell centres in world space (see `coords`), scaled
+// so that edge-adjacent squares and hexagons are 1 apart. Cubes add the
+// `s` offset as the third axis.
+
+use crate::cell_manager::coords::{SQRT_3, cell_center, is_volumetric};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Kernel {
+    /// Every neighbor weighs 1, the plain count.
+    #[default]
+    Uniform,
+    /// `exp(-d² / 2σ²)`
+    Gaussian { sigma: f32 },
+    /// A Gaussian bump of standard deviation `width` around `radius`, the
+    /// shell used by Lenia-like rules.
+    Ring { radius: f32, width: f32 },
+    /// `1 / d^power`; a zero offset weighs 1.
+    InverseDistance { power: f32 },
+}
+
+impl Kernel {
+    /// Parses `uniform`, `gaussian[:sigma]`, `ring[:radius[,width]]` and
+    /// `inverse[:power]`. Missing parameters default to sigma 1, radius 1,
+    /// width 0.5 and power 1.
+    pub fn parse(text: &str) -> Option<Kernel> {
+        let text = text.trim();
+        let (name, params) = text.split_once(':').unwrap_or((text, ""));
+        let params: Vec<f32> = match params.is_empty() {
+            true => Vec::new(),
+            false => params.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?,
+        };
+        let param = |i: usize, default: f32| params.get(i).copied().unwrap_or(default);
+
+        let kernel = match name.to_ascii_lowercase().as_str() {
+            "uniform" if params.is_empty() => Kernel::Uniform,
+            "gaussian" if params.len() <= 1 => Kernel::Gaussian { sigma: param(0, 1.0) },
+            "ring" if params.len() <= 2 => Kernel::Ring { radius: param(0, 1.0), width: param(1, 0.5) },
+            "inverse" | "inverse-distance" if params.len() <= 1 => Kernel::InverseDistance { power: param(0, 1.0) },
+            _ => return None,
+        };
+        match kernel {
+            Kernel::Gaussian { sigma: spread } | Kernel::Ring { width: spread, .. } if spread <= 0.0 => None,
+            _ => Some(kernel),
+        }
+    }
+
+    /// Text form accepted by [`Kernel::parse`], as stored in the config.
+    pub fn name(&self) -> String {
+        match self {
+            Kernel::Uniform => "uniform".to_string(),
+            Kernel::Gaussian { sigma } => format!("gaussian:{sigma}"),
+            Kernel::Ring { radius, width } => format!("ring:{radius},{width}"),
+            Kernel::InverseDistance { power } => format!("inverse:{power}"),
+        }
+    }
+
+    pub fn weight(&self, distance: f32) -> f32 {
+        match *self {
+            Kernel::Uniform => 1.0,
+            Kernel::Gaussian { sigma } => (-distance * distance / (2.0 * sigma * sigma)).exp(),
+            Kernel::Ring { radius, width } => {
+                let x = (distance - radius) / width;
+                (-x * x / 2.0).exp()
+            }
+            Kernel::InverseDistance { power } if distance > 0.0 => distance.powf(-power),
+            Kernel::InverseDistance { .. } => 1.0,
+        }
+    }
+
+    /// Weight of every offset of every layer of a neighborhood.
+    pub fn weights(&self, shape: &str, layers: &[Vec<(i32, i32, i32)>]) -> Vec<Vec<f32>> {
+        let scale = match shape {
+            "hexagon" | "rhombus" => SQRT_3,
+            _ => 1.0,
+        };
+        layers
+            .iter()
+            .enumerate()
+            .map(|(s, offsets)| {
+                let s = s as i32;
+                let (x0, y0) = cell_center(shape, 0, 0, s);
+                offsets
+                    .iter()
+                    .map(|&(dq, dr, ds)| {
+                        let (x, y) = cell_center(shape, dq, dr, s + ds);
+                        let dz = if is_volumetric(shape) { ds as f32 } else { 0.0 };
+                        self.weight((x - x0).hypot(y - y0).hypot(dz) / scale)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
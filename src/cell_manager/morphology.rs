@@ -0,0 +1,140 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::CellManager;
+use crate::cell_manager::Neighborhood;
+
+type Cell = (i32, i32, i32);
+
+// Morphology treats every non-zero cell as foreground. Structuring elements
+// are neighborhoods: the cell itself is always part of the element, and
+// cells off the edge of a bounded grid are ignored rather than counted as
+// background.
+impl CellManager {
+    /// Structuring element of this grid's shape.
+    pub fn structuring_element(&self, kind: &str, range: i32) -> Neighborhood {
+        Neighborhood::new(&self.config.shape, kind, range)
+    }
+
+    /// Sets every empty cell reached by `element` from a live cell to
+    /// `value`. Returns the number of cells changed.
+    pub fn dilate(&mut self, element: &Neighborhood, value: u32) -> usize {
+        let mut grown: HashSet<Cell> = HashSet::new();
+        for c in self.for_each_cell().chunks(4) {
+            for cell in self.element_cells(element, (c[0], c[1], c[2])) {
+                if self.get_cell(cell.0, cell.1, cell.2) == 0 {
+                    grown.insert(cell);
+                }
+            }
+        }
+        let changed = grown.len();
+        self.begin_transaction("dilate");
+        self.batch_update(grown.into_iter().map(|(q, r, s)| (q, r, s, value)).collect(), None);
+        self.commit_transaction();
+        changed
+    }
+
+    /// Clears every live cell whose element is not entirely live.
+    pub fn erode(&mut self, element: &Neighborhood) -> usize {
+        let removed = self.boundary(element);
+        let changed = removed.len();
+        self.begin_transaction("erode");
+        self.batch_update(removed.into_iter().map(|(q, r, s)| (q, r, s, 0)).collect(), None);
+        self.commit_transaction();
+        changed
+    }
+
+    /// Erosion followed by dilation: removes specks and thin bridges.
+    pub fn open(&mut self, element: &Neighborhood, value: u32) -> usize {
+        self.begin_transaction("open");
+        let changed = self.erode(element) + self.dilate(element, value);
+        self.commit_transaction();
+        changed
+    }
+
+    /// Dilation followed by erosion: fills small holes and gaps.
+    pub fn close(&mut self, element: &Neighborhood, value: u32) -> usize {
+        self.begin_transaction("close");
+        let changed = self.dilate(element, value) + self.erode(element);
+        self.commit_transaction();
+        changed
+    }
+
+    /// Live cells that an erosion with `element` would remove, sorted.
+    pub fn boundary(&self, element: &Neighborhood) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = self
+            .for_each_cell()
+            .chunks(4)
+            .map(|c| (c[0], c[1], c[2]))
+            .filter(|&cell| {
+                self.element_cells(element, cell)
+                    .iter()
+                    .any(|&(q, r, s)| self.get_cell(q, r, s) == 0)
+            })
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// Clears the interior of every region, keeping only its boundary.
+    pub fn extract_boundary(&mut self, element: &Neighborhood) -> usize {
+        let boundary: HashSet<Cell> = self.boundary(element).into_iter().collect();
+        let interior: Vec<(i32, i32, i32, u32)> = self
+            .for_each_cell()
+            .chunks(4)
+            .filter(|c| !boundary.contains(&(c[0], c[1], c[2])))
+            .map(|c| (c[0], c[1], c[2], 0))
+            .collect();
+        let changed = interior.len();
+        self.begin_transaction("boundary");
+        self.batch_update(interior, None);
+        self.commit_transaction();
+        changed
+    }
+
+    /// For every live cell, how many erosions with `element` it survives
+    /// plus one (1 on the boundary, 2 just inside it, ...). Cells that no
+    /// amount of erosion reaches, e.g. on a completely full torus, are left out.
+    pub fn distance_transform(&self, element: &Neighborhood) -> HashMap<Cell, u32> {
+        let live: HashSet<Cell> = self
+            .for_each_cell()
+            .chunks(4)
+            .map(|c| (c[0], c[1], c[2]))
+            .collect();
+
+        // Cell → live cells whose element contains it
+        let mut seen_by: HashMap<Cell, Vec<Cell>> = HashMap::new();
+        for &cell in &live {
+            for other in self.element_cells(element, cell) {
+                seen_by.entry(other).or_default().push(cell);
+            }
+        }
+
+        let mut distance: HashMap<Cell, u32> = HashMap::new();
+        let mut queue: VecDeque<Cell> = VecDeque::new();
+        for cell in self.boundary(element) {
+            distance.insert(cell, 1);
+            queue.push_back(cell);
+        }
+        while let Some(cell) = queue.pop_front() {
+            let d = distance[&cell];
+            for &other in seen_by.get(&cell).into_iter().flatten() {
+                if let Entry::Vacant(slot) = distance.entry(other) {
+                    slot.insert(d + 1);
+                    queue.push_back(other);
+                }
+            }
+        }
+        distance
+    }
+
+    // Cells covered by `element` placed on `(q, r, s)`, wrapped, without the centre
+    fn element_cells(&self, element: &Neighborhood, (q, r, s): Cell) -> Vec<Cell> {
+        element
+            .get_neighbor_offsets(s)
+            .iter()
+            .filter_map(|&(dq, dr, ds)| self.wrap_cell(q + dq, r + dr, s + ds))
+            .filter(|&cell| cell != (q, r, s))
+            .collect()
+    }
+}
@@ -1,22 +1,98 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
 
+use crate::cell_manager::coords::{
+    DoubledKind, cell_polygon, doubled_to_axial, hex_distance, is_volumetric, shape_layers, triangle_vertices,
+};
+use crate::cell_manager::kernels::Kernel;
+use crate::cell_manager::tilings::{Tiling, tiling};
+
+// Prefixes of neighborhoods given as data rather than by name
+const CUSTOM_PREFIXES: [&str; 3] = ["mask:", "offsets:", "parity:"];
+
+// Offsets for each layer
+type Layers = Vec<Vec<(i32, i32, i32)>>;
+
+
+#[derive(Clone)]
 pub struct Neighborhood {
     pub shape: String,
     pub chosen_type: String,
     pub range: i32,
     pub adj_neighbors: Vec<Vec<(i32,i32,i32)>>,
+    pub kernel: Kernel,
+    /// One weight per offset, parallel to `adj_neighbors`
+    pub weights: Vec<Vec<f32>>,
 }
 
 impl Neighborhood {
+    /// `chosen_type` is a name from [`Neighborhood::kinds_for_shape`] or a
+    /// custom spec (see [`Neighborhood::parse`]). Unknown names and bad specs
+    /// fall back to the cell itself.
     pub fn new(shape: &str, chosen_type: &str, range: i32) -> Self {
-        let adj_neighbors = Self::get_neighbors_for_shape(shape, chosen_type, range);
+        let chosen_type = Self::canonical_spec(chosen_type);
+        let adj_neighbors = Self::get_neighbors_for_shape(shape, &chosen_type, range);
+        let kernel = Kernel::Uniform;
         Self {
             shape: shape.to_string(),
-            chosen_type: chosen_type.to_string(),
+            chosen_type,
             range,
+            weights: kernel.weights(shape, &adj_neighbors),
             adj_neighbors,
+            kernel,
         }
     }
 
+    /// Like [`Neighborhood::new`], but rejects unknown names and malformed
+    /// specs. Besides the named kinds, `chosen_type` can be
+    ///
+    /// - `mask:<rows>`: rows of `.` and `#` split by `/` or newlines, with one
+    ///   `O` on the centre cell. Hexagon masks use the doubled-width layout
+    ///   the TUI draws (`.#.#.` over `#.O.#`); other shapes read each
+    ///   character as one `(q, r)` step on every layer.
+    /// - `offsets:dq,dr[,ds];...`: the same offsets for every layer.
+    /// - `parity:<offsets>|<offsets>...`: one offset list per layer, for
+    ///   triangles (2) and rhombi (3).
+    ///
+    /// Custom specs ignore `range`. Whitespace is dropped, so the stored
+    /// `chosen_type` fits on one line of a pattern header.
+    pub fn parse(shape: &str, chosen_type: &str, range: i32) -> Result<Self, String> {
+        let chosen_type = Self::canonical_spec(chosen_type);
+        match Self::parse_custom(shape, &chosen_type) {
+            Some(Err(e)) => return Err(e),
+            None if !Self::kinds_for_shape(shape).contains(&chosen_type.as_str()) => {
+                return Err(format!("unknown {shape} neighborhood '{chosen_type}'"));
+            }
+            _ => {}
+        }
+        Ok(Self::new(shape, &chosen_type, range))
+    }
+
+    /// Neighborhood from an ASCII mask, see [`Neighborhood::parse`].
+    pub fn from_mask(shape: &str, mask: &str) -> Result<Self, String> {
+        Self::parse(shape, &format!("mask:{mask}"), 1)
+    }
+
+    /// Neighborhood using the same offsets on every layer.
+    pub fn from_offsets(shape: &str, offsets: &[(i32, i32, i32)]) -> Result<Self, String> {
+        Self::parse(shape, &format!("offsets:{}", Self::format_offsets(offsets)), 1)
+    }
+
+    /// Neighborhood with one offset list per layer.
+    pub fn from_parity_offsets(shape: &str, layers: &[Vec<(i32, i32, i32)>]) -> Result<Self, String> {
+        let lists: Vec<String> = layers.iter().map(|l| Self::format_offsets(l)).collect();
+        Self::parse(shape, &format!("parity:{}", lists.join("|")), 1)
+    }
+
+    /// Whether this neighborhood came from a mask or offset list.
+    pub fn is_custom(&self) -> bool {
+        Self::is_custom_spec(&self.chosen_type)
+    }
+
+    pub fn is_custom_spec(chosen_type: &str) -> bool {
+        CUSTOM_PREFIXES.iter().any(|p| chosen_type.starts_with(p))
+    }
+
     pub fn get_neighbors(&self, q: i32, r: i32, s: i32) -> Vec<i32> {
         let mut out = Vec::new();
         let use_neighbors = self.get_neighbor_offsets(s);
@@ -38,19 +114,54 @@ impl Neighborhood {
         &self.adj_neighbors[use_state as usize]
     }
 
+    /// Weights of [`Neighborhood::get_neighbor_offsets`], in the same order.
+    pub fn get_neighbor_weights(&self, state: i32) -> &[f32] {
+        let use_state = if self.weights.len() == 1 { 0 } else { state };
+        &self.weights[use_state as usize]
+    }
+
+    /// Largest `|dq|` or `|dr|` of any offset.
+    pub fn reach(&self) -> i32 {
+        self.adj_neighbors.iter().flatten().map(|&(dq, dr, _)| dq.abs().max(dr.abs())).max().unwrap_or(0)
+    }
+
+    pub fn set_kernel(&mut self, kernel: Kernel) {
+        self.kernel = kernel;
+        self.weights = kernel.weights(&self.shape, &self.adj_neighbors);
+    }
+
     pub fn change_cell_properties(&mut self, shape: &str, chosen_type: &str, range: i32) {
         self.shape = shape.to_string();
-        self.chosen_type = chosen_type.to_string();
+        self.chosen_type = Self::canonical_spec(chosen_type);
         self.range = range;
-        self.adj_neighbors = Self::get_neighbors_for_shape(shape, chosen_type, range);
+        self.adj_neighbors = Self::get_neighbors_for_shape(shape, &self.chosen_type, range);
+        self.weights = self.kernel.weights(shape, &self.adj_neighbors);
+    }
+
+    /// Neighborhood names understood for each shape, in menu order.
+    pub fn kinds_for_shape(shape: &str) -> &'static [&'static str] {
+        match shape {
+            "square" => &["vonNeumann", "cross", "checkerboard", "moore", "circular", "star"],
+            "hexagon" => &["hexagonal", "ring", "tripod", "asterix"],
+            "rhombus" => &["Qbert", "vonNeumann", "moore"],
+            "triangle" => &["vonNeumann", "biohazard", "inner", "vertices", "moore"],
+            "cube" => &["vonNeumann", "faceEdge", "moore", "spherical"],
+            _ if tiling(shape).is_some() => &["vonNeumann", "moore"],
+            _ => &[],
+        }
     }
 
     fn get_neighbors_for_shape(shape: &str, chosen_type: &str, range: i32) -> Vec<Vec<(i32,i32,i32)>> {
+        if let Some(custom) = Self::parse_custom(shape, chosen_type) {
+            return custom.unwrap_or_else(|_| vec![vec![(0, 0, 0)]]);
+        }
         match shape {
             "hexagon" => vec![Self::get_hexagon_neighborhood(chosen_type, range)],
             "square" => vec![Self::get_square_neighborhood(chosen_type, range)],
-            "rhombus" => Self::get_rhombus_neighborhood(chosen_type),
+            "rhombus" => Self::get_rhombus_neighborhood(chosen_type, range),
             "triangle" => Self::get_triangle_neighborhood(chosen_type, range),
+            "cube" => vec![Self::get_cube_neighborhood(chosen_type, range)],
+            _ if let Some(t) = tiling(shape) => Self::get_tiling_neighborhood(t, chosen_type, range),
             _ => vec![vec![(0, 0, 0)]],
         }
     }
@@ -76,11 +187,10 @@ impl Neighborhood {
             "checkerboard" => {
                 for dx in -range..=range {
                     for dy in -range..=range {
-                        // Check parity to alternate like a chessboard
-                        if (dx + dy) % 2 != 0 {  // only even-sum cells
-                            if dx != 0 || dy != 0 {
-                                neigh.push((dx, dy, 0));
-                            }
+                        // Odd-sum offsets: the squares of the other colour,
+                        // which never include the centre
+                        if (dx + dy) % 2 != 0 {
+                            neigh.push((dx, dy, 0));
                         }
                     }
                 }
@@ -104,6 +214,17 @@ impl Neighborhood {
                 }
             }
 
+            // Euclidean disk: centres within range + 0.5, as drawn circles
+            "circular" => {
+                for dx in -range..=range {
+                    for dy in -range..=range {
+                        if (dx != 0 || dy != 0) && dx * dx + dy * dy <= range * range + range {
+                            neigh.push((dx, dy, 0));
+                        }
+                    }
+                }
+            }
+
             // Orthogonal + diagonal rays extending outward
             "star" => {
                 for d in 1..=range {
@@ -127,6 +248,38 @@ impl Neighborhood {
         neigh
     }
 
+    // Offsets through faces (L1 distance), faces and edges, the whole cube
+    // (Chebyshev distance) or a ball. At range 1 these are the 6, 18, 26
+    // and 18 cell neighborhoods.
+    fn get_cube_neighborhood(chosen_type: &str, range: i32) -> Vec<(i32, i32, i32)> {
+        let inside = |dx: i32, dy: i32, dz: i32| {
+            let l1 = dx.abs() + dy.abs() + dz.abs();
+            match chosen_type {
+                "vonNeumann" => l1 <= range,
+                "faceEdge" => l1 <= 2 * range,
+                "moore" => true,
+                // Centres within range + 0.5, like square `circular`
+                "spherical" => dx * dx + dy * dy + dz * dz <= range * range + range,
+                _ => false,
+            }
+        };
+
+        let mut neigh = Vec::new();
+        for dz in -range..=range {
+            for dy in -range..=range {
+                for dx in -range..=range {
+                    if (dx, dy, dz) != (0, 0, 0) && inside(dx, dy, dz) {
+                        neigh.push((dx, dy, dz));
+                    }
+                }
+            }
+        }
+        if neigh.is_empty() {
+            neigh.push((0, 0, 0));
+        }
+        neigh
+    }
+
     fn get_hexagon_neighborhood(chosen_type: &str, range: i32) -> Vec<(i32, i32, i32)> {
         let mut neigh: Vec<(i32, i32, i32)> = Vec::new();
         match chosen_type {
@@ -142,16 +295,10 @@ impl Neighborhood {
                     neigh.push((0,  d, 0)); neigh.push((0, -d, 0));
                 }
             }
-            "hexagonal" => {
-                for dx in -range..=range {
-                    for dy in -range..=range {
-                        if dx != 0 || dy != 0 {
-                            neigh.push((-dx,  dy, 0)); neigh.push(( dx, -dy, 0));
-                            neigh.push(( dx, 0, 0)); neigh.push((-dx, 0, 0));
-                            neigh.push((0,  dy, 0)); neigh.push((0, -dy, 0));                        }
-                    }
-                }
-            }
+            // Every hex within `range` steps
+            "hexagonal" => neigh = Self::hex_distances(range, 1..=range),
+            // Only the hexes exactly `range` steps away
+            "ring" => neigh = Self::hex_distances(range, range..=range),
             // Fallback — minimal neighborhood
             _ => {neigh.push((0, 1, 0))}
         }
@@ -159,6 +306,18 @@ impl Neighborhood {
         neigh
     }
 
+    fn hex_distances(range: i32, distances: RangeInclusive<i32>) -> Vec<(i32, i32, i32)> {
+        let mut neigh = Vec::new();
+        for dr in -range..=range {
+            for dq in -range..=range {
+                if distances.contains(&hex_distance(dq, dr)) {
+                    neigh.push((dq, dr, 0));
+                }
+            }
+        }
+        neigh
+    }
+
     fn get_triangle_neighborhood(chosen_type: &str, range: i32) -> Vec<Vec<(i32, i32, i32)>> {
         match chosen_type {
             "vonNeumann" => vec![
@@ -207,73 +366,248 @@ impl Neighborhood {
                 ],
             ],
 
-            // NEED TO FIX MOORE NEIGHBORHOOD FOR TRIANGULAR CELLS
-            "moore0" => vec![
-                vec![
-                    (-1, 1, 1),  (0, -1, 0),  (1, 0, 0),
-                    (0, 0, 1),   (0, 1, 1),   (-1, 0, 1),
-                    (1, 1, 0),   (1, 1, 1),   (0, 1, 0),
-                    (-1, 0, 0),  (-1, -1, 0), (-1, -1, 1),
-                ],
-                vec![
-                    (0, -1, 0),  (1, 0, 0),   (1, 1, 0),
-                    (0, 1, 0),   (-1, 0, 0),  (-1, -1, 0),
-                    (0, 0, -1),  (0, -1, -1), (1, 0, -1),
-                    (1, 1, -1),  (-1, -1, -1), (1, -1, -1),
-                ],
-            ],
+            // Triangles sharing a corner, grown `range` times
+            "moore" => (0..2).map(|s| Self::triangle_moore(s, range)).collect(),
 
-            "moore" => {
-                let mut layers: Vec<Vec<(i32,i32,i32)>> = Vec::new();
+            _ => vec![vec![(0, 0, 0)]],
+        }
+    }
+
+    /// Offsets of the triangles within `range` corner-sharing steps of
+    /// triangle `(0, 0, s)`, found by walking the vertices of the lattice.
+    fn triangle_moore(s: i32, range: i32) -> Vec<(i32, i32, i32)> {
+        let mut seen: HashSet<(i32, i32, i32)> = HashSet::from([(0, 0, s)]);
+        let mut frontier = vec![(0, 0, s)];
+
+        for _ in 0..range.max(0) {
+            let mut next = Vec::new();
+            for &(q, r, t) in &frontier {
+                for (x, y) in triangle_vertices(q, r, t) {
+                    // The six triangles around a vertex lie in the squares up and left of it
+                    for cell in [(x - 1, y - 1), (x - 1, y), (x, y - 1), (x, y)]
+                        .into_iter()
+                        .flat_map(|(cq, cr)| [(cq, cr, 0), (cq, cr, 1)])
+                    {
+                        if triangle_vertices(cell.0, cell.1, cell.2).contains(&(x, y)) && seen.insert(cell) {
+                            next.push(cell);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let mut offsets: Vec<(i32, i32, i32)> = seen
+            .into_iter()
+            .filter(|&cell| cell != (0, 0, s))
+            .map(|(q, r, t)| (q, r, t - s))
+            .collect();
+        offsets.sort_unstable();
+        offsets
+    }
 
+    // Rhombi from the tile geometry (`coords::cell_polygon`, as drawn by
+    // grids/tiles/rhomboid.js): `vonNeumann` steps across edges, `moore`
+    // across corners, `range` times. `Qbert` is the range 1 Moore set.
+    fn get_rhombus_neighborhood(chosen_type: &str, range: i32) -> Vec<Vec<(i32, i32, i32)>> {
+        match chosen_type {
+            "vonNeumann" => (0..3).map(|s| Self::rhombus_steps(s, range, 2)).collect(),
+            "moore" => (0..3).map(|s| Self::rhombus_steps(s, range, 1)).collect(),
+            "Qbert" => (0..3).map(|s| Self::rhombus_steps(s, 1, 1)).collect(),
+            _ => vec![vec![(0, 0, 0)]],
+        }
+    }
 
-                let mut upper = Vec::new();
-                for dq in -range..=range {
-                    for dr in -range..=range {
-                        upper.push((dq, dr,  0)); // upwards triangle
-                        upper.push((dq, dr,  1)); // downwards triangle
+    // Rhombi reached from (0, 0, s) in `range` steps, each step moving to a
+    // rhombus with at least `shared` corners in common
+    fn rhombus_steps(s: i32, range: i32, shared: usize) -> Vec<(i32, i32, i32)> {
+        let mut seen: HashSet<(i32, i32, i32)> = HashSet::from([(0, 0, s)]);
+        let mut frontier = vec![(0, 0, s)];
 
+        for _ in 0..range.max(0) {
+            let mut next = Vec::new();
+            for &(q, r, t) in &frontier {
+                let corners = Self::rhombus_corners(q, r, t);
+                // Rhombi touching a hexagon's corners lie in it or the six around it
+                for cell in (-1..=1)
+                    .flat_map(|dq| (-1..=1).map(move |dr| (dq, dr)))
+                    .filter(|&(dq, dr)| hex_distance(dq, dr) <= 1)
+                    .flat_map(|(dq, dr)| (0..3).map(move |u| (q + dq, r + dr, u)))
+                {
+                    let common = Self::rhombus_corners(cell.0, cell.1, cell.2)
+                        .iter()
+                        .filter(|c| corners.contains(c))
+                        .count();
+                    if common >= shared && seen.insert(cell) {
+                        next.push(cell);
                     }
                 }
-                layers.push(upper);
+            }
+            frontier = next;
+        }
+
+        let mut offsets: Vec<(i32, i32, i32)> = seen
+            .into_iter()
+            .filter(|&cell| cell != (0, 0, s))
+            .map(|(q, r, t)| (q, r, t - s))
+            .collect();
+        offsets.sort_unstable();
+        offsets
+    }
+
+    // Corners snapped to a fine grid so shared corners compare equal
+    fn rhombus_corners(q: i32, r: i32, s: i32) -> Vec<(i32, i32)> {
+        cell_polygon("rhombus", q, r, s)
+            .into_iter()
+            .map(|(x, y)| ((x * 1000.0).round() as i32, (y * 1000.0).round() as i32))
+            .collect()
+    }
+
+    // Tiles of the `tilings` reached in `range` steps across edges
+    // (`vonNeumann`) or corners (`moore`)
+    fn get_tiling_neighborhood(t: &Tiling, chosen_type: &str, range: i32) -> Vec<Vec<(i32, i32, i32)>> {
+        let corners = match chosen_type {
+            "vonNeumann" => false,
+            "moore" => true,
+            _ => return vec![vec![(0, 0, 0)]],
+        };
+        let step = |u: i32| if corners { t.vertex_offsets(u) } else { t.edge_offsets(u) };
 
-                let mut lower = Vec::new();
-                for dq in -range..=range {
-                    for dr in -range..=range {
-                        lower.push((dq, dr, -1)); // upwards triangle
-                        lower.push((dq, dr, 0)); // downwards triangle
+        (0..t.layers())
+            .map(|s| {
+                let mut seen: HashSet<(i32, i32, i32)> = HashSet::from([(0, 0, s)]);
+                let mut frontier = vec![(0, 0, s)];
+                for _ in 0..range.max(0) {
+                    let mut next = Vec::new();
+                    for &(q, r, u) in &frontier {
+                        for &(dq, dr, ds) in step(u) {
+                            let cell = (q + dq, r + dr, u + ds);
+                            if seen.insert(cell) {
+                                next.push(cell);
+                            }
+                        }
                     }
+                    frontier = next;
                 }
-                layers.push(lower);
 
-                layers
-            }
+                let mut offsets: Vec<(i32, i32, i32)> = seen
+                    .into_iter()
+                    .filter(|&cell| cell != (0, 0, s))
+                    .map(|(q, r, u)| (q, r, u - s))
+                    .collect();
+                offsets.sort_unstable();
+                offsets
+            })
+            .collect()
+    }
 
-            _ => vec![vec![(0, 0, 0)]],
+    // CUSTOM NEIGHBORHOODS
+    // Mask rows keep their leading dots (hex rows are staggered); offset
+    // lists lose all whitespace
+    fn canonical_spec(spec: &str) -> String {
+        if let Some(mask) = spec.strip_prefix("mask:") {
+            let rows: Vec<String> = mask.split(['\n', '/']).map(|row| row.trim_end().replace(' ', ".")).collect();
+            let first = rows.iter().position(|row| !row.is_empty()).unwrap_or(rows.len());
+            let last = rows.iter().rposition(|row| !row.is_empty()).map_or(first, |i| i + 1);
+            format!("mask:{}", rows[first..last].join("/"))
+        } else if Self::is_custom_spec(spec) {
+            spec.chars().filter(|c| !c.is_whitespace()).collect()
+        } else {
+            spec.to_string()
         }
     }
 
-    fn get_rhombus_neighborhood(chosen_type: &str) -> Vec<Vec<(i32, i32, i32)>> {
-        match chosen_type {
-            "Qbert" => vec![
-                vec![ // (0, 0, 0)
-                    (0, 0, 2), (1, 0, 2), (-1, 1, 2), (0, 1, 2),
-                    (1, 0, 1), (0, 0, 1), (1, -1, 1), (0, 1, 1),
-                    (1, -1, 0), (-1, 1, 0)
-                    ],
-                vec![ // (0, 0, 1)
-                    (0, 0, -1), (0, 0, 1), (-1, 1, 1), (-1, 0, -1),
-                    (0, -1, -1), (0, -1, 0), (-1, 0, 1), (0, 1, 0),
-                    (-1, 1, -1), (0, 1, 1)
-                ],
-                vec![ // (0, 0, 2)
-                    (0, 0, -1), (0, 0, -2), (0, -1, -2), (1, -1, -1),
-                    (0, -1, -1), (-1, 0, 0), (-1, 0, -2),
-                    (1, -1, -2), (1, 0, 0), (1, 0, -1)
-                ]
-            ],
-            _ => vec![vec![(0, 0, 0)]],
+    // None when `spec` is a plain name
+    fn parse_custom(shape: &str, spec: &str) -> Option<Result<Layers, String>> {
+        let layers = shape_layers(shape) as usize;
+        let lists = if let Some(mask) = spec.strip_prefix("mask:") {
+            Self::mask_offsets(shape, mask).map(|offsets| vec![offsets; layers])
+        } else if let Some(list) = spec.strip_prefix("offsets:") {
+            Self::parse_offsets(list).map(|offsets| vec![offsets; layers])
+        } else if let Some(lists) = spec.strip_prefix("parity:") {
+            lists
+                .split('|')
+                .map(Self::parse_offsets)
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|lists| match lists.len() == layers {
+                    true => Ok(lists),
+                    false => Err(format!("{shape} needs {layers} parity lists, got {}", lists.len())),
+                })
+        } else {
+            return None;
+        };
+        Some(lists.and_then(|lists| Self::check_layers(shape, &lists)))
+    }
+
+    fn mask_offsets(shape: &str, mask: &str) -> Result<Vec<(i32, i32, i32)>, String> {
+        let rows: Vec<Vec<char>> = mask.split('/').map(|row| row.chars().collect()).collect();
+        let centres: Vec<(i32, i32)> = Self::mask_positions(&rows, 'O').collect();
+        let &[(centre_row, centre_col)] = centres.as_slice() else {
+            return Err(format!("mask needs exactly one 'O' centre, found {}", centres.len()));
+        };
+        if let Some(c) = rows.iter().flatten().find(|c| !matches!(c, '.' | '#' | 'O')) {
+            return Err(format!("unexpected '{c}' in mask (use '.', '#' and 'O')"));
+        }
+
+        let mut neigh = Vec::new();
+        for (row, col) in Self::mask_positions(&rows, '#') {
+            let (dcol, drow) = (col - centre_col, row - centre_row);
+            if shape == "hexagon" {
+                if (dcol + drow) % 2 != 0 {
+                    return Err(format!("'#' at row {} column {} is between hexagons", row + 1, col + 1));
+                }
+                let (dq, dr) = doubled_to_axial(dcol, drow, DoubledKind::Width);
+                neigh.push((dq, dr, 0));
+            } else {
+                neigh.push((dcol, drow, 0));
+            }
         }
+        Ok(neigh)
+    }
+
+    // (row, column) of every `mark` in the mask
+    fn mask_positions(rows: &[Vec<char>], mark: char) -> impl Iterator<Item = (i32, i32)> + '_ {
+        rows.iter().enumerate().flat_map(move |(row, chars)| {
+            chars.iter().enumerate().filter(move |(_, c)| **c == mark).map(move |(col, _)| (row as i32, col as i32))
+        })
+    }
+
+    fn parse_offsets(list: &str) -> Result<Vec<(i32, i32, i32)>, String> {
+        list.split(';')
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let values = entry
+                    .split(',')
+                    .map(|v| v.parse::<i32>().map_err(|_| format!("bad offset '{entry}'")))
+                    .collect::<Result<Vec<i32>, _>>()?;
+                match values[..] {
+                    [dq, dr] => Ok((dq, dr, 0)),
+                    [dq, dr, ds] => Ok((dq, dr, ds)),
+                    _ => Err(format!("offset '{entry}' needs 2 or 3 values")),
+                }
+            })
+            .collect()
     }
-}
 
+    fn format_offsets(offsets: &[(i32, i32, i32)]) -> String {
+        let entries: Vec<String> = offsets.iter().map(|(dq, dr, ds)| format!("{dq},{dr},{ds}")).collect();
+        entries.join(";")
+    }
+
+    // Every offset has to land on an existing layer (any `s` of a cube)
+    fn check_layers(shape: &str, lists: &[Vec<(i32, i32, i32)>]) -> Result<Layers, String> {
+        let layers = lists.len() as i32;
+        for (s, list) in lists.iter().enumerate() {
+            if list.is_empty() {
+                return Err(format!("layer {s} has no neighbors"));
+            }
+            if is_volumetric(shape) {
+                continue;
+            }
+            if let Some(&(dq, dr, ds)) = list.iter().find(|o| !(0..layers).contains(&(s as i32 + o.2))) {
+                return Err(format!("offset ({dq},{dr},{ds}) from layer {s} lands outside layers 0..{layers}"));
+            }
+        }
+        Ok(lists.to_vec())
+    }
+}
@@ -0,0 +1,255 @@
+use crate::CellManager;
+
+/// Cells read from a pattern file, plus whatever settings the file carried.
+#[derive(Clone, Debug, Default)]
+pub struct Pattern {
+    pub cells: Vec<(i32, i32, i32, u32)>,
+    pub rule: Option<String>,
+    /// `key=value` pairs from a cell list header (shape, neighbor_type, ...)
+    pub properties: Vec<(String, String)>,
+}
+
+impl Pattern {
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Picks the format from the content: RLE (`x = .., y = ..` header),
+/// plaintext (`!` comments, `.`/`O` rows) or a `q r s state` cell list.
+pub fn parse_pattern(text: &str) -> Result<Pattern, String> {
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !is_rle_comment(l))
+        .unwrap_or("");
+
+    if first.starts_with('x') && first.contains('=') {
+        parse_rle(text)
+    } else if first.starts_with('!') || first.chars().all(|c| matches!(c, '.' | 'O' | 'o' | '*')) {
+        parse_plaintext(text)
+    } else {
+        parse_cell_list(text)
+    }
+}
+
+// `#N name`, `#C comment`, `#O author`... as opposed to a `# key=value` header
+fn is_rle_comment(line: &str) -> bool {
+    line.starts_with('#') && line[1..].starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+/// Widest and tallest RLE pattern [`parse_rle`] reads; keeps huge run
+/// counts like `2000000000o` from allocating.
+pub const MAX_PATTERN_SIZE: i32 = 1 << 16;
+
+/// Run Length Encoded patterns as used by Golly. `b`/`.` are dead, `o` is
+/// state 1 and `A`..`X` are states 1..24. The pattern is centred on the origin.
+pub fn parse_rle(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+    let mut width = 0;
+    let mut height = 0;
+    let mut body = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('x') && body.is_empty() {
+            for field in line.split(',') {
+                let Some((key, value)) = field.split_once('=') else { continue };
+                let value = value.trim();
+                match key.trim() {
+                    "x" => width = value.parse().map_err(|_| format!("bad RLE width '{value}'"))?,
+                    "y" => height = value.parse().map_err(|_| format!("bad RLE height '{value}'"))?,
+                    "rule" => pattern.rule = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            continue;
+        }
+        body.push_str(line);
+    }
+
+    let (ox, oy) = (width / 2, height / 2);
+    let (mut x, mut y) = (0i32, 0i32);
+    let mut run = String::new();
+    let advance = |pos: i32, count: i32| {
+        pos.checked_add(count)
+            .filter(|&p| p <= MAX_PATTERN_SIZE)
+            .ok_or_else(|| format!("RLE pattern larger than {MAX_PATTERN_SIZE} cells per side"))
+    };
+
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            run.push(c);
+            continue;
+        }
+        let count: i32 = if run.is_empty() { 1 } else { run.parse().map_err(|_| "bad RLE run")? };
+        run.clear();
+
+        match c {
+            'b' | '.' => x = advance(x, count)?,
+            '$' => {
+                y = advance(y, count)?;
+                x = 0;
+            }
+            '!' => break,
+            'o' | 'A'..='X' => {
+                let state = if c == 'o' { 1 } else { c as u32 - 'A' as u32 + 1 };
+                advance(x, count)?;
+                for _ in 0..count {
+                    pattern.cells.push((x - ox, y - oy, 0, state));
+                    x += 1;
+                }
+            }
+            c if c.is_whitespace() => {}
+            other => return Err(format!("unexpected '{other}' in RLE body")),
+        }
+    }
+
+    Ok(pattern)
+}
+
+/// Plaintext `.cells` patterns: `!` comment lines, `O` (or `*`) alive.
+pub fn parse_plaintext(text: &str) -> Result<Pattern, String> {
+    let rows: Vec<&str> = text
+        .lines()
+        .filter(|l| !l.starts_with('!'))
+        .collect();
+    let width = rows.iter().map(|r| r.trim_end().len()).max().unwrap_or(0) as i32;
+    let height = rows.len() as i32;
+
+    let mut pattern = Pattern::default();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.trim_end().chars().enumerate() {
+            match c {
+                'O' | 'o' | '*' => pattern.cells.push((x as i32 - width / 2, y as i32 - height / 2, 0, 1)),
+                '.' | ' ' => {}
+                other => return Err(format!("unexpected '{other}' in plaintext pattern")),
+            }
+        }
+    }
+    Ok(pattern)
+}
+
+/// The crate's own format, one `q r s state` per line (commas also accepted).
+/// A `# key=value ...` header line carries the grid configuration.
+pub fn parse_cell_list(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+
+    for (n, line) in text.lines().map(str::trim).enumerate() {
+        if let Some(header) = line.strip_prefix('#') {
+            for field in header.split_whitespace() {
+                if let Some((key, value)) = field.split_once('=') {
+                    if key == "rule" {
+                        pattern.rule = Some(value.to_string());
+                    }
+                    pattern.properties.push((key.to_string(), value.to_string()));
+                }
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let values: Vec<i64> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse::<i64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("line {}: expected integers, got '{line}'", n + 1))?;
+
+        let out_of_range = |_| format!("line {}: value out of range in '{line}'", n + 1);
+        let coord = |v: i64| i32::try_from(v).map_err(out_of_range);
+        match values[..] {
+            [q, r, s, v] => {
+                pattern.cells.push((coord(q)?, coord(r)?, coord(s)?, u32::try_from(v).map_err(out_of_range)?))
+            }
+            [q, r, s] => pattern.cells.push((coord(q)?, coord(r)?, coord(s)?, 1)),
+            [q, r] => pattern.cells.push((coord(q)?, coord(r)?, 0, 1)),
+            _ => return Err(format!("line {}: expected 'q r [s] [state]'", n + 1)),
+        }
+    }
+    Ok(pattern)
+}
+
+/// Writes the live cells of `cm` as a cell list, with the grid
+/// configuration in the header so the file can be loaded back as-is.
+pub fn write_cell_list(cm: &CellManager) -> String {
+    let c = &cm.config;
+    let mut out = format!(
+        "# width={} height={} depth={} shape={} neighbor_type={} range={} topology={} rule={} kernel={} generation={}\n",
+        c.width, c.height, c.depth, c.shape, c.neighbor_type, c.range, c.topology_type, c.rule, c.kernel, cm.generation
+    );
+    for chunk in cm.for_each_cell().chunks(4) {
+        out.push_str(&format!("{} {} {} {}\n", chunk[0], chunk[1], chunk[2], chunk[3]));
+    }
+    out
+}
+
+/// RLE for single-layer (`s = 0`) patterns.
+pub fn write_rle(cells: &[i32], rule: &str) -> String {
+    let live: Vec<(i32, i32, u32)> = cells
+        .chunks(4)
+        .filter(|c| c[2] == 0)
+        .map(|c| (c[0], c[1], c[3] as u32))
+        .collect();
+
+    let Some(min_q) = live.iter().map(|c| c.0).min() else {
+        return format!("x = 0, y = 0, rule = {rule}\n!\n");
+    };
+    let max_q = live.iter().map(|c| c.0).max().unwrap();
+    let min_r = live.iter().map(|c| c.1).min().unwrap();
+    let max_r = live.iter().map(|c| c.1).max().unwrap();
+    let multistate = live.iter().any(|c| c.2 > 1);
+
+    let (w, h) = ((max_q - min_q + 1) as usize, (max_r - min_r + 1) as usize);
+    let mut grid = vec![0u32; w * h];
+    for &(q, r, v) in &live {
+        grid[(q - min_q) as usize + (r - min_r) as usize * w] = v;
+    }
+
+    let symbol = |v: u32| -> char {
+        match (v, multistate) {
+            (0, false) => 'b',
+            (0, true) => '.',
+            (_, false) => 'o',
+            (v, true) => char::from_u32('A' as u32 + v.min(24) - 1).unwrap(),
+        }
+    };
+
+    let mut tokens: Vec<(usize, char)> = Vec::new();
+    let push = |tokens: &mut Vec<(usize, char)>, c: char| match tokens.last_mut() {
+        Some((n, last)) if *last == c => *n += 1,
+        _ => tokens.push((1, c)),
+    };
+
+    for y in 0..h {
+        let row = &grid[y * w..(y + 1) * w];
+        let end = row.iter().rposition(|&v| v != 0).map_or(0, |i| i + 1);
+        for &v in &row[..end] {
+            push(&mut tokens, symbol(v));
+        }
+        if y + 1 < h {
+            push(&mut tokens, '$');
+        }
+    }
+
+    let mut out = format!("x = {w}, y = {h}, rule = {rule}\n");
+    let mut line_len = 0;
+    for (n, c) in tokens.into_iter().chain([(1, '!')]) {
+        let token = if n > 1 { format!("{n}{c}") } else { c.to_string() };
+        if line_len + token.len() > 70 {
+            out.push('\n');
+            line_len = 0;
+        }
+        line_len += token.len();
+        out.push_str(&token);
+    }
+    out.push('\n');
+    out
+}
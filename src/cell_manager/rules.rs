@@ -0,0 +1,354 @@
+use std::collections::BTreeSet;
+
+/// Life-like (outer totalistic) rule: a dead cell is born when its live
+/// neighbor count is in `birth`, a live cell survives when it is in `survival`.
+///
+/// Weighted rules (`W:B...`) compare the kernel-weighted sum of live
+/// neighbors against `birth_sums` and `survival_sums` instead.
+///
+/// With more than two `states`, a live cell (state 1) that does not survive
+/// decays through states 2, 3, ... before dying, and only state 1 counts as
+/// a live neighbor, unless the rule has `species` (see [`Species`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub rulestring: String,
+    pub birth: Vec<u32>,
+    pub survival: Vec<u32>,
+    pub weighted: bool,
+    /// Inclusive `(low, high)` ranges of the weighted sum
+    pub birth_sums: Vec<(f32, f32)>,
+    pub survival_sums: Vec<(f32, f32)>,
+    /// Cell states, dead included
+    pub states: u32,
+    /// Neighborhood kind the rule asks for (`S/B/C/M` or `S/B/C/N`)
+    pub neighborhood: Option<&'static str>,
+    /// Competing species (`species:...` rules), stepped with
+    /// [`Rule::next_state_species`]
+    pub species: Option<Species>,
+}
+
+/// Species of a `species:` rule: state `k` is species `k`, every non-zero
+/// state is alive. A dead cell with a total live neighbor count in the
+/// rule's `birth` takes the species that wins the vote; a live cell
+/// survives when its own view of the neighbors is in its survival counts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    /// Survival counts per species, species 1 first
+    pub survival: Vec<Vec<u32>>,
+    /// `interaction[i][j]`: what one neighbor of species `j + 1` counts for
+    /// a cell of species `i + 1`. All ones (every live cell counts alike)
+    /// unless given; negative entries make a species harm another.
+    pub interaction: Vec<Vec<f32>>,
+    pub vote: Vote,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Vote {
+    /// The species with the most neighbors
+    Majority,
+    /// The species whose interaction-weighted neighbor sum is highest
+    Weighted,
+}
+
+impl Species {
+    pub fn count(&self) -> usize {
+        self.survival.len()
+    }
+
+    // How a cell of `species` (1-based) sees the neighbors counted per species
+    fn view(&self, species: usize, neighbors: &[u32]) -> f32 {
+        self.interaction[species - 1].iter().zip(neighbors).map(|(&w, &n)| w * n as f32).sum()
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::parse("B3/S23").unwrap()
+    }
+}
+
+impl Rule {
+    /// Parses `B3/S23` style strings (case-insensitive, either order) and the
+    /// legacy `23/3` survival/birth form. Counts above 9 can be written as
+    /// comma separated values or ranges, e.g. `B34-45/S33-57`.
+    ///
+    /// The 3D form `survival/birth/states/neighborhood` (e.g. `4/4/5/M` or
+    /// `9-26/5-7,12-13,15/5/M`) lists counts as numbers or ranges, and ends
+    /// with `M` (Moore) or `N` (von Neumann).
+    ///
+    /// Competing species are written `species:B<counts>/S<counts>/S<counts>...`
+    /// with one survival part per species (see [`Species`]), optionally
+    /// followed by an interaction matrix `I<row>;<row>...` of comma separated
+    /// numbers and `majority` (default) or `weighted` for the birth vote,
+    /// e.g. `species:B3/S23/S23` (Immigration) or
+    /// `species:B3/S23/S234/I1,-1;1,1/weighted`.
+    pub fn parse(rulestring: &str) -> Option<Rule> {
+        let text = rulestring.trim();
+        if let Some(sums) = text.strip_prefix("W:").or_else(|| text.strip_prefix("w:")) {
+            return Self::parse_weighted(text, sums);
+        }
+        if let Some(parts) = text.strip_prefix("species:") {
+            return Self::parse_species(text, parts);
+        }
+        let mut birth = None;
+        let mut survival = None;
+
+        let parts: Vec<&str> = text.split('/').collect();
+        if parts.iter().any(|p| p.starts_with(['B', 'b', 'S', 's'])) {
+            for part in parts {
+                let (head, tail) = part.split_at(part.len().min(1));
+                match head {
+                    "B" | "b" => birth = Some(parse_counts(tail)?),
+                    "S" | "s" => survival = Some(parse_counts(tail)?),
+                    _ => return None,
+                }
+            }
+        } else if parts.len() == 2 {
+            survival = Some(parse_counts(parts[0])?);
+            birth = Some(parse_counts(parts[1])?);
+        } else if let [survival, birth, states, neighborhood] = parts[..] {
+            return Self::parse_volumetric(text, survival, birth, states, neighborhood);
+        } else {
+            return None;
+        }
+
+        Some(Rule {
+            rulestring: text.to_string(),
+            birth: birth.unwrap_or_default(),
+            survival: survival.unwrap_or_default(),
+            weighted: false,
+            birth_sums: Vec::new(),
+            survival_sums: Vec::new(),
+            states: 2,
+            neighborhood: None,
+            species: None,
+        })
+    }
+
+    // `4/4/5/M`: survival, birth, state count, neighborhood letter
+    fn parse_volumetric(text: &str, survival: &str, birth: &str, states: &str, neighborhood: &str) -> Option<Rule> {
+        let states: u32 = states.trim().parse().ok().filter(|&n| n >= 2)?;
+        let neighborhood = match neighborhood.trim() {
+            "M" | "m" => "moore",
+            "N" | "n" => "vonNeumann",
+            _ => return None,
+        };
+        Some(Rule {
+            rulestring: text.to_string(),
+            birth: parse_list(birth)?,
+            survival: parse_list(survival)?,
+            weighted: false,
+            birth_sums: Vec::new(),
+            survival_sums: Vec::new(),
+            states,
+            neighborhood: Some(neighborhood),
+            species: None,
+        })
+    }
+
+    // `species:B3/S23/S23/I1,0;0,1/weighted`
+    fn parse_species(text: &str, parts: &str) -> Option<Rule> {
+        let mut birth = None;
+        let mut survival = Vec::new();
+        let mut interaction = None;
+        let mut vote = Vote::Majority;
+        for part in parts.split('/') {
+            let (head, tail) = part.split_at(part.len().min(1));
+            match (part, head) {
+                ("majority", _) => vote = Vote::Majority,
+                ("weighted", _) => vote = Vote::Weighted,
+                (_, "B" | "b") if birth.is_none() => birth = Some(parse_counts(tail)?),
+                (_, "S" | "s") => survival.push(parse_counts(tail)?),
+                (_, "I" | "i") if interaction.is_none() => interaction = Some(parse_matrix(tail)?),
+                _ => return None,
+            }
+        }
+        let n = survival.len();
+        let interaction = interaction.unwrap_or_else(|| vec![vec![1.0; n]; n]);
+        if n == 0 || interaction.len() != n || interaction.iter().any(|row| row.len() != n) {
+            return None;
+        }
+        Some(Rule {
+            rulestring: text.to_string(),
+            birth: birth?,
+            survival: Vec::new(),
+            weighted: false,
+            birth_sums: Vec::new(),
+            survival_sums: Vec::new(),
+            states: n as u32 + 1,
+            neighborhood: None,
+            species: Some(Species { survival, interaction, vote }),
+        })
+    }
+
+    // `W:B0.8-1.2/S0.5-1.5`: comma separated values or ranges of the sum
+    fn parse_weighted(text: &str, sums: &str) -> Option<Rule> {
+        let mut birth_sums = Vec::new();
+        let mut survival_sums = Vec::new();
+        for part in sums.split('/') {
+            let (head, tail) = part.split_at(part.len().min(1));
+            match head {
+                "B" | "b" => birth_sums = parse_sums(tail)?,
+                "S" | "s" => survival_sums = parse_sums(tail)?,
+                _ => return None,
+            }
+        }
+        Some(Rule {
+            rulestring: text.to_string(),
+            birth: Vec::new(),
+            survival: Vec::new(),
+            weighted: true,
+            birth_sums,
+            survival_sums,
+            states: 2,
+            neighborhood: None,
+            species: None,
+        })
+    }
+
+    /// Whether a cell in `state` counts as a live neighbor.
+    pub fn is_alive(&self, state: u32) -> bool {
+        match self.states {
+            _ if self.species.is_some() => state != 0,
+            0..=2 => state != 0,
+            _ => state == 1,
+        }
+    }
+
+    pub fn next_state(&self, current: u32, live_neighbors: u32) -> u32 {
+        if current == 0 {
+            if self.birth.contains(&live_neighbors) { 1 } else { 0 }
+        } else if self.states > 2 && current != 1 {
+            if current + 1 < self.states { current + 1 } else { 0 }
+        } else if self.survival.contains(&live_neighbors) {
+            current
+        } else if self.states > 2 {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// Like [`Rule::next_state`] for weighted rules, given the weighted sum
+    /// of live neighbors.
+    pub fn next_state_weighted(&self, current: u32, sum: f32) -> u32 {
+        let within = |ranges: &[(f32, f32)]| ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&sum));
+        if current == 0 {
+            if within(&self.birth_sums) { 1 } else { 0 }
+        } else if within(&self.survival_sums) {
+            current
+        } else {
+            0
+        }
+    }
+
+    /// Random start state: live with probability `density`, spread evenly
+    /// over the species of species rules.
+    pub fn random_state(&self, rng: &mut fastrand::Rng, density: f32) -> u32 {
+        match rng.f32() < density {
+            true => match &self.species {
+                Some(species) if species.count() > 1 => 1 + rng.u32(..species.count() as u32),
+                _ => 1,
+            },
+            false => 0,
+        }
+    }
+
+    /// Like [`Rule::next_state`] for species rules, given the live neighbors
+    /// of each species (species 1 first). `external` is what coupled worlds
+    /// add, a neutral count every species sees. Rules without species read
+    /// the total.
+    pub fn next_state_species(&self, current: u32, neighbors: &[u32], external: f32) -> u32 {
+        let Some(species) = &self.species else {
+            let total = neighbors.iter().sum::<u32>() as f32 + external;
+            return self.next_state(current, total.round().max(0.0) as u32);
+        };
+        let round = |count: f32| count.round().max(0.0) as u32;
+
+        if current != 0 {
+            let own = current as usize;
+            let survives = own <= species.count()
+                && species.survival[own - 1].contains(&round(species.view(own, neighbors) + external));
+            return if survives { current } else { 0 };
+        }
+        if !self.birth.contains(&round(neighbors.iter().sum::<u32>() as f32 + external)) {
+            return 0;
+        }
+        // A tied vote leaves the cell dead
+        let votes = (1..=species.count()).map(|k| match species.vote {
+            Vote::Majority => neighbors.get(k - 1).copied().unwrap_or(0) as f32,
+            Vote::Weighted => species.view(k, neighbors),
+        });
+        let mut best = (0, 0.0);
+        let mut tied = false;
+        for (k, vote) in (1..).zip(votes) {
+            if vote > best.1 {
+                (best, tied) = ((k, vote), false);
+            } else if vote == best.1 {
+                tied = true;
+            }
+        }
+        if tied { 0 } else { best.0 }
+    }
+}
+
+// Rows split by `;`, entries by `,`
+fn parse_matrix(text: &str) -> Option<Vec<Vec<f32>>> {
+    text.split(';')
+        .map(|row| row.split(',').map(|v| v.trim().parse::<f32>().ok().filter(|v| v.is_finite())).collect())
+        .collect()
+}
+
+fn parse_sums(text: &str) -> Option<Vec<(f32, f32)>> {
+    text.split(',')
+        .filter(|t| !t.trim().is_empty())
+        .map(|item| {
+            let item = item.trim();
+            // Skip a leading sign so negative bounds still parse
+            let (lo, hi) = match item.char_indices().skip(1).find(|&(_, c)| c == '-') {
+                Some((i, _)) => (&item[..i], &item[i + 1..]),
+                None => (item, item),
+            };
+            let (lo, hi): (f32, f32) = (lo.trim().parse().ok()?, hi.trim().parse().ok()?);
+            (lo <= hi).then_some((lo, hi))
+        })
+        .collect()
+}
+
+fn parse_counts(text: &str) -> Option<Vec<u32>> {
+    if text.contains(',') || text.contains('-') {
+        return parse_list(text);
+    }
+    let mut counts = Vec::new();
+    for c in text.chars() {
+        counts.push(c.to_digit(10)?);
+    }
+
+    counts.sort_unstable();
+    counts.dedup();
+    Some(counts)
+}
+
+/// Largest neighbor count a rule can list, far above any neighborhood the
+/// grid builds; keeps rulestrings like `B0-4000000000` from allocating.
+pub const MAX_NEIGHBOR_COUNT: u32 = 1 << 16;
+
+// Comma separated numbers and `lo-hi` ranges (`lo <= hi`), each at most
+// MAX_NEIGHBOR_COUNT
+fn parse_list(text: &str) -> Option<Vec<u32>> {
+    let count = |t: &str| t.trim().parse::<u32>().ok().filter(|&n| n <= MAX_NEIGHBOR_COUNT);
+    // A set, so repeated ranges cannot pile up either
+    let mut counts = BTreeSet::new();
+    for item in text.split(',').filter(|t| !t.trim().is_empty()) {
+        match item.split_once('-') {
+            Some((lo, hi)) => {
+                let (lo, hi) = (count(lo)?, count(hi)?);
+                counts.extend((lo <= hi).then_some(lo..=hi)?);
+            }
+            None => {
+                counts.insert(count(item)?);
+            }
+        }
+    }
+    Some(counts.into_iter().collect())
+}
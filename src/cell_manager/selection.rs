@@ -0,0 +1,425 @@
+use std::collections::HashSet;
+
+use crate::CellManager;
+use crate::cell_manager::coords::{SQRT_3, cell_center, hex_distance, polygon_contains};
+use crate::cell_manager::tilings::tiling;
+
+type Cell = (i32, i32, i32);
+
+/// Longest run of cells a `Rect` or `Hex` selection covers along an axis
+/// of a grid without edges; `Hex` radii above half of it select nothing.
+pub const MAX_SELECTION_SPAN: i32 = 1 << 13;
+
+/// Region of the grid that copy, cut and the transforms act on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selection {
+    /// `[min_q, max_q, min_r, max_r]`, every layer
+    Rect([i32; 4]),
+    /// Cells within `radius` hex steps of `(q, r)`, every layer. Meant for
+    /// hexagon and rhombus grids, where it is a hexagon.
+    Hex { q: i32, r: i32, radius: i32 },
+    /// Cells whose centre lies inside the polygon, in world units (see
+    /// [`coords`](crate::cell_manager::coords))
+    Polygon(Vec<(f32, f32)>),
+    /// An explicit list of cells, e.g. what was just pasted
+    Cells(Vec<Cell>),
+}
+
+/// How pasted cells combine with the grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PasteMode {
+    /// Live clipboard cells are written, dead ones leave the grid alone
+    Or,
+    /// Live clipboard cells toggle their target: empty cells take the
+    /// clipboard state, live cells are cleared
+    Xor,
+    /// The whole clipboard area is written, clearing cells it has as dead
+    Overwrite,
+}
+
+impl PasteMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "or" => Some(PasteMode::Or),
+            "xor" => Some(PasteMode::Xor),
+            "overwrite" | "copy" => Some(PasteMode::Overwrite),
+            _ => None,
+        }
+    }
+}
+
+/// Copied cells, dead ones included so `Overwrite` knows the area they
+/// covered. Coordinates are shifted so the smallest `q` and `r` are 0.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Clipboard {
+    pub shape: String,
+    /// `(q, r, s, state)` per cell, sorted
+    pub cells: Vec<(i32, i32, i32, u32)>,
+}
+
+impl Clipboard {
+    fn new(shape: &str, mut cells: Vec<(i32, i32, i32, u32)>) -> Self {
+        let min_q = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_r = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        for c in &mut cells {
+            c.0 -= min_q;
+            c.1 -= min_r;
+        }
+        cells.sort_unstable();
+        Self { shape: shape.to_string(), cells }
+    }
+
+    /// Rotates clockwise (as drawn) by `turns` steps of the shape's
+    /// rotational symmetry, see [`rotation_step`].
+    pub fn rotate(&mut self, turns: i32) {
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(q, r, s, v)| {
+                let (q, r, s) = rotate_cell(&self.shape, (q, r, s), turns);
+                (q, r, s, v)
+            })
+            .collect();
+        *self = Self::new(&self.shape, cells);
+    }
+
+    /// Mirrors left to right; triangles mirror across the cell diagonal,
+    /// the only mirror line of the grid as drawn.
+    pub fn reflect(&mut self) {
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(q, r, s, v)| {
+                let (q, r, s) = reflect_cell(&self.shape, (q, r, s));
+                (q, r, s, v)
+            })
+            .collect();
+        *self = Self::new(&self.shape, cells);
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|c| c.3 != 0).count()
+    }
+}
+
+/// Angle in degrees of one rotation step that maps the shape's tiling onto
+/// itself (360 for shapes without a known symmetry).
+pub fn rotation_step(shape: &str) -> u32 {
+    match shape {
+        "square" | "cube" => 90,
+        "hexagon" => 60,
+        "triangle" | "rhombus" => 120,
+        _ => 360,
+    }
+}
+
+/// `cell` rotated clockwise by `turns` steps of [`rotation_step`] about the
+/// origin: a cell centre for squares and hexagons, a vertex for triangles
+/// and the centre of hexagon `(0, 0)` for rhombi. Cubes turn about the `s`
+/// axis.
+pub fn rotate_cell(shape: &str, (mut q, mut r, mut s): Cell, turns: i32) -> Cell {
+    let steps = (360 / rotation_step(shape)) as i32;
+    for _ in 0..turns.rem_euclid(steps) {
+        (q, r, s) = match shape {
+            "square" | "cube" => (-r, q, s),
+            "hexagon" => (-r, q + r, s),
+            "rhombus" => (-q - r, q, (s + 1) % 3),
+            "triangle" => {
+                // Three times the centroid lies on the vertex lattice, where
+                // a third of a turn is (x, y) -> (-y, x - y)
+                let (x, y) = (3 * q + 1 + s, 3 * r + 2 - s);
+                triangle_from_centroid(-y, x - y)
+            }
+            _ => (q, r, s),
+        };
+    }
+    (q, r, s)
+}
+
+/// `cell` mirrored as described in [`Clipboard::reflect`].
+pub fn reflect_cell(shape: &str, (q, r, s): Cell) -> Cell {
+    match shape {
+        "square" | "cube" => (-q, r, s),
+        "hexagon" => (-q - r, r, s),
+        // Left and right rhombi swap, the bottom one stays
+        "rhombus" => (-q - r, r, [1, 0, 2][s.rem_euclid(3) as usize]),
+        "triangle" => (r, q, 1 - s),
+        _ => (q, r, s),
+    }
+}
+
+fn triangle_from_centroid(x: i32, y: i32) -> Cell {
+    let s = if x.rem_euclid(3) == 1 { 0 } else { 1 };
+    ((x - 1 - s).div_euclid(3), (y - 2 + s).div_euclid(3), s)
+}
+
+// Editing works on unwrapped coordinates so a selection crossing a torus
+// edge keeps its shape; reads and writes go through `wrap_cell`.
+impl CellManager {
+    /// Makes `selection` current and returns how many cells it covers.
+    pub fn select(&mut self, selection: Selection) -> usize {
+        let count = self.cells_in(&selection).len();
+        self.selection = Some(selection);
+        count
+    }
+
+    pub fn selection(&self) -> Option<&Selection> {
+        self.selection.as_ref()
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// Cells of the current selection, sorted.
+    pub fn selection_cells(&self) -> Vec<Cell> {
+        self.selection.as_ref().map_or(Vec::new(), |sel| self.cells_in(sel))
+    }
+
+    /// Cells covered by `selection` that exist on this grid, sorted.
+    pub fn cells_in(&self, selection: &Selection) -> Vec<Cell> {
+        let [_, _, _, _, min_s, max_s] = self.get_bounds();
+        let layers = |q: i32, r: i32| (min_s..=max_s).map(move |s| (q, r, s));
+
+        let mut cells: Vec<Cell> = match selection {
+            &Selection::Rect([min_q, max_q, min_r, max_r]) => {
+                // One period of a wrapping axis already holds every cell
+                let clamp = |axis: usize, lo: i32, hi: i32| match self.axis_edges(axis) {
+                    Some((min, max, false)) => (lo.max(min), hi.min(max)),
+                    Some((min, max, true)) => (lo, hi.min(lo.saturating_add(max - min))),
+                    None => (lo, hi.min(lo.saturating_add(MAX_SELECTION_SPAN - 1))),
+                };
+                let (min_q, max_q) = clamp(0, min_q, max_q);
+                let (min_r, max_r) = clamp(1, min_r, max_r);
+                (min_r..=max_r).flat_map(|r| (min_q..=max_q).flat_map(move |q| layers(q, r))).collect()
+            }
+            &Selection::Hex { q, r, radius } if (0..=MAX_SELECTION_SPAN / 2).contains(&radius) => {
+                // Further than a period from the centre, every cell has a
+                // nearer copy on a wrapping axis
+                let clamp = |axis: usize, center: i32| {
+                    let (lo, hi) = (center.saturating_sub(radius), center.saturating_add(radius));
+                    match self.axis_edges(axis) {
+                        Some((min, max, false)) => (lo.max(min), hi.min(max)),
+                        Some((min, max, true)) => {
+                            let period = max - min + 1;
+                            (lo.max(center.saturating_sub(period)), hi.min(center.saturating_add(period)))
+                        }
+                        None => (lo, hi),
+                    }
+                };
+                let (min_q, max_q) = clamp(0, q);
+                let (min_r, max_r) = clamp(1, r);
+                (min_r..=max_r)
+                    .flat_map(|nr| (min_q..=max_q).map(move |nq| (nq, nr)))
+                    .filter(|&(nq, nr)| hex_distance(nq - q, nr - r) <= radius)
+                    .flat_map(|(nq, nr)| layers(nq, nr))
+                    .collect()
+            }
+            Selection::Hex { .. } => Vec::new(),
+            Selection::Polygon(points) if points.len() >= 3 => {
+                let [min_q, max_q, min_r, max_r] = self.polygon_candidates(points);
+                (min_r..=max_r)
+                    .flat_map(|r| (min_q..=max_q).flat_map(move |q| layers(q, r)))
+                    .filter(|&(q, r, s)| polygon_contains(points, cell_center(&self.config.shape, q, r, s)))
+                    .collect()
+            }
+            Selection::Polygon(_) => Vec::new(),
+            Selection::Cells(cells) => cells.clone(),
+        };
+        cells.retain(|&(q, r, s)| self.wrap_cell(q, r, s).is_some());
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    /// Copies the current selection to the clipboard. Returns the number
+    /// of live cells copied.
+    pub fn copy(&mut self) -> usize {
+        let clipboard = self.extract(&self.selection_cells());
+        let copied = clipboard.population();
+        self.clipboard = Some(clipboard);
+        copied
+    }
+
+    /// Copies the current selection to the clipboard and clears it.
+    pub fn cut(&mut self) -> usize {
+        let cells = self.selection_cells();
+        self.clipboard = Some(self.extract(&cells));
+        self.begin_transaction("cut");
+        let cleared = self.clear_cells(&cells);
+        self.commit_transaction();
+        cleared
+    }
+
+    /// Pastes the clipboard with its `(0, 0)` corner on `(q, r)` and
+    /// selects the pasted area. Returns the number of cells changed.
+    pub fn paste(&mut self, q: i32, r: i32, mode: PasteMode) -> usize {
+        let Some(clipboard) = self.clipboard.take() else {
+            return 0;
+        };
+        let changed = if clipboard.shape == self.config.shape {
+            self.begin_transaction("paste");
+            let changed = self.place(&clipboard, q, r, mode);
+            self.commit_transaction();
+            changed
+        } else {
+            0
+        };
+        self.clipboard = Some(clipboard);
+        changed
+    }
+
+    pub fn clipboard(&self) -> Option<&Clipboard> {
+        self.clipboard.as_ref()
+    }
+
+    /// Mutable access, e.g. to rotate the clipboard before pasting.
+    pub fn clipboard_mut(&mut self) -> Option<&mut Clipboard> {
+        self.clipboard.as_mut()
+    }
+
+    pub fn set_clipboard(&mut self, clipboard: Clipboard) {
+        self.clipboard = Some(clipboard);
+    }
+
+    /// Rotates the selected cells clockwise by `turns` steps of
+    /// [`rotation_step`], keeping the corner of the selection in place.
+    /// Returns the number of live cells moved.
+    pub fn rotate_selection(&mut self, turns: i32) -> usize {
+        self.transform_selection("rotate", |clipboard| clipboard.rotate(turns), (0, 0))
+    }
+
+    /// Mirrors the selected cells, see [`Clipboard::reflect`].
+    pub fn reflect_selection(&mut self) -> usize {
+        self.transform_selection("reflect", Clipboard::reflect, (0, 0))
+    }
+
+    /// Moves the selected cells by `(dq, dr)`.
+    pub fn translate_selection(&mut self, dq: i32, dr: i32) -> usize {
+        self.transform_selection("translate", |_| {}, (dq, dr))
+    }
+
+    // Lifts the selection, transforms it and puts it back (ORed) with its
+    // corner moved by `offset`; the selection follows the cells
+    fn transform_selection(
+        &mut self,
+        label: &str,
+        transform: impl FnOnce(&mut Clipboard),
+        offset: (i32, i32),
+    ) -> usize {
+        let cells = self.selection_cells();
+        if cells.is_empty() {
+            return 0;
+        }
+        let min_q = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_r = cells.iter().map(|c| c.1).min().unwrap_or(0);
+
+        let mut lifted = self.extract(&cells);
+        transform(&mut lifted);
+
+        self.begin_transaction(label);
+        let moved = self.clear_cells(&cells);
+        self.place(&lifted, min_q + offset.0, min_r + offset.1, PasteMode::Or);
+        self.commit_transaction();
+        moved
+    }
+
+    fn extract(&self, cells: &[Cell]) -> Clipboard {
+        let cells = cells
+            .iter()
+            .map(|&(q, r, s)| {
+                let value = self.wrap_cell(q, r, s).map_or(0, |(q, r, s)| self.get_cell(q, r, s));
+                (q, r, s, value)
+            })
+            .collect();
+        Clipboard::new(&self.config.shape, cells)
+    }
+
+    // Clears the live cells among `cells`, returning how many there were
+    fn clear_cells(&mut self, cells: &[Cell]) -> usize {
+        let live: HashSet<Cell> = cells
+            .iter()
+            .filter_map(|&(q, r, s)| self.wrap_cell(q, r, s))
+            .filter(|&(q, r, s)| self.get_cell(q, r, s) != 0)
+            .collect();
+        let cleared = live.len();
+        self.batch_update(live.into_iter().map(|(q, r, s)| (q, r, s, 0)).collect(), None);
+        cleared
+    }
+
+    // Writes `clipboard` at `(q, r)` and selects its area
+    fn place(&mut self, clipboard: &Clipboard, q: i32, r: i32, mode: PasteMode) -> usize {
+        let mut writes = Vec::new();
+        let mut area = Vec::with_capacity(clipboard.cells.len());
+        for &(dq, dr, s, value) in &clipboard.cells {
+            area.push((q + dq, r + dr, s));
+            let Some((tq, tr, ts)) = self.wrap_cell(q + dq, r + dr, s) else {
+                continue;
+            };
+            let current = self.get_cell(tq, tr, ts);
+            let new = match mode {
+                PasteMode::Or if value == 0 => continue,
+                PasteMode::Xor if value == 0 => continue,
+                PasteMode::Xor if current != 0 => 0,
+                _ => value,
+            };
+            if new != current {
+                writes.push((tq, tr, ts, new));
+            }
+        }
+        let changed = writes.len();
+        self.batch_update(writes, None);
+        self.selection = Some(Selection::Cells(area));
+        changed
+    }
+
+    // Edges of the grid along axis 0 (q) or 1 (r), and whether walking past
+    // them wraps around; None when cells go on past them (chunked `none`)
+    fn axis_edges(&self, axis: usize) -> Option<(i32, i32, bool)> {
+        let bounds = self.get_bounds();
+        let (min, max) = (bounds[2 * axis], bounds[2 * axis + 1]);
+        let past = if axis == 0 { (max + 1, bounds[2], 0) } else { (bounds[0], max + 1, 0) };
+        match self.wrap_cell(past.0, past.1, past.2) {
+            None => Some((min, max, false)),
+            Some(cell) if cell != past => Some((min, max, true)),
+            Some(_) => None,
+        }
+    }
+
+    // Cell rectangle covering a world-space polygon, with a margin of one
+    fn polygon_candidates(&self, points: &[(f32, f32)]) -> [i32; 4] {
+        let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for &(x, y) in points {
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+        // Tiling lattices can be sheared: bound the lattice coordinates of
+        // the corners, widened by how far tiles reach out of their unit cell
+        if let Some(t) = tiling(&self.config.shape) {
+            let extent = |corners: &mut dyn Iterator<Item = (f32, f32)>| {
+                corners.fold([f32::MAX, f32::MIN, f32::MAX, f32::MIN], |[a, b, c, d], (fq, fr)| {
+                    [a.min(fq), b.max(fq), c.min(fr), d.max(fr)]
+                })
+            };
+            let [q0, q1, r0, r1] = extent(&mut points.iter().map(|&(x, y)| t.fractional(x, y)));
+            let [tq0, tq1, tr0, tr1] = extent(&mut t.tiles.iter().flatten().map(|&(x, y)| t.fractional(x, y)));
+            return [
+                (q0 - tq1).floor() as i32 - 1,
+                (q1 - tq0).ceil() as i32 + 1,
+                (r0 - tr1).floor() as i32 - 1,
+                (r1 - tr0).ceil() as i32 + 1,
+            ];
+        }
+        match self.config.shape.as_str() {
+            "hexagon" | "rhombus" => {
+                let (min_r, max_r) = ((y0 / 1.5).floor() as i32 - 1, (y1 / 1.5).ceil() as i32 + 1);
+                let min_q = (x0 / SQRT_3 - max_r as f32 * 0.5).floor() as i32 - 1;
+                let max_q = (x1 / SQRT_3 - min_r as f32 * 0.5).ceil() as i32 + 1;
+                [min_q, max_q, min_r, max_r]
+            }
+            // Triangles fill the unit square from (q, r) to (q + 1, r + 1)
+            "triangle" => [x0.floor() as i32 - 1, x1.floor() as i32 + 1, y0.floor() as i32 - 1, y1.floor() as i32 + 1],
+            _ => [x0.floor() as i32 - 1, x1.ceil() as i32 + 1, y0.floor() as i32 - 1, y1.ceil() as i32 + 1],
+        }
+    }
+}
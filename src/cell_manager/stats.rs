@@ -0,0 +1,182 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::cell_manager::cellmanager::CellChange;
+
+/// Population figures for one generation, collected while stepping.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: usize,
+    /// Live cells per state, indexed by state (index 0 is always 0); the
+    /// population of each species under species rules
+    pub per_state: Vec<usize>,
+    pub births: usize,
+    pub deaths: usize,
+    /// `[min_q, max_q, min_r, max_r]` of the live cells, if any
+    pub bounds: Option<[i32; 4]>,
+    /// Chunks (of `chunk_size` × `chunk_size` × `depth` cells) holding live cells
+    pub active_chunks: usize,
+}
+
+impl GenerationStats {
+    /// Builds the stats of the generation produced by applying `changes` to
+    /// `live` (the `for_each_cell` output the stepper already walked).
+    pub fn from_step(
+        generation: u64,
+        live: &[i32],
+        changes: &[CellChange],
+        chunk_size: usize,
+        depth: usize,
+    ) -> Self {
+        let mut stats = GenerationStats { generation, ..Default::default() };
+        let changed: HashSet<(i32, i32, i32)> = changes.iter().map(|c| (c.q, c.r, c.s)).collect();
+        let mut chunks = HashSet::new();
+        let (cs, depth) = (chunk_size.max(1) as i32, depth.max(1) as i32);
+        let chunk_of = |q: i32, r: i32, s: i32| (q.div_euclid(cs), r.div_euclid(cs), s.div_euclid(depth));
+
+        for chunk in live.chunks(4) {
+            let (q, r, s) = (chunk[0], chunk[1], chunk[2]);
+            if !changed.contains(&(q, r, s)) {
+                stats.add_cell(q, r, chunk[3] as u32);
+                chunks.insert(chunk_of(q, r, s));
+            }
+        }
+
+        for c in changes {
+            match (c.old, c.new) {
+                (0, 0) => {}
+                (0, _) => stats.births += 1,
+                (_, 0) => stats.deaths += 1,
+                _ => {}
+            }
+            if c.new != 0 {
+                stats.add_cell(c.q, c.r, c.new);
+                chunks.insert(chunk_of(c.q, c.r, c.s));
+            }
+        }
+
+        stats.active_chunks = chunks.len();
+        stats
+    }
+
+    /// Stats of a plain snapshot, without births or deaths.
+    pub fn from_cells(generation: u64, live: &[i32], chunk_size: usize, depth: usize) -> Self {
+        Self::from_step(generation, live, &[], chunk_size, depth)
+    }
+
+    fn add_cell(&mut self, q: i32, r: i32, state: u32) {
+        self.population += 1;
+
+        let state = state as usize;
+        if self.per_state.len() <= state {
+            self.per_state.resize(state + 1, 0);
+        }
+        self.per_state[state] += 1;
+
+        let b = self.bounds.get_or_insert([q, q, r, r]);
+        b[0] = b[0].min(q);
+        b[1] = b[1].max(q);
+        b[2] = b[2].min(r);
+        b[3] = b[3].max(r);
+    }
+}
+
+/// Fixed-capacity ring buffer of the most recent generation stats.
+pub struct StatsHistory {
+    capacity: usize,
+    entries: VecDeque<GenerationStats>,
+}
+
+impl StatsHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity.min(4096)),
+        }
+    }
+
+    pub fn push(&mut self, stats: GenerationStats) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(stats);
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drops the entries after `generation`, e.g. after stepping backward.
+    pub fn truncate_after(&mut self, generation: u64) {
+        while self.entries.back().is_some_and(|s| s.generation > generation) {
+            self.entries.pop_back();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<&GenerationStats> {
+        self.entries.back()
+    }
+
+    pub fn get(&self, generation: u64) -> Option<&GenerationStats> {
+        self.entries.iter().rev().find(|s| s.generation == generation)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GenerationStats> {
+        self.entries.iter()
+    }
+
+    /// Live cells in `state` (a species under species rules) for every
+    /// stored generation, oldest first.
+    pub fn state_history(&self, state: usize) -> Vec<usize> {
+        self.entries.iter().map(|s| s.per_state.get(state).copied().unwrap_or(0)).collect()
+    }
+
+    /// One row per stored generation; the per-state columns cover every
+    /// state seen in the buffer.
+    pub fn to_csv(&self) -> String {
+        let states = self.entries.iter().map(|s| s.per_state.len()).max().unwrap_or(0);
+        let mut out = String::from("generation,population,births,deaths,min_q,max_q,min_r,max_r,active_chunks");
+        for state in 1..states {
+            out.push_str(&format!(",state_{state}"));
+        }
+        out.push('\n');
+
+        for s in &self.entries {
+            let bounds = match s.bounds {
+                Some([a, b, c, d]) => format!("{a},{b},{c},{d}"),
+                None => ",,,".to_string(),
+            };
+            out.push_str(&format!(
+                "{},{},{},{},{},{}",
+                s.generation, s.population, s.births, s.deaths, bounds, s.active_chunks
+            ));
+            for state in 1..states {
+                out.push_str(&format!(",{}", s.per_state.get(state).copied().unwrap_or(0)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,230 @@
+// Periodic tilings with several tile types per unit cell. Cell `(q, r, s)`
+// is tile `s` of the unit cell moved by `q * a + r * b`, the same `s` layer
+// trick the triangle and rhombus grids use. Edges are 1 world unit long
+// (Cairo pentagons excepted, which are the duals of the snub square).
+//
+// - `truncated_square` (4.8.8): octagon, square
+// - `trihexagonal` (3.6.3.6): hexagon, two triangles
+// - `snub_square` (3.3.4.3.4): two squares, four triangles
+// - `cairo`: four pentagons, one around each snub square vertex
+
+use std::sync::OnceLock;
+
+use crate::cell_manager::coords::{SQRT_3, polygon_contains};
+
+type Cell = (i32, i32, i32);
+type Point = (f32, f32);
+// Lattice vectors and tiles of a unit cell
+type UnitCell = (Point, Point, Vec<Vec<Point>>);
+
+pub const TILINGS: [&str; 4] = ["truncated_square", "trihexagonal", "snub_square", "cairo"];
+
+// Corners closer than this are the same corner
+const SNAP: f32 = 1e-3;
+
+pub struct Tiling {
+    /// Lattice vectors between unit cells
+    pub a: Point,
+    pub b: Point,
+    /// Corners of each tile of unit cell `(0, 0)`, in angle order
+    pub tiles: Vec<Vec<Point>>,
+    // Offsets of the tiles sharing an edge / at least a corner, per layer
+    edge: Vec<Vec<Cell>>,
+    vertex: Vec<Vec<Cell>>,
+}
+
+/// The tiling behind a shape name, if it is one of [`TILINGS`].
+pub fn tiling(shape: &str) -> Option<&'static Tiling> {
+    static BUILT: OnceLock<Vec<Tiling>> = OnceLock::new();
+    let index = TILINGS.iter().position(|&t| t == shape)?;
+    let built = BUILT.get_or_init(|| {
+        vec![
+            Tiling::new(truncated_square()),
+            Tiling::new(trihexagonal()),
+            Tiling::new(snub_square()),
+            Tiling::new(cairo()),
+        ]
+    });
+    Some(&built[index])
+}
+
+impl Tiling {
+    fn new((a, b, tiles): UnitCell) -> Self {
+        let mut tiling = Tiling { a, b, tiles, edge: Vec::new(), vertex: Vec::new() };
+        for s in 0..tiling.layers() {
+            let corners = tiling.polygon(0, 0, s);
+            let mut edge = Vec::new();
+            let mut vertex = Vec::new();
+            for cell in tiling.cells_around(0, 0, 2).filter(|&c| c != (0, 0, s)) {
+                let shared = tiling
+                    .polygon(cell.0, cell.1, cell.2)
+                    .iter()
+                    .filter(|p| corners.iter().any(|c| close(*c, **p)))
+                    .count();
+                let offset = (cell.0, cell.1, cell.2 - s);
+                if shared >= 2 {
+                    edge.push(offset);
+                }
+                if shared >= 1 {
+                    vertex.push(offset);
+                }
+            }
+            tiling.edge.push(edge);
+            tiling.vertex.push(vertex);
+        }
+        tiling
+    }
+
+    pub fn layers(&self) -> i32 {
+        self.tiles.len() as i32
+    }
+
+    /// World position of unit cell `(q, r)`'s origin.
+    pub fn origin(&self, q: i32, r: i32) -> Point {
+        let (q, r) = (q as f32, r as f32);
+        (q * self.a.0 + r * self.b.0, q * self.a.1 + r * self.b.1)
+    }
+
+    pub fn polygon(&self, q: i32, r: i32, s: i32) -> Vec<Point> {
+        let (x, y) = self.origin(q, r);
+        self.tiles[s.rem_euclid(self.layers()) as usize].iter().map(|&(px, py)| (x + px, y + py)).collect()
+    }
+
+    /// Average of the corners, which lies inside every tile used here.
+    pub fn center(&self, q: i32, r: i32, s: i32) -> Point {
+        let corners = self.polygon(q, r, s);
+        let n = corners.len() as f32;
+        let (x, y) = corners.iter().fold((0.0, 0.0), |(x, y), &(px, py)| (x + px, y + py));
+        (x / n, y / n)
+    }
+
+    /// Fractional `(q, r)` of a world point.
+    pub fn fractional(&self, x: f32, y: f32) -> Point {
+        let det = self.a.0 * self.b.1 - self.a.1 * self.b.0;
+        ((x * self.b.1 - y * self.b.0) / det, (y * self.a.0 - x * self.a.1) / det)
+    }
+
+    /// Tile under a world point; on an edge, the tile with the nearest centre.
+    pub fn pixel_to_cell(&self, x: f32, y: f32) -> Cell {
+        let (fq, fr) = self.fractional(x, y);
+        let (q, r) = (fq.floor() as i32, fr.floor() as i32);
+        let distance = |&(cq, cr, cs): &Cell| {
+            let (cx, cy) = self.center(cq, cr, cs);
+            (cx - x).powi(2) + (cy - y).powi(2)
+        };
+        self.cells_around(q, r, 1)
+            .find(|&(cq, cr, cs)| polygon_contains(&self.polygon(cq, cr, cs), (x, y)))
+            .or_else(|| self.cells_around(q, r, 1).min_by(|a, b| distance(a).total_cmp(&distance(b))))
+            .unwrap_or((q, r, 0))
+    }
+
+    /// Offsets of the tiles sharing an edge with tile `s`.
+    pub fn edge_offsets(&self, s: i32) -> &[Cell] {
+        &self.edge[s.rem_euclid(self.layers()) as usize]
+    }
+
+    /// Offsets of the tiles sharing at least a corner with tile `s`.
+    pub fn vertex_offsets(&self, s: i32) -> &[Cell] {
+        &self.vertex[s.rem_euclid(self.layers()) as usize]
+    }
+
+    // Every tile of the unit cells within `reach` of (q, r)
+    fn cells_around(&self, q: i32, r: i32, reach: i32) -> impl Iterator<Item = Cell> + '_ {
+        (-reach..=reach)
+            .flat_map(move |dr| (-reach..=reach).map(move |dq| (q + dq, r + dr)))
+            .flat_map(move |(cq, cr)| (0..self.layers()).map(move |s| (cq, cr, s)))
+    }
+}
+
+fn close(a: Point, b: Point) -> bool {
+    (a.0 - b.0).abs() < SNAP && (a.1 - b.1).abs() < SNAP
+}
+
+fn regular(center: Point, radius: f32, degrees: &[f32]) -> Vec<Point> {
+    degrees
+        .iter()
+        .map(|d| {
+            let angle = d.to_radians();
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect()
+}
+
+fn shifted(points: &[Point], (dx, dy): Point) -> Vec<Point> {
+    points.iter().map(|&(x, y)| (x + dx, y + dy)).collect()
+}
+
+// Octagon on the lattice points, squares in the gaps
+fn truncated_square() -> UnitCell {
+    let w = 1.0 + 2f32.sqrt();
+    let (h, e) = (w / 2.0, 0.5);
+    let octagon = vec![(h, -e), (h, e), (e, h), (-e, h), (-h, e), (-h, -e), (-e, -h), (e, -h)];
+    let square = vec![(h, e), (w - e, h), (h, w - e), (e, h)];
+    ((w, 0.0), (0.0, w), vec![octagon, square])
+}
+
+// Hexagons touching at corners, triangles below and above each top corner
+fn trihexagonal() -> UnitCell {
+    let hexagon = regular((0.0, 0.0), 1.0, &[0.0, 60.0, 120.0, 180.0, 240.0, 300.0]);
+    let y = SQRT_3 / 2.0;
+    let lower = vec![(1.0, 0.0), (1.5, y), (0.5, y)];
+    let upper = vec![(0.5, y), (0.0, SQRT_3), (-0.5, y)];
+    ((2.0, 0.0), (1.0, SQRT_3), vec![hexagon, lower, upper])
+}
+
+// Squares turned by +15° and -15°, meeting at corners, with four triangles
+// in the gaps. P, Q, R and S are the four corners of each unit cell.
+fn snub_square() -> UnitCell {
+    let side = (2.0 + SQRT_3).sqrt();
+    let (a, b, ab) = ((side, 0.0), (0.0, side), (side, side));
+    let half = 0.5f32.sqrt();
+    let first = regular((0.0, 0.0), half, &[60.0, 150.0, 240.0, 330.0]);
+    let second = regular((side / 2.0, side / 2.0), half, &[30.0, 120.0, 210.0, 300.0]);
+    let [p, r, s, q] = [first[0], first[1], first[2], first[3]];
+    let add = |(x, y): Point, (dx, dy): Point| (x + dx, y + dy);
+
+    let tiles = vec![
+        first,
+        second,
+        vec![add(s, b), p, add(q, b)],
+        vec![p, q, add(r, a)],
+        vec![add(q, b), add(s, ab), add(r, ab)],
+        vec![add(r, a), add(p, a), add(s, ab)],
+    ];
+    (a, b, tiles)
+}
+
+// The dual of the snub square: each pentagon joins the centres of the five
+// tiles around one snub square corner
+fn cairo() -> UnitCell {
+    let (a, b, snub) = snub_square();
+    let corners = [snub[0][0], snub[0][3], snub[0][1], snub[0][2]];
+
+    let mut around: Vec<(Point, Vec<Point>)> = Vec::new();
+    for q in -1..=1 {
+        for r in -1..=1 {
+            let offset = (q as f32 * a.0 + r as f32 * b.0, q as f32 * a.1 + r as f32 * b.1);
+            around.extend(snub.iter().map(|tile| {
+                let tile = shifted(tile, offset);
+                let n = tile.len() as f32;
+                let center = tile.iter().fold((0.0, 0.0), |(x, y), &(px, py)| (x + px / n, y + py / n));
+                (center, tile)
+            }));
+        }
+    }
+
+    let tiles = corners
+        .iter()
+        .map(|&corner| {
+            let mut centers: Vec<Point> = around
+                .iter()
+                .filter(|(_, tile)| tile.iter().any(|&p| close(p, corner)))
+                .map(|&(center, _)| center)
+                .collect();
+            let angle = |&(x, y): &Point| (y - corner.1).atan2(x - corner.0);
+            centers.sort_by(|p, q| angle(p).total_cmp(&angle(q)));
+            centers
+        })
+        .collect();
+    (a, b, tiles)
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::cell_manager::cellmanager::CellChange;
+
+/// Keyframe interval and retention front ends that step backward start
+/// with; a bare [`CellManager`](crate::CellManager) records nothing.
+pub const DEFAULT_TIMELINE: (u64, u64) = (64, 1024);
+
+/// Full snapshot (`for_each_cell` layout) of one generation.
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    pub generation: u64,
+    pub cells: Vec<i32>,
+}
+
+/// Recorded run used to step backward: a keyframe every `interval`
+/// generations plus the changes that produced every later generation.
+/// Only the last `retention` generations stay restorable.
+pub struct Timeline {
+    interval: u64,
+    retention: u64,
+    keyframes: VecDeque<Keyframe>,
+    // (generation produced, changes from the generation before)
+    deltas: VecDeque<(u64, Vec<CellChange>)>,
+    dirty: bool,
+}
+
+impl Timeline {
+    pub fn new(interval: u64, retention: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            retention,
+            keyframes: VecDeque::new(),
+            deltas: VecDeque::new(),
+            dirty: false,
+        }
+    }
+
+    /// `retention` of 0 turns recording off.
+    pub fn configure(&mut self, interval: u64, retention: u64) {
+        self.interval = interval.max(1);
+        self.retention = retention;
+        if retention == 0 {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+        self.deltas.clear();
+        self.dirty = false;
+    }
+
+    /// The grid was edited: the next recorded step starts from a fresh keyframe.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Records the step from `generation` (whose cells are `live`) to the
+    /// next one. Anything recorded after `generation`, e.g. before jumping
+    /// back, is dropped since the run now branches off.
+    pub fn record_step(&mut self, generation: u64, live: &[i32], changes: Vec<CellChange>) {
+        if self.retention == 0 {
+            return;
+        }
+
+        let stale_from = if self.dirty { generation } else { generation + 1 };
+        while self.keyframes.back().is_some_and(|k| k.generation >= stale_from) {
+            self.keyframes.pop_back();
+        }
+        while self.deltas.back().is_some_and(|d| d.0 > generation) {
+            self.deltas.pop_back();
+        }
+
+        let has_start = self.keyframes.front().is_some_and(|k| k.generation <= generation);
+        let last_keyframe = self.keyframes.back().map(|k| k.generation);
+        if !has_start || self.dirty || (generation.is_multiple_of(self.interval) && last_keyframe != Some(generation)) {
+            self.keyframes.push_back(Keyframe { generation, cells: live.to_vec() });
+        }
+        self.dirty = false;
+
+        self.deltas.push_back((generation + 1, changes));
+        self.enforce_retention(generation + 1);
+    }
+
+    /// `(earliest, latest)` generations that can be restored.
+    pub fn range(&self) -> Option<(u64, u64)> {
+        let first = self.keyframes.front()?.generation;
+        let last = self.deltas.back().map_or(first, |d| d.0);
+        Some((first, last.max(self.keyframes.back()?.generation)))
+    }
+
+    /// Latest keyframe at or before `generation`.
+    pub fn keyframe_for(&self, generation: u64) -> Option<&Keyframe> {
+        self.keyframes.iter().rev().find(|k| k.generation <= generation)
+    }
+
+    /// Changes producing generations `from + 1 ..= to`, in order.
+    pub fn deltas_between(&self, from: u64, to: u64) -> impl Iterator<Item = &CellChange> {
+        self.deltas
+            .iter()
+            .filter(move |(g, _)| *g > from && *g <= to)
+            .flat_map(|(_, changes)| changes)
+    }
+
+    /// Approximate bytes held by keyframes and deltas.
+    pub fn memory_used(&self) -> usize {
+        let keyframes: usize = self.keyframes.iter().map(|k| k.cells.len() * size_of::<i32>()).sum();
+        let deltas: usize = self.deltas.iter().map(|d| d.1.len() * size_of::<CellChange>()).sum();
+        keyframes + deltas
+    }
+
+    // Keeps the newest keyframe at or before the window start so every
+    // generation inside the window stays reachable
+    fn enforce_retention(&mut self, latest: u64) {
+        let window_start = latest.saturating_sub(self.retention);
+        while self.keyframes.len() > 1 && self.keyframes[1].generation <= window_start {
+            self.keyframes.pop_front();
+        }
+        let first = self.keyframes.front().map_or(latest, |k| k.generation);
+        while self.deltas.front().is_some_and(|d| d.0 <= first) {
+            self.deltas.pop_front();
+        }
+    }
+}
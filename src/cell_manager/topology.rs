@@ -0,0 +1,135 @@
+/// Maps coordinates that fall off the edge of the grid back onto it,
+/// according to the topology selected in the UI.
+///
+/// Supported types:
+/// - `none` / `infinite`: coordinates are passed through untouched
+/// - `finite`: anything outside the bounds is dead
+/// - `cylinder`: wraps horizontally, bounded vertically
+/// - `torus`: wraps on both axes, and along `s` for volumetric shapes
+/// - `klein_bottle`: wraps on both axes, flipping `q` across the vertical seam
+/// - `cross_surface`: wraps on both axes, flipping the other axis on each seam
+/// - `sphere`: joins adjacent sides (left with top, right with bottom)
+///
+/// For volumetric shapes `finite` also bounds `s`; the other surfaces leave
+/// it to the grid depth.
+#[derive(Clone)]
+pub struct Topology {
+    pub topology_type: String,
+    bounds: [i32; 6],
+    volumetric: bool,
+}
+
+impl Topology {
+    pub fn new(topology_type: &str, bounds: [i32; 6]) -> Self {
+        Self {
+            topology_type: topology_type.to_string(),
+            bounds,
+            volumetric: false,
+        }
+    }
+
+    /// Whether `s` is a spatial axis to bound and wrap like `q` and `r`.
+    pub fn set_volumetric(&mut self, volumetric: bool) {
+        self.volumetric = volumetric;
+    }
+
+    pub fn set_bounds(&mut self, bounds: [i32; 6]) {
+        self.bounds = bounds;
+    }
+
+    pub fn set_type(&mut self, topology_type: &str) {
+        self.topology_type = topology_type.to_string();
+    }
+
+    /// True when the topology never drops or moves coordinates.
+    pub fn is_unbounded(&self) -> bool {
+        matches!(self.topology_type.as_str(), "none" | "infinite")
+    }
+
+    /// True when crossing a seam mirrors or turns the grid, so an offset
+    /// can point a different way on the other side.
+    pub fn reorients(&self) -> bool {
+        matches!(self.topology_type.as_str(), "klein_bottle" | "cross_surface" | "sphere")
+    }
+
+    /// Returns the on-grid cell for `(q, r, s)`, or `None` if it falls
+    /// outside a bounded edge.
+    pub fn wrap(&self, q: i32, r: i32, s: i32) -> Option<(i32, i32, i32)> {
+        let [min_q, max_q, min_r, max_r, min_s, max_s] = self.bounds;
+        let in_q = q >= min_q && q <= max_q;
+        let in_r = r >= min_r && r <= max_r;
+        let in_s = !self.volumetric || (s >= min_s && s <= max_s);
+
+        match self.topology_type.as_str() {
+            "finite" => (in_q && in_r && in_s).then_some((q, r, s)),
+
+            "torus" if self.volumetric => Some((
+                wrap_axis(q, min_q, max_q),
+                wrap_axis(r, min_r, max_r),
+                wrap_axis(s, min_s, max_s),
+            )),
+
+            "cylinder" => in_r.then(|| (wrap_axis(q, min_q, max_q), r, s)),
+
+            "torus" => Some((wrap_axis(q, min_q, max_q), wrap_axis(r, min_r, max_r), s)),
+
+            "klein_bottle" => {
+                let mut q = q;
+                if !in_r && crossings(r, min_r, max_r) % 2 != 0 {
+                    q = min_q + max_q - q;
+                }
+                Some((wrap_axis(q, min_q, max_q), wrap_axis(r, min_r, max_r), s))
+            }
+
+            "cross_surface" => {
+                let (mut q2, mut r2) = (q, r);
+                if !in_r && crossings(r, min_r, max_r) % 2 != 0 {
+                    q2 = min_q + max_q - q2;
+                }
+                if !in_q && crossings(q, min_q, max_q) % 2 != 0 {
+                    r2 = min_r + max_r - r2;
+                }
+                Some((wrap_axis(q2, min_q, max_q), wrap_axis(r2, min_r, max_r), s))
+            }
+
+            "sphere" => {
+                if in_q && in_r {
+                    return Some((q, r, s));
+                }
+                if !in_q && !in_r {
+                    return None;
+                }
+                // Depth past the edge, 0 for the first row/column outside
+                let (nq, nr) = if q < min_q {
+                    (min_q + (r - min_r), min_r + (min_q - 1 - q))
+                } else if q > max_q {
+                    (min_q + (r - min_r), max_r - (q - max_q - 1))
+                } else if r < min_r {
+                    (min_q + (min_r - 1 - r), min_r + (q - min_q))
+                } else {
+                    (max_q - (r - max_r - 1), min_r + (q - min_q))
+                };
+                (nq >= min_q && nq <= max_q && nr >= min_r && nr <= max_r)
+                    .then_some((nq, nr, s))
+            }
+
+            _ => Some((q, r, s)),
+        }
+    }
+}
+
+fn wrap_axis(v: i32, min: i32, max: i32) -> i32 {
+    let span = max - min + 1;
+    if span <= 0 {
+        return v;
+    }
+    min + (v - min).rem_euclid(span)
+}
+
+fn crossings(v: i32, min: i32, max: i32) -> i32 {
+    let span = max - min + 1;
+    if span <= 0 {
+        return 0;
+    }
+    (v - min).div_euclid(span)
+}
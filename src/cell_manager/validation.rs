@@ -0,0 +1,123 @@
+// Checks on neighborhood offset tables: duplicates, whether adjacency is
+// mutual, and which symmetries of the tiling leave the neighborhood
+// unchanged. Symmetries are the ones the editor uses, see
+// `selection::rotate_cell` and `selection::reflect_cell`.
+
+use std::collections::HashSet;
+
+use crate::cell_manager::Neighborhood;
+use crate::cell_manager::coords::shape_layers;
+use crate::cell_manager::selection::{reflect_cell, rotate_cell, rotation_step};
+
+type Cell = (i32, i32, i32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeighborhoodReport {
+    /// Offsets listed more than once on their layer
+    pub duplicates: usize,
+    /// `(layer, offset)` pairs whose neighbor does not count the cell back
+    pub one_way: Vec<(i32, Cell)>,
+    /// Rotations (in steps of `rotation_step`) mapping the neighborhood to itself
+    pub rotations: u32,
+    /// Whether some reflection maps the neighborhood to itself
+    pub reflections: bool,
+    /// Rotations of the tiling itself
+    pub full_rotations: u32,
+}
+
+impl NeighborhoodReport {
+    pub fn is_reciprocal(&self) -> bool {
+        self.one_way.is_empty()
+    }
+
+    /// `C<n>` or `D<n>`: `n` rotations, with (`D`) or without (`C`) mirror images.
+    pub fn symmetry_group(&self) -> String {
+        format!("{}{}", if self.reflections { "D" } else { "C" }, self.rotations)
+    }
+
+    /// Whether the neighborhood has every symmetry of the tiling, which
+    /// isotropic rules (ones that only see shapes up to rotation and
+    /// reflection) need.
+    pub fn is_isotropic(&self) -> bool {
+        self.reflections && self.rotations == self.full_rotations
+    }
+}
+
+impl Neighborhood {
+    /// Removes repeated offsets (and their weights) from every layer,
+    /// keeping the first. Returns the number removed.
+    pub fn dedup(&mut self) -> usize {
+        let mut removed = 0;
+        for (offsets, weights) in self.adj_neighbors.iter_mut().zip(self.weights.iter_mut()) {
+            let mut seen = HashSet::new();
+            let keep: Vec<bool> = offsets.iter().map(|&o| seen.insert(o)).collect();
+            removed += keep.iter().filter(|k| !**k).count();
+
+            let mut flags = keep.iter();
+            offsets.retain(|_| *flags.next().unwrap());
+            let mut flags = keep.iter();
+            weights.retain(|_| *flags.next().unwrap());
+        }
+        removed
+    }
+
+    /// Deduplicates, then reports on what is left.
+    pub fn validate(&mut self) -> NeighborhoodReport {
+        let duplicates = self.dedup();
+        NeighborhoodReport { duplicates, ..self.report() }
+    }
+
+    /// Report on the neighborhood as it is, without changing it.
+    pub fn report(&self) -> NeighborhoodReport {
+        let layers: Vec<HashSet<Cell>> = (0..self.layers()).map(|s| self.offset_set(s)).collect();
+        let listed: usize = (0..self.layers()).map(|s| self.get_neighbor_offsets(s).len()).sum();
+        let duplicates = listed - layers.iter().map(HashSet::len).sum::<usize>();
+
+        let mut one_way = Vec::new();
+        for s in 0..self.layers() {
+            for &(dq, dr, ds) in self.get_neighbor_offsets(s) {
+                // A single layer serves every `s`, e.g. each z of a cube grid
+                let t = if layers.len() == 1 { 0 } else { s + ds };
+                let back = layers.get(t as usize).is_some_and(|l| l.contains(&(-dq, -dr, -ds)));
+                if !back && !one_way.contains(&(s, (dq, dr, ds))) {
+                    one_way.push((s, (dq, dr, ds)));
+                }
+            }
+        }
+
+        let shape = self.shape.as_str();
+        let full_rotations = 360 / rotation_step(shape);
+        let turns = 0..full_rotations as i32;
+        let rotations = turns.clone().filter(|&t| self.is_invariant(|c| rotate_cell(shape, c, t))).count() as u32;
+        let reflections = turns
+            .into_iter()
+            .any(|t| self.is_invariant(|c| reflect_cell(shape, rotate_cell(shape, c, t))));
+
+        NeighborhoodReport { duplicates, one_way, rotations, reflections, full_rotations }
+    }
+
+    fn layers(&self) -> i32 {
+        shape_layers(&self.shape)
+    }
+
+    fn offset_set(&self, s: i32) -> HashSet<Cell> {
+        self.get_neighbor_offsets(s).iter().copied().collect()
+    }
+
+    // Whether mapping every cell and its neighbors through `symmetry` gives
+    // the neighbors of the mapped cell
+    fn is_invariant(&self, symmetry: impl Fn(Cell) -> Cell) -> bool {
+        (0..self.layers()).all(|s| {
+            let (q0, r0, s0) = symmetry((0, 0, s));
+            let mapped: HashSet<Cell> = self
+                .offset_set(s)
+                .into_iter()
+                .map(|(dq, dr, ds)| {
+                    let (q, r, t) = symmetry((dq, dr, s + ds));
+                    (q - q0, r - r0, t - s0)
+                })
+                .collect();
+            (0..self.layers()).contains(&s0) && mapped == self.offset_set(s0)
+        })
+    }
+}
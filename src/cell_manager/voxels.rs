@@ -0,0 +1,136 @@
+// Exports of volumetric (`cube`) grids for external viewers: MagicaVoxel
+// `.vox` voxels and Wavefront `.obj` isosurface meshes. Both take
+// `for_each_cell` output and map `(q, r, s)` to `(x, y, z)`, so `s` is up.
+
+use std::collections::{HashMap, HashSet};
+
+type Voxel = (i32, i32, i32);
+
+// The .vox format stores each axis in one byte
+const VOX_MAX_SIZE: i32 = 256;
+
+/// MagicaVoxel `.vox` file of the live cells, moved so the lowest corner is
+/// at the origin. The colour index is the cell state (capped at 255), shown
+/// with the viewer's default palette. Fails when the cells span more than
+/// 256 on any axis.
+pub fn write_vox(cells: &[i32]) -> Result<Vec<u8>, String> {
+    let voxels: Vec<(Voxel, u32)> = cells.chunks(4).map(|c| ((c[0], c[1], c[2]), c[3] as u32)).collect();
+    let (min, max) = extent(voxels.iter().map(|&(v, _)| v)).unwrap_or(((0, 0, 0), (0, 0, 0)));
+    let size = (max.0 - min.0 + 1, max.1 - min.1 + 1, max.2 - min.2 + 1);
+    if size.0 > VOX_MAX_SIZE || size.1 > VOX_MAX_SIZE || size.2 > VOX_MAX_SIZE {
+        return Err(format!("cells span {}x{}x{}, .vox holds at most 256 per axis", size.0, size.1, size.2));
+    }
+
+    let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+    for &((q, r, s), state) in &voxels {
+        xyzi.extend([(q - min.0) as u8, (r - min.1) as u8, (s - min.2) as u8, state.min(255) as u8]);
+    }
+    let dims: Vec<u8> = [size.0, size.1, size.2].iter().flat_map(|&n| (n as u32).to_le_bytes()).collect();
+    let mut children = chunk(b"SIZE", &dims);
+    children.extend(chunk(b"XYZI", &xyzi));
+
+    let mut out = b"VOX ".to_vec();
+    out.extend(150u32.to_le_bytes());
+    out.extend(b"MAIN");
+    out.extend(0u32.to_le_bytes());
+    out.extend((children.len() as u32).to_le_bytes());
+    out.extend(children);
+    Ok(out)
+}
+
+// Chunk without children: id, content size, children size, content
+fn chunk(id: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((content.len() as u32).to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    out.extend(content);
+    out
+}
+
+/// Wavefront `.obj` mesh of the boundary between live and dead cells, the
+/// isosurface at half way, built with surface nets: one vertex per unit
+/// cube of cell centres that the surface crosses, one quad per live-dead
+/// pair of face neighbors. Quads face out of the live cells.
+pub fn write_obj(cells: &[i32]) -> String {
+    let live: HashSet<Voxel> = cells.chunks(4).map(|c| (c[0], c[1], c[2])).collect();
+    let is_live = |v: Voxel| live.contains(&v);
+
+    let mut vertices: Vec<(f32, f32, f32)> = Vec::new();
+    let mut index: HashMap<Voxel, usize> = HashMap::new();
+    // Vertex of the cube of centres with lowest corner `min`
+    let mut vertex = |min: Voxel| {
+        *index.entry(min).or_insert_with(|| {
+            vertices.push(crossing_centre(min, is_live));
+            vertices.len()
+        })
+    };
+
+    let mut faces = Vec::new();
+    let mut sorted: Vec<Voxel> = live.iter().copied().collect();
+    sorted.sort_unstable();
+    for &v in &sorted {
+        for axis in 0..3 {
+            let (u, w) = ((axis + 1) % 3, (axis + 2) % 3);
+            for outward in [true, false] {
+                let next = add(v, unit(axis, if outward { 1 } else { -1 }));
+                if is_live(next) {
+                    continue;
+                }
+                // The quad around the edge from `low` to `low + axis`, wound
+                // so its normal is +axis
+                let low = if outward { v } else { next };
+                let mut quad = [(0, 0), (1, 0), (1, 1), (0, 1)]
+                    .map(|(i, j)| vertex(add(add(low, unit(u, -i)), unit(w, -j))));
+                if !outward {
+                    quad.reverse();
+                }
+                faces.push(quad);
+            }
+        }
+    }
+
+    let mut out = format!("# {} cells, {} vertices, {} faces\n", live.len(), vertices.len(), faces.len());
+    for (x, y, z) in vertices {
+        out.push_str(&format!("v {x} {y} {z}\n"));
+    }
+    for [a, b, c, d] in faces {
+        out.push_str(&format!("f {a} {b} {c} {d}\n"));
+    }
+    out
+}
+
+// Average of the midpoints of the cube edges joining a live and a dead centre
+fn crossing_centre(min: Voxel, is_live: impl Fn(Voxel) -> bool) -> (f32, f32, f32) {
+    let (mut sum, mut n) = ((0.0, 0.0, 0.0), 0.0);
+    for corner in 0..8 {
+        let a = add(min, (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1));
+        for axis in 0..3 {
+            let b = add(a, unit(axis, 1));
+            if b.0 > min.0 + 1 || b.1 > min.1 + 1 || b.2 > min.2 + 1 || is_live(a) == is_live(b) {
+                continue;
+            }
+            sum = (sum.0 + (a.0 + b.0) as f32 / 2.0, sum.1 + (a.1 + b.1) as f32 / 2.0, sum.2 + (a.2 + b.2) as f32 / 2.0);
+            n += 1.0;
+        }
+    }
+    (sum.0 / n, sum.1 / n, sum.2 / n)
+}
+
+fn extent(voxels: impl Iterator<Item = Voxel>) -> Option<(Voxel, Voxel)> {
+    voxels.fold(None, |acc, v| {
+        let (lo, hi) = acc.unwrap_or((v, v));
+        Some(((lo.0.min(v.0), lo.1.min(v.1), lo.2.min(v.2)), (hi.0.max(v.0), hi.1.max(v.1), hi.2.max(v.2))))
+    })
+}
+
+fn unit(axis: usize, step: i32) -> Voxel {
+    match axis {
+        0 => (step, 0, 0),
+        1 => (0, step, 0),
+        _ => (0, 0, step),
+    }
+}
+
+fn add(a: Voxel, b: Voxel) -> Voxel {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
@@ -0,0 +1,46 @@
+// Worlds stacked on a `CellManager`'s grid, the "stacked CA worlds that
+// interact" of the README. Every world has the grid's size, shape,
+// neighborhood and topology, but its own cells and rule. Couplings let the
+// rule of one world also count the live cells of another through
+// `(dq, dr, ds)` offsets from the cell being updated, the same offsets
+// neighborhoods use, e.g. a resource field feeding a life world.
+//
+// World 0 is the grid itself, named `main`; the others are kept in
+// `CellManager::worlds` and numbered from 1 in the order they were added.
+
+use crate::cell_manager::{CellBackend, Neighborhood, Rule};
+use crate::cell_manager::hashing::GridHash;
+
+pub const MAIN_WORLD: &str = "main";
+
+pub struct World {
+    pub name: String,
+    pub rule: Rule,
+    pub(crate) cells: CellBackend,
+    pub(crate) hash: GridHash,
+}
+
+impl World {
+    pub(crate) fn new(name: &str, rule: Rule, cells: CellBackend) -> Self {
+        Self { name: name.to_string(), rule, cells, hash: GridHash::default() }
+    }
+
+    pub(crate) fn set_cell(&mut self, q: i32, r: i32, s: i32, value: u32) {
+        let old = self.cells.get_cell(q, r, s);
+        self.cells.set_cell(q, r, s, value);
+        self.hash.update(q, r, s, old, self.cells.get_cell(q, r, s));
+    }
+}
+
+/// Makes the rule of world `target` also see world `source`: every live
+/// source cell at one of `neighborhood`'s offsets adds `weight` times its
+/// kernel weight to the count (rounded, and at least 0, for plain rules).
+/// A negative weight inhibits.
+#[derive(Clone)]
+pub struct Coupling {
+    /// World indices, 0 being the main grid
+    pub target: usize,
+    pub source: usize,
+    pub neighborhood: Neighborhood,
+    pub weight: f32,
+}
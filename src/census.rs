@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::CellManager;
+use crate::cell_manager::CycleState;
+use crate::cell_manager::analysis::{ObjectClass, classify_objects};
+
+// Anything above the 2500 cell threshold gets the unbounded chunked backend
+const PLANE_SIZE: usize = 1 << 16;
+// Small chunks keep `for_each_cell` cheap for soup-sized patterns
+const PLANE_CHUNK_SIZE: usize = 32;
+// Seeds kept per object in the report
+const SEEDS_PER_OBJECT: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct CensusOptions {
+    pub soups: usize,
+    /// Soup `i` uses seed `seed + i`
+    pub seed: u64,
+    /// Side of the square region filled at random
+    pub soup_size: usize,
+    pub density: f32,
+    /// Generations after which a soup counts as unstabilised
+    pub max_generations: usize,
+    /// Longest period looked for, both for stabilisation and classification
+    pub max_period: u64,
+    pub threads: usize,
+    /// Objects seen at most this many times are reported as rare
+    pub rare_threshold: usize,
+}
+
+impl Default for CensusOptions {
+    fn default() -> Self {
+        Self {
+            soups: 1000,
+            seed: 0,
+            soup_size: 16,
+            density: 0.5,
+            max_generations: 10_000,
+            max_period: 64,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            rare_threshold: 1,
+        }
+    }
+}
+
+/// Tally of one apgcode across the census.
+#[derive(Clone, Debug)]
+pub struct CensusEntry {
+    pub apgcode: String,
+    pub class: ObjectClass,
+    pub count: usize,
+    /// Seeds of the first soups the object appeared in
+    pub seeds: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Census {
+    /// Sorted by decreasing count, then apgcode
+    pub entries: Vec<CensusEntry>,
+    /// Soups that had not settled after `max_generations`
+    pub unstabilised: Vec<u64>,
+    pub soups: usize,
+    pub generations: u64,
+}
+
+#[derive(Default)]
+struct SoupResult {
+    objects: Vec<(String, ObjectClass)>,
+    generations: u64,
+    stabilised: bool,
+}
+
+impl Census {
+    /// Census report: a summary header, one CSV row per object and the
+    /// seeds of the rare finds.
+    pub fn report(&self, template: &CellManager, options: &CensusOptions) -> String {
+        let c = &template.config;
+        let mut out = format!(
+            "# census shape={} neighbor_type={} range={} rule={} kernel={}\n",
+            c.shape, c.neighbor_type, c.range, c.rule, c.kernel
+        );
+        out.push_str(&format!(
+            "# soups={} seeds={}..{} soup_size={} density={} generations={}\n",
+            self.soups,
+            options.seed,
+            options.seed.wrapping_add(self.soups as u64),
+            options.soup_size,
+            options.density,
+            self.generations
+        ));
+        if !self.unstabilised.is_empty() {
+            out.push_str(&format!(
+                "# unstabilised soups: {} (seeds {})\n",
+                self.unstabilised.len(),
+                join_seeds(&self.unstabilised)
+            ));
+        }
+
+        out.push_str("apgcode,class,count,seeds\n");
+        for e in &self.entries {
+            out.push_str(&format!("{},{},{},{}\n", e.apgcode, e.class, e.count, join_seeds(&e.seeds)));
+        }
+
+        let rare: Vec<&CensusEntry> = self.rare(options.rare_threshold).collect();
+        if !rare.is_empty() {
+            out.push_str(&format!("# rare finds (seen at most {} times)\n", options.rare_threshold));
+            for e in rare {
+                out.push_str(&format!("# {} ({}) seeds {}\n", e.apgcode, e.class, join_seeds(&e.seeds)));
+            }
+        }
+        out
+    }
+
+    /// Objects seen at most `threshold` times, ignoring the debris that
+    /// did not classify cleanly.
+    pub fn rare(&self, threshold: usize) -> impl Iterator<Item = &CensusEntry> {
+        self.entries.iter().filter(move |e| {
+            e.count <= threshold && !matches!(e.class, ObjectClass::Unstable | ObjectClass::Dies)
+        })
+    }
+}
+
+fn join_seeds(seeds: &[u64]) -> String {
+    seeds.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Runs `options.soups` soups in the grid settings and rule of `template`
+/// across `options.threads` threads. The result does not depend on the
+/// thread count.
+pub fn run_census(template: &CellManager, options: &CensusOptions) -> Census {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<SoupResult>>> = Mutex::new((0..options.soups).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads.clamp(1, options.soups.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= options.soups {
+                        break;
+                    }
+                    let result = run_soup(template, options, options.seed.wrapping_add(i as u64));
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
+        }
+    });
+
+    // Tallied in soup order so the seed lists are deterministic
+    let mut census = Census { soups: options.soups, ..Default::default() };
+    let mut tally: HashMap<String, CensusEntry> = HashMap::new();
+    for (i, result) in results.into_inner().unwrap().into_iter().enumerate() {
+        let result = result.unwrap_or_default();
+        let seed = options.seed.wrapping_add(i as u64);
+        census.generations += result.generations;
+        if !result.stabilised {
+            census.unstabilised.push(seed);
+        }
+        for (apgcode, class) in result.objects {
+            let entry = tally.entry(apgcode.clone()).or_insert_with(|| CensusEntry {
+                apgcode,
+                class,
+                count: 0,
+                seeds: Vec::new(),
+            });
+            entry.count += 1;
+            if entry.seeds.len() < SEEDS_PER_OBJECT && entry.seeds.last() != Some(&seed) {
+                entry.seeds.push(seed);
+            }
+        }
+    }
+
+    census.entries = tally.into_values().collect();
+    census.entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.apgcode.cmp(&b.apgcode)));
+    census
+}
+
+/// Empty unbounded plane with the settings of `template`, holding soup `seed`.
+pub fn soup_manager(template: &CellManager, options: &CensusOptions, seed: u64) -> CellManager {
+    let mut cm = template.blank_copy(PLANE_SIZE, PLANE_SIZE, Some(PLANE_CHUNK_SIZE));
+
+    let half = options.soup_size as i32 / 2;
+    let region = [-half, options.soup_size as i32 - half - 1, -half, options.soup_size as i32 - half - 1];
+    cm.random_cells_seeded(seed, options.density, region);
+    cm
+}
+
+fn run_soup(template: &CellManager, options: &CensusOptions, seed: u64) -> SoupResult {
+    let mut cm = soup_manager(template, options, seed);
+    cm.set_stats_capacity(1);
+    cm.set_hash_history_capacity(options.max_period as usize + 2);
+    cm.record_stats();
+
+    let mut populations = vec![cm.latest_stats().map_or(0, |s| s.population)];
+    let mut stabilised = false;
+    for g in 1..=options.max_generations {
+        cm.step();
+        populations.push(cm.latest_stats().map_or(0, |s| s.population));
+        if cm.cycle_state() != CycleState::Running
+            || (g % 8 == 0 && population_is_periodic(&populations, options.max_period as usize))
+        {
+            stabilised = true;
+            break;
+        }
+    }
+
+    let objects = classify_objects(&cm, options.max_period)
+        .into_iter()
+        .map(|o| (o.apgcode, o.class))
+        .collect();
+    SoupResult { objects, generations: cm.generation, stabilised }
+}
+
+// Population repeating with some period for a long enough stretch; this also
+// catches soups whose only change is spaceships flying off
+fn population_is_periodic(populations: &[usize], max_period: usize) -> bool {
+    let n = populations.len();
+    (1..=max_period.max(1)).any(|p| {
+        let window = (4 * p).max(64);
+        n > window + p && (n - window..n).all(|i| populations[i] == populations[i - p])
+    })
+}
@@ -5,12 +5,44 @@ pub mod cell_manager {
     pub mod chunk_cells;
     pub mod cell_backend;
     pub mod neighbourhood;
+    pub mod kernels;
+    pub mod validation;
+    pub mod topology;
+    pub mod rules;
+    pub mod stats;
+    pub mod hashing;
+    pub mod coords;
+    pub mod tilings;
+    pub mod graph;
+    pub mod history;
+    pub mod timeline;
+    pub mod worlds;
     pub mod cellmanager;
+    pub mod pattern_io;
+    pub mod voxels;
+    pub mod components;
+    pub mod morphology;
+    pub mod selection;
+    pub mod drawing;
+    pub mod analysis;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub mod gif_export;
 
     pub use cell_backend::CellBackend;
     pub use chunk_cells::ChunkedCellManager;
     pub use flat_cells::FlatCellManager;
     pub use neighbourhood::Neighborhood;
+    pub use kernels::Kernel;
+    pub use validation::NeighborhoodReport;
+    pub use graph::GraphManager;
+    pub use worlds::{Coupling, World};
+    pub use topology::Topology;
+    pub use rules::{Rule, Species, Vote};
+    pub use stats::{GenerationStats, StatsHistory};
+    pub use hashing::{CycleState, GridHash};
+    pub use components::{Component, ComponentMap};
+    pub use selection::{Clipboard, PasteMode, Selection};
 }
 
 pub use cell_manager::cellmanager::CellManager;
@@ -23,6 +55,12 @@ pub mod wasm_interface;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod native_interface;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tui;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod census;
+
 
 
 // Re-export wasm interface (so wasm-bindgen can see it)
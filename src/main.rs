@@ -1,5 +1,5 @@
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
-    cell_manager::native_interface::run_native_tests();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cell_manager::native_interface::run_cli(args));
 }
-
@@ -1,4 +1,15 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
 use crate::CellManager;
+use crate::cell_manager::{CycleState, GenerationStats, GraphManager, Neighborhood, Rule};
+use crate::cell_manager::coords::{is_volumetric, shape_layers};
+use crate::tui::run_tui;
+use crate::census::{CensusOptions, run_census, soup_manager};
+use crate::cell_manager::gif_export::{FrameRenderer, GifCrop, GifOptions, palette_for_depth};
+use crate::cell_manager::pattern_io::{parse_pattern, write_cell_list, write_rle};
+use crate::cell_manager::voxels::{write_obj, write_vox};
 
 /// Runs a naive/native test sequence for the current CellManager
 pub fn run_native_tests() {
@@ -46,10 +57,9 @@ pub fn run_native_tests() {
     let neighbors = cm.count_live_neighbors(q, r, s);
     println!("Live neighbors of ({},{},{}) = {}", q, r, s, neighbors);
 
-    // 7️⃣ Flood fill from live cells
-    cm.floodfill();
-    let cells = cm.for_each_cell();
-    println!("Cells after floodfill: {}", cells.len() / 4);
+    // 7️⃣ Bucket fill the region around the origin
+    let filled = cm.bucket_fill(q, r, s, 1).unwrap_or(0);
+    println!("Cells after bucket fill: {} ({filled} filled)", cm.for_each_cell().len() / 4);
 
     // 8️⃣ Clear everything
     cm.clear();
@@ -65,3 +75,667 @@ pub fn run_native_tests() {
 
     println!("\n=== Naive Tests Completed ===");
 }
+
+const USAGE: &str = "\
+usage: cell_manager_bin <command> [options]
+
+commands:
+  run        run a simulation headlessly and write the results
+  tui        interactive terminal viewer (takes the grid options of `run`)
+  census     classify the objects left by many random soups
+  graph      run a rule on the nodes of a network instead of a grid
+  selftest   print the naive CellManager test sequence
+  help       show this message
+
+run options:
+  --pattern FILE       pattern to load (RLE, plaintext .cells or q r s state list)
+  --random             random fill instead of / on top of a pattern
+  --morph OPS          clean up the start, e.g. open:moore:1,close (ops: dilate, erode,
+                       open, close, boundary; kind and range default to the grid's)
+  --width N            grid columns (default 64)
+  --height N           grid rows (default 64)
+  --depth N            tile layers (default from shape: 1, triangle 2, rhombus 3), or
+                       the z size of a cube grid (default the width)
+  --shape S            square | hexagon | triangle | rhombus | cube (3D), or a tiling:
+                       truncated_square | trihexagonal | snub_square | cairo
+  --neighborhood N     neighborhood name for the shape (e.g. moore, hexagonal), a custom
+                       spec (mask:.#./#O#/.#. | offsets:1,0;0,1 | parity:LIST|LIST),
+                       or @FILE holding a spec or a bare mask
+  --range N            neighborhood range (default 1)
+  --topology T         none | finite | cylinder | torus | klein_bottle | cross_surface | sphere
+                       (torus also wraps z on cube grids)
+  --rule RULE          e.g. B3/S23 (default: pattern rule, then B3/S23), a weighted rule
+                       on the kernel sum, e.g. W:B2.5-3.5/S1.5-3.5, a 3D rule
+                       survival/birth/states/M|N, e.g. 4/4/5/M, or competing species
+                       (state k is species k) with one S per species, an optional
+                       interaction matrix and vote, e.g. species:B3/S23/S23 or
+                       species:B3/S23/S234/I1,-1;1,1/weighted
+  --kernel K           neighbor weights: uniform | gaussian[:sigma] | ring[:radius[,width]]
+                       | inverse[:power] (default uniform)
+  --world NAME:RULE    extra world on the same grid with its own rule (repeatable);
+                       --random fills it too. The grid itself is the world 'main'
+  --couple T:S:N[:W]   world T also counts world S's live cells at the offsets of
+                       neighborhood N, each weighing W (default 1, negative inhibits),
+                       e.g. main:food:offsets:0,0:2 (repeatable)
+  --generations N      generations to run, or the cap when --until is given (default 100)
+  --until COND         extinct | static | cycle | population<N | population>N
+  --out FILE           final state; .rle writes RLE, .vox MagicaVoxel voxels, .obj an
+                       isosurface mesh, anything else a cell list
+  --stats FILE         per generation CSV
+  --gif FILE           animated GIF of the run
+  --frames DIR         one PPM image per recorded generation
+  --frame-skip N       generations skipped between recorded frames (default 0)
+  --frame-delay N      GIF frame delay in 1/100 s (default 10)
+  --cell-size N        pixels per cell (default 8)
+  --crop C             full | live[:margin] | min_q,max_q,min_r,max_r
+
+census options (plus --shape, --neighborhood, --range, --depth, --rule and --kernel):
+  --soups N            number of soups (default 1000)
+  --seed N             seed of the first soup; soup i uses seed + i (default 0)
+  --soup-size N        side of the random square (default 16)
+  --density D          live cell probability (default 0.5)
+  --max-generations N  give up on a soup after N generations (default 10000)
+  --max-period N       longest period detected (default 64)
+  --threads N          worker threads (default: all cores)
+  --rare N             flag objects seen at most N times (default 1)
+  --report FILE        write the report here instead of stdout
+  --soup-dir DIR       save the soups of rare finds as DIR/soup_<seed>.rle
+
+graph options (plus --rule, --kernel, --generations and --until of `run`):
+  --edges FILE         edge list, one 'a b [length]' per line
+  --generate G         geometric:n,radius | watts_strogatz:n,k,p | penrose:generations
+  --seed N             seed of the generator and the random start (default 0)
+  --density D          live node probability at the start (default 0.5)
+  --out FILE           final live nodes, one 'label state' per line
+  --stats FILE         per generation CSV
+  --save-graph FILE    write the graph as an edge list
+";
+
+/// Entry point for `cell_manager_bin`; returns the process exit code.
+pub fn run_cli(args: Vec<String>) -> i32 {
+    let Some(command) = args.first() else {
+        print!("{USAGE}");
+        return 0;
+    };
+
+    let result = match command.as_str() {
+        "run" => parse_run_args(&args[1..]).and_then(|opts| run_headless(&opts)),
+        "census" => run_census_command(&args[1..]),
+        "graph" => run_graph_command(&args[1..]),
+        "tui" => parse_run_args(&args[1..])
+            .and_then(|opts| build_manager(&opts))
+            .and_then(|cm| run_tui(cm).map_err(|e| format!("terminal error: {e}"))),
+        "selftest" => {
+            run_native_tests();
+            Ok(())
+        }
+        "help" | "-h" | "--help" => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        other => Err(format!("unknown command '{other}'\n\n{USAGE}")),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e}");
+            1
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopCondition {
+    Extinct,
+    Static,
+    /// Extinct, still, oscillating or moving, as reported by the hash history
+    Cycle,
+    PopulationBelow(usize),
+    PopulationAbove(usize),
+}
+
+impl StopCondition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let number = |v: &str| v.trim().parse::<usize>().map_err(|_| format!("bad population in '{text}'"));
+        match text {
+            "extinct" => Ok(Self::Extinct),
+            "static" => Ok(Self::Static),
+            "cycle" => Ok(Self::Cycle),
+            _ if text.starts_with("population<") => Ok(Self::PopulationBelow(number(&text[11..])?)),
+            _ if text.starts_with("population>") => Ok(Self::PopulationAbove(number(&text[11..])?)),
+            _ => Err(format!("unknown --until condition '{text}'")),
+        }
+    }
+
+    fn is_met(&self, cycle: CycleState, population: usize, changed: usize) -> bool {
+        match *self {
+            Self::Extinct => population == 0,
+            Self::Static => changed == 0,
+            Self::Cycle => cycle != CycleState::Running,
+            Self::PopulationBelow(n) => population < n,
+            Self::PopulationAbove(n) => population > n,
+        }
+    }
+}
+
+/// One `--morph` operation; kind and range default to the grid's own.
+#[derive(Clone, Debug)]
+pub struct MorphStep {
+    pub op: String,
+    pub kind: Option<String>,
+    pub range: Option<i32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RunOptions {
+    pub pattern: Option<String>,
+    pub random: bool,
+    /// Applied in order after loading
+    pub morph: Vec<MorphStep>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub depth: Option<usize>,
+    pub shape: Option<String>,
+    pub neighborhood: Option<String>,
+    pub range: Option<i32>,
+    pub topology: Option<String>,
+    pub rule: Option<String>,
+    pub kernel: Option<String>,
+    /// Extra worlds as (name, rule)
+    pub worlds: Vec<(String, String)>,
+    /// Couplings as (target, source, neighborhood, weight)
+    pub couplings: Vec<(String, String, String, f32)>,
+    pub generations: usize,
+    pub until: Option<StopCondition>,
+    pub out: Option<String>,
+    pub stats: Option<String>,
+    pub gif: Option<String>,
+    pub frames: Option<String>,
+    pub gif_options: GifOptions,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            random: false,
+            morph: Vec::new(),
+            width: None,
+            height: None,
+            depth: None,
+            shape: None,
+            neighborhood: None,
+            range: None,
+            topology: None,
+            rule: None,
+            kernel: None,
+            worlds: Vec::new(),
+            couplings: Vec::new(),
+            generations: 100,
+            until: None,
+            out: None,
+            stats: None,
+            gif: None,
+            frames: None,
+            gif_options: GifOptions::default(),
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
+
+pub fn parse_run_args(args: &[String]) -> Result<RunOptions, String> {
+    let mut opts = RunOptions::default();
+    let mut iter = args.iter();
+
+    while let Some(flag) = iter.next() {
+        if flag == "--random" {
+            opts.random = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?
+            .as_str();
+
+        match flag.as_str() {
+            "--pattern" => opts.pattern = Some(value.to_string()),
+            "--morph" => opts.morph = parse_morph(value)?,
+            "--width" => opts.width = Some(parse_value(flag, value)?),
+            "--height" => opts.height = Some(parse_value(flag, value)?),
+            "--depth" => opts.depth = Some(parse_value(flag, value)?),
+            "--shape" => opts.shape = Some(value.to_string()),
+            "--neighborhood" => opts.neighborhood = Some(value.to_string()),
+            "--range" => opts.range = Some(parse_value(flag, value)?),
+            "--topology" => opts.topology = Some(value.to_string()),
+            "--rule" => opts.rule = Some(value.to_string()),
+            "--kernel" => opts.kernel = Some(value.to_string()),
+            "--world" => match value.split_once(':') {
+                Some((name, rule)) => opts.worlds.push((name.to_string(), rule.to_string())),
+                None => return Err(format!("--world expects NAME:RULE, got '{value}'")),
+            },
+            "--couple" => opts.couplings.push(parse_coupling(value)?),
+            "--generations" => opts.generations = parse_value(flag, value)?,
+            "--until" => opts.until = Some(StopCondition::parse(value)?),
+            "--out" => opts.out = Some(value.to_string()),
+            "--stats" => opts.stats = Some(value.to_string()),
+            "--gif" => opts.gif = Some(value.to_string()),
+            "--frames" => opts.frames = Some(value.to_string()),
+            "--frame-skip" => opts.gif_options.frame_skip = parse_value(flag, value)?,
+            "--frame-delay" => opts.gif_options.frame_delay = parse_value(flag, value)?,
+            "--cell-size" => opts.gif_options.cell_size = parse_value(flag, value)?,
+            "--crop" => opts.gif_options.crop = parse_crop(value)?,
+            other => return Err(format!("unknown option '{other}'")),
+        }
+    }
+    Ok(opts)
+}
+
+// TARGET:SOURCE:NEIGHBORHOOD[:WEIGHT]; specs hold colons themselves, so a
+// trailing number is only taken as the weight when something precedes it
+fn parse_coupling(value: &str) -> Result<(String, String, String, f32), String> {
+    let mut parts = value.splitn(3, ':');
+    let (Some(target), Some(source), Some(rest)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(format!("--couple expects TARGET:SOURCE:NEIGHBORHOOD[:WEIGHT], got '{value}'"));
+    };
+    let (spec, weight) = match rest.rsplit_once(':').map(|(spec, w)| (spec, w.parse::<f32>())) {
+        Some((spec, Ok(weight))) => (spec, weight),
+        _ => (rest, 1.0),
+    };
+    Ok((target.to_string(), source.to_string(), spec.to_string(), weight))
+}
+
+fn parse_morph(value: &str) -> Result<Vec<MorphStep>, String> {
+    value
+        .split(',')
+        .map(|step| {
+            let mut parts = step.trim().split(':');
+            let op = parts.next().unwrap_or_default().to_string();
+            if !matches!(op.as_str(), "dilate" | "erode" | "open" | "close" | "boundary") {
+                return Err(format!("unknown --morph operation '{op}'"));
+            }
+            let kind = parts.next().map(str::to_string);
+            let range = parts.next().map(|r| parse_value("--morph", r)).transpose()?;
+            Ok(MorphStep { op, kind, range })
+        })
+        .collect()
+}
+
+fn parse_crop(value: &str) -> Result<GifCrop, String> {
+    match value {
+        "full" => Ok(GifCrop::Full),
+        "live" => Ok(GifCrop::LiveBounds(1)),
+        _ if value.starts_with("live:") => Ok(GifCrop::LiveBounds(parse_value("--crop", &value[5..])?)),
+        _ => {
+            let parts: Vec<i32> = value
+                .split(',')
+                .map(|v| parse_value("--crop", v.trim()))
+                .collect::<Result<_, _>>()?;
+            match parts[..] {
+                [a, b, c, d] => Ok(GifCrop::Region([a, b, c, d])),
+                _ => Err(format!("--crop expects full, live[:margin] or 4 numbers, got '{value}'")),
+            }
+        }
+    }
+}
+
+// Population of each species of a species rule, e.g. `species 1: 40, 2: 35`
+fn print_species(rule: &Rule, stats: Option<&GenerationStats>) {
+    let Some(species) = &rule.species else { return };
+    let per_state = stats.map_or(&[][..], |s| &s.per_state);
+    let counts: Vec<String> = (1..=species.count())
+        .map(|k| format!("{k}: {}", per_state.get(k).copied().unwrap_or(0)))
+        .collect();
+    println!("species {}", counts.join(", "));
+}
+
+pub fn describe_cycle(state: CycleState) -> String {
+    match state {
+        CycleState::Running => "no cycle detected".to_string(),
+        CycleState::Extinct { since } => format!("extinct since generation {since}"),
+        CycleState::Still { since } => format!("still since generation {since}"),
+        CycleState::Oscillating { start, period } => format!("period {period} cycle from generation {start}"),
+        CycleState::Moving { start, period, dq, dr } => {
+            format!("period {period} cycle moving ({dq},{dr}) from generation {start}")
+        }
+    }
+}
+
+fn default_depth(shape: &str, width: usize) -> usize {
+    match is_volumetric(shape) {
+        true => width,
+        false => shape_layers(shape) as usize,
+    }
+}
+
+fn default_neighborhood(shape: &str) -> &'static str {
+    match shape {
+        "hexagon" => "hexagonal",
+        "rhombus" => "Qbert",
+        _ => "moore",
+    }
+}
+
+/// Builds a manager from the options, falling back to the settings stored
+/// in the pattern file and then to the defaults.
+pub fn build_manager(opts: &RunOptions) -> Result<CellManager, String> {
+    let pattern = match &opts.pattern {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+            Some(parse_pattern(&text)?)
+        }
+        None => None,
+    };
+    let prop = |key: &str| pattern.as_ref().and_then(|p| p.property(key)).map(str::to_string);
+    let prop_num = |key: &str| prop(key).and_then(|v| v.parse::<usize>().ok());
+
+    let shape = opts.shape.clone().or_else(|| prop("shape")).unwrap_or_else(|| "square".to_string());
+    let width = opts.width.or_else(|| prop_num("width")).unwrap_or(64);
+    let height = opts.height.or_else(|| prop_num("height")).unwrap_or(64);
+    let depth = opts.depth.or_else(|| prop_num("depth")).unwrap_or_else(|| default_depth(&shape, width));
+    let neighborhood = opts
+        .neighborhood
+        .clone()
+        .or_else(|| prop("neighbor_type"))
+        .unwrap_or_else(|| default_neighborhood(&shape).to_string());
+    let neighborhood = match neighborhood.strip_prefix('@') {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+            // A file without a spec prefix holds a bare mask
+            match Neighborhood::is_custom_spec(text.trim_start()) {
+                true => text.trim_start().to_string(),
+                false => format!("mask:{text}"),
+            }
+        }
+        None => neighborhood,
+    };
+    let range = opts.range.or_else(|| prop_num("range").map(|r| r as i32)).unwrap_or(1);
+    let topology = opts.topology.clone().or_else(|| prop("topology")).unwrap_or_else(|| "none".to_string());
+
+    Neighborhood::parse(&shape, &neighborhood, range)?;
+
+    let mut cm = CellManager::new(width, height, depth, None);
+    cm.change_grid_properties(shape, neighborhood, range, topology);
+
+    let rule = opts
+        .rule
+        .clone()
+        .or_else(|| pattern.as_ref().and_then(|p| p.rule.clone()))
+        .unwrap_or_else(|| cm.config.rule.clone());
+    if !cm.set_rule(&rule) {
+        return Err(format!("cannot parse rule '{rule}'"));
+    }
+    if let Some(kernel) = opts.kernel.clone().or_else(|| prop("kernel"))
+        && !cm.set_kernel(&kernel)
+    {
+        return Err(format!("cannot parse kernel '{kernel}'"));
+    }
+
+    if let Some(generation) = prop("generation").and_then(|g| g.parse().ok()) {
+        cm.generation = generation;
+    }
+    for (name, rule) in &opts.worlds {
+        if !cm.add_world(name, rule) {
+            return Err(format!("cannot add world '{name}' with rule '{rule}'"));
+        }
+    }
+    for (target, source, spec, weight) in &opts.couplings {
+        cm.couple(target, source, spec, *weight)?;
+    }
+    if opts.random {
+        cm.random_cells();
+        let [min_q, max_q, min_r, max_r, _, _] = cm.get_bounds();
+        for (name, _) in &opts.worlds {
+            cm.random_world_cells_seeded(name, fastrand::u64(..), 0.42, [min_q, max_q, min_r, max_r]);
+        }
+    }
+    if let Some(pattern) = pattern {
+        cm.batch_update(pattern.cells, None);
+    }
+    for step in &opts.morph {
+        let kind = step.kind.clone().unwrap_or_else(|| cm.config.neighbor_type.clone());
+        let element = cm.structuring_element(&kind, step.range.unwrap_or(cm.config.range));
+        match step.op.as_str() {
+            "dilate" => cm.dilate(&element, 1),
+            "erode" => cm.erode(&element),
+            "open" => cm.open(&element, 1),
+            "close" => cm.close(&element, 1),
+            _ => cm.extract_boundary(&element),
+        };
+    }
+    Ok(cm)
+}
+
+pub fn run_headless(opts: &RunOptions) -> Result<(), String> {
+    let mut cm = build_manager(opts)?;
+    let capture = opts.gif.is_some() || opts.frames.is_some();
+    let stride = opts.gif_options.frame_skip + 1;
+
+    let mut snapshots = Vec::new();
+    cm.set_stats_capacity(opts.generations.saturating_add(1));
+    cm.set_hash_history_capacity(opts.generations.clamp(1, 1 << 16));
+    cm.record_stats();
+    if capture {
+        snapshots.push(cm.for_each_cell());
+    }
+
+    let mut stop_reason = "generation limit";
+    for g in 1..=opts.generations {
+        let changed = cm.step();
+        let population = cm.latest_stats().map_or(0, |s| s.population);
+
+        if capture && g % stride == 0 {
+            snapshots.push(cm.for_each_cell());
+        }
+        if let Some(cond) = &opts.until
+            && cond.is_met(cm.cycle_state(), population, changed)
+        {
+            stop_reason = "--until condition";
+            break;
+        }
+    }
+
+    let cells = cm.for_each_cell();
+    println!(
+        "stopped at generation {} ({stop_reason}), population {}, {}",
+        cm.generation,
+        cells.len() / 4,
+        describe_cycle(cm.cycle_state())
+    );
+    print_species(cm.get_rule(), cm.latest_stats());
+
+    let write = |path: &str, data: &[u8]| fs::write(path, data).map_err(|e| format!("cannot write {path}: {e}"));
+
+    if let Some(path) = &opts.out {
+        let data = if path.ends_with(".rle") {
+            write_rle(&cells, &cm.config.rule).into_bytes()
+        } else if path.ends_with(".vox") {
+            write_vox(&cells)?
+        } else if path.ends_with(".obj") {
+            write_obj(&cells).into_bytes()
+        } else {
+            write_cell_list(&cm).into_bytes()
+        };
+        write(path, &data)?;
+    }
+    if let Some(path) = &opts.stats {
+        write(path, cm.stats_csv().as_bytes())?;
+    }
+    if let Some(path) = &opts.gif {
+        let file = fs::File::create(path).map_err(|e| format!("cannot write {path}: {e}"))?;
+        cm.write_gif_frames(&snapshots, &opts.gif_options, io::BufWriter::new(file))
+            .map_err(|e| format!("cannot encode {path}: {e}"))?;
+    }
+    if let Some(dir) = &opts.frames {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {dir}: {e}"))?;
+        let region = cm.gif_region(&opts.gif_options.crop, &snapshots);
+        let renderer = FrameRenderer::new(&cm.config.shape, cm.palette_states(), region, opts.gif_options.cell_size)
+            .map_err(|e| format!("cannot render frames: {e}"))?;
+        let palette = palette_for_depth(cm.palette_states());
+
+        for (i, frame) in snapshots.iter().enumerate() {
+            let path = Path::new(dir).join(format!("frame_{:06}.ppm", i * stride));
+            let file = fs::File::create(&path).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+            renderer
+                .write_ppm(frame, &palette, io::BufWriter::new(file))
+                .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn run_census_command(args: &[String]) -> Result<(), String> {
+    let mut options = CensusOptions::default();
+    let mut report_path = None;
+    let mut soup_dir = None;
+    let mut grid_args = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?
+            .as_str();
+
+        match flag.as_str() {
+            "--soups" => options.soups = parse_value(flag, value)?,
+            "--seed" => options.seed = parse_value(flag, value)?,
+            "--soup-size" => options.soup_size = parse_value(flag, value)?,
+            "--density" => options.density = parse_value(flag, value)?,
+            "--max-generations" => options.max_generations = parse_value(flag, value)?,
+            "--max-period" => options.max_period = parse_value(flag, value)?,
+            "--threads" => options.threads = parse_value(flag, value)?,
+            "--rare" => options.rare_threshold = parse_value(flag, value)?,
+            "--report" => report_path = Some(value.to_string()),
+            "--soup-dir" => soup_dir = Some(value.to_string()),
+            "--shape" | "--neighborhood" | "--range" | "--depth" | "--rule" | "--kernel" => {
+                grid_args.extend([flag.clone(), value.to_string()]);
+            }
+            other => return Err(format!("unknown census option '{other}'")),
+        }
+    }
+
+    let template = build_manager(&parse_run_args(&grid_args)?)?;
+    let census = run_census(&template, &options);
+    let report = census.report(&template, &options);
+
+    println!(
+        "{} soups, {} generations, {} distinct objects, {} rare, {} unstabilised",
+        census.soups,
+        census.generations,
+        census.entries.len(),
+        census.rare(options.rare_threshold).count(),
+        census.unstabilised.len()
+    );
+    match &report_path {
+        Some(path) => fs::write(path, &report).map_err(|e| format!("cannot write {path}: {e}"))?,
+        None => print!("{report}"),
+    }
+
+    if let Some(dir) = &soup_dir {
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {dir}: {e}"))?;
+        let mut seeds: Vec<u64> = census.rare(options.rare_threshold).flat_map(|e| e.seeds.clone()).collect();
+        seeds.sort_unstable();
+        seeds.dedup();
+        for seed in seeds {
+            let soup = soup_manager(&template, &options, seed);
+            let path = Path::new(dir).join(format!("soup_{seed}.rle"));
+            fs::write(&path, write_rle(&soup.for_each_cell(), &template.config.rule))
+                .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn run_graph_command(args: &[String]) -> Result<(), String> {
+    let mut source = None;
+    let (mut seed, mut density) = (0, 0.5);
+    let (mut rule, mut kernel) = (None, None);
+    let (mut generations, mut until) = (100usize, None);
+    let (mut out, mut stats, mut save_graph) = (None, None, None);
+    let mut iter = args.iter();
+
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?
+            .as_str();
+
+        match flag.as_str() {
+            "--edges" | "--generate" => source = Some((flag.as_str(), value)),
+            "--seed" => seed = parse_value(flag, value)?,
+            "--density" => density = parse_value(flag, value)?,
+            "--rule" => rule = Some(value),
+            "--kernel" => kernel = Some(value),
+            "--generations" => generations = parse_value(flag, value)?,
+            "--until" => until = Some(StopCondition::parse(value)?),
+            "--out" => out = Some(value),
+            "--stats" => stats = Some(value),
+            "--save-graph" => save_graph = Some(value),
+            other => return Err(format!("unknown graph option '{other}'")),
+        }
+    }
+
+    let mut graph = match source {
+        Some(("--edges", path)) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+            GraphManager::from_edge_list(&text).map_err(|e| format!("{path}: {e}"))?
+        }
+        Some((_, spec)) => GraphManager::generate(spec, seed)?,
+        None => return Err("graph needs --edges FILE or --generate G".to_string()),
+    };
+    if let Some(rule) = rule
+        && !graph.set_rule(rule)
+    {
+        return Err(format!("invalid rule '{rule}'"));
+    }
+    if let Some(kernel) = kernel
+        && !graph.set_kernel(kernel)
+    {
+        return Err(format!("invalid kernel '{kernel}'"));
+    }
+    graph.random_cells_seeded(seed, density);
+
+    graph.set_stats_capacity(generations.saturating_add(1));
+    graph.set_hash_history_capacity(generations.clamp(1, 1 << 16));
+    graph.record_stats();
+
+    let mut stop_reason = "generation limit";
+    for _ in 0..generations {
+        let changed = graph.step();
+        let population = graph.latest_stats().map_or(0, |s| s.population);
+        if let Some(cond) = &until
+            && cond.is_met(graph.cycle_state(), population, changed)
+        {
+            stop_reason = "--until condition";
+            break;
+        }
+    }
+
+    let live = graph.for_each_cell();
+    println!(
+        "{} nodes, {} edges; stopped at generation {} ({stop_reason}), population {}, {}",
+        graph.node_count(),
+        graph.edge_count(),
+        graph.generation,
+        live.len() / 4,
+        describe_cycle(graph.cycle_state())
+    );
+    print_species(graph.get_rule(), graph.latest_stats());
+
+    let write = |path: &str, text: String| fs::write(path, text).map_err(|e| format!("cannot write {path}: {e}"));
+    if let Some(path) = out {
+        let lines = live.chunks(4).map(|c| format!("{} {}\n", graph.label(c[0] as usize).unwrap_or_default(), c[3]));
+        write(path, lines.collect())?;
+    }
+    if let Some(path) = stats {
+        write(path, graph.stats_csv())?;
+    }
+    if let Some(path) = save_graph {
+        write(path, graph.to_edge_list())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,462 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+use crate::CellManager;
+use crate::cell_manager::Neighborhood;
+use crate::cell_manager::gif_export::palette_for_depth;
+use crate::cell_manager::timeline::DEFAULT_TIMELINE;
+use crate::cell_manager::tilings::tiling;
+
+const HELP: &str = "space play/pause  n step  , back  arrows cursor  HJKL pan  +/- zoom  e edit  0-9 state  tab layer  \
+                    N neighborhood  R range  T topology  r random  c clear  u/U undo/redo  [/] speed  q quit";
+
+const TOPOLOGIES: [&str; 7] = ["none", "finite", "cylinder", "torus", "klein_bottle", "cross_surface", "sphere"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Zoom {
+    /// One glyph per cell (two columns for square cells)
+    Glyph,
+    /// Two cells per character, stacked with `▀`/`▄`
+    HalfBlock,
+    /// Eight cells per character as braille dots
+    Braille,
+}
+
+/// Cells are drawn on a "dot" lattice where `x = a*q + b*r + c*s` and `y = r`.
+/// Hexagons use doubled columns so odd rows come out staggered.
+pub struct Layout {
+    a: i32,
+    b: i32,
+    c: i32,
+    all_layers: bool,
+}
+
+impl Layout {
+    pub fn for_shape(shape: &str) -> Self {
+        match shape {
+            "hexagon" => Layout { a: 2, b: 1, c: 0, all_layers: false },
+            "triangle" => Layout { a: 2, b: 0, c: 1, all_layers: true },
+            "rhombus" => Layout { a: 6, b: 3, c: 2, all_layers: true },
+            // Tilings show each unit cell as a run of its tiles
+            _ if let Some(t) = tiling(shape) => Layout { a: t.layers(), b: 0, c: 1, all_layers: true },
+            _ => Layout { a: 1, b: 0, c: 0, all_layers: false },
+        }
+    }
+
+    /// Dot holding cell `(q, r, s)`.
+    pub fn dot(&self, q: i32, r: i32, s: i32) -> (i32, i32) {
+        (self.a * q + self.b * r + self.c * s, r)
+    }
+}
+
+/// State of the viewer between frames.
+pub struct TuiApp {
+    cm: CellManager,
+    layout: Layout,
+    zoom: Zoom,
+    playing: bool,
+    delay: Duration,
+    cursor: (i32, i32, i32),
+    /// Dot coordinates at the centre of the screen
+    view: (i32, i32),
+    brush: u32,
+    message: String,
+}
+
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Interactive viewer: renders `cm` in the terminal until the user quits.
+pub fn run_tui(cm: CellManager) -> io::Result<()> {
+    let mut app = TuiApp::new(cm);
+    let _guard = TerminalGuard::enter()?;
+    let mut out = io::stdout();
+    let mut last_step = Instant::now();
+
+    loop {
+        app.draw(&mut out)?;
+
+        let timeout = if app.playing {
+            app.delay.saturating_sub(last_step.elapsed())
+        } else {
+            Duration::from_millis(500)
+        };
+
+        if event::poll(timeout)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle_key(key)
+        {
+            return Ok(());
+        }
+
+        if app.playing && last_step.elapsed() >= app.delay {
+            app.cm.step();
+            last_step = Instant::now();
+        }
+    }
+}
+
+impl TuiApp {
+    pub fn new(mut cm: CellManager) -> Self {
+        // `,` steps backward
+        let (keyframe_interval, retention) = DEFAULT_TIMELINE;
+        cm.set_timeline(keyframe_interval, retention);
+        let layout = Layout::for_shape(&cm.config.shape);
+        Self {
+            cm,
+            layout,
+            zoom: Zoom::Glyph,
+            playing: false,
+            delay: Duration::from_millis(100),
+            cursor: (0, 0, 0),
+            view: (0, 0),
+            brush: 1,
+            message: String::new(),
+        }
+    }
+
+    /// Dots covered by one character in each direction.
+    fn dots_per_char(&self) -> (i32, i32) {
+        match self.zoom {
+            Zoom::Glyph => (1, 1),
+            Zoom::HalfBlock => (1, 2),
+            Zoom::Braille => (2, 4),
+        }
+    }
+
+    /// Characters used by one dot column in glyph mode.
+    fn glyph_width(&self) -> i32 {
+        if self.zoom == Zoom::Glyph && self.layout.a == 1 { 2 } else { 1 }
+    }
+
+    pub fn cm(&self) -> &CellManager {
+        &self.cm
+    }
+
+    pub fn cursor(&self) -> (i32, i32, i32) {
+        self.cursor
+    }
+
+    /// Dot at the centre of the screen.
+    pub fn view(&self) -> (i32, i32) {
+        self.view
+    }
+
+    /// Applies one key press; returns false when the viewer should quit.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let (q, r, s) = self.cursor;
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let (dx, dy) = self.dots_per_char();
+        let pan = (8 * dx, 4 * dy);
+        self.message.clear();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char(' ') => self.playing = !self.playing,
+            KeyCode::Char('n') | KeyCode::Char('.') => {
+                self.playing = false;
+                self.cm.step();
+            }
+
+            KeyCode::Char(',') => {
+                self.playing = false;
+                if !self.cm.step_back() {
+                    self.message = "no earlier generation recorded".to_string();
+                }
+            }
+
+            KeyCode::Left if shift => self.view.0 -= pan.0,
+            KeyCode::Right if shift => self.view.0 += pan.0,
+            KeyCode::Up if shift => self.view.1 -= pan.1,
+            KeyCode::Down if shift => self.view.1 += pan.1,
+            KeyCode::Char('H') => self.view.0 -= pan.0,
+            KeyCode::Char('L') => self.view.0 += pan.0,
+            KeyCode::Char('K') => self.view.1 -= pan.1,
+            KeyCode::Char('J') => self.view.1 += pan.1,
+
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(q - 1, r, s),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(q + 1, r, s),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(q, r - 1, s),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(q, r + 1, s),
+            KeyCode::Tab => {
+                let layers = self.cm.config.depth.max(1) as i32;
+                self.cursor.2 = (s + 1) % layers;
+            }
+
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.zoom = match self.zoom {
+                    Zoom::Braille => Zoom::HalfBlock,
+                    _ => Zoom::Glyph,
+                }
+            }
+            KeyCode::Char('-') => {
+                self.zoom = match self.zoom {
+                    Zoom::Glyph => Zoom::HalfBlock,
+                    _ => Zoom::Braille,
+                }
+            }
+
+            KeyCode::Char('e') | KeyCode::Enter => {
+                let value = if self.cm.get_cell(q, r, s) == 0 { self.brush } else { 0 };
+                self.cm.set_cell(q, r, s, value);
+            }
+            KeyCode::Char(d) if d.is_ascii_digit() => {
+                self.brush = d.to_digit(10).unwrap();
+                self.cm.set_cell(q, r, s, self.brush);
+            }
+
+            KeyCode::Char('N') => {
+                let kinds = Neighborhood::kinds_for_shape(&self.cm.config.shape);
+                if !kinds.is_empty() {
+                    let current = kinds.iter().position(|k| *k == self.cm.config.neighbor_type);
+                    let next = kinds[current.map_or(0, |i| (i + 1) % kinds.len())];
+                    self.change_grid(next.to_string(), self.cm.config.range, self.cm.config.topology_type.clone());
+                }
+            }
+            KeyCode::Char('R') => {
+                let range = self.cm.config.range % 5 + 1;
+                self.change_grid(self.cm.config.neighbor_type.clone(), range, self.cm.config.topology_type.clone());
+            }
+            KeyCode::Char('T') => {
+                let current = TOPOLOGIES.iter().position(|t| *t == self.cm.config.topology_type);
+                let next = TOPOLOGIES[current.map_or(0, |i| (i + 1) % TOPOLOGIES.len())];
+                self.change_grid(self.cm.config.neighbor_type.clone(), self.cm.config.range, next.to_string());
+            }
+
+            KeyCode::Char('u') => self.undo(false),
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => self.undo(false),
+            KeyCode::Char('U') => self.undo(true),
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => self.undo(true),
+
+            KeyCode::Char('r') => self.cm.random_cells(),
+            KeyCode::Char('c') => self.cm.clear(),
+            KeyCode::Char('[') => self.delay = (self.delay * 2).min(Duration::from_secs(2)),
+            KeyCode::Char(']') => self.delay = (self.delay / 2).max(Duration::from_millis(5)),
+            _ => {}
+        }
+        true
+    }
+
+    fn change_grid(&mut self, neighbor_type: String, range: i32, topology: String) {
+        let shape = self.cm.config.shape.clone();
+        self.cm.change_grid_properties(shape, neighbor_type, range, topology);
+        self.message = format!(
+            "neighborhood {} range {} topology {}",
+            self.cm.config.neighbor_type, self.cm.config.range, self.cm.config.topology_type
+        );
+    }
+
+    fn undo(&mut self, redo: bool) {
+        let label = if redo { self.cm.history().redo_label() } else { self.cm.history().undo_label() };
+        let label = label.unwrap_or_default().to_string();
+        let done = if redo { self.cm.redo() } else { self.cm.undo() };
+        self.message = match (done, redo) {
+            (true, false) => format!("undid {label}"),
+            (true, true) => format!("redid {label}"),
+            (false, false) => "nothing to undo".to_string(),
+            (false, true) => "nothing to redo".to_string(),
+        };
+    }
+
+    fn move_cursor(&mut self, q: i32, r: i32, s: i32) {
+        self.cursor = (q, r, s);
+
+        // Keep the cursor on screen
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let (dx, dy) = self.dots_per_char();
+        let half_w = (cols as i32 / self.glyph_width()) * dx / 2 - dx;
+        let half_h = (rows.saturating_sub(2) as i32) * dy / 2 - dy;
+        let (x, y) = self.layout.dot(q, r, s);
+
+        if x < self.view.0 - half_w || x > self.view.0 + half_w {
+            self.view.0 = x;
+        }
+        if y < self.view.1 - half_h || y > self.view.1 + half_h {
+            self.view.1 = y;
+        }
+    }
+
+    /// Cell states on the visible dot rectangle, plus the origin of that rectangle.
+    pub fn dot_buffer(&self, width: i32, height: i32) -> (Vec<Option<(u32, i32)>>, i32, i32) {
+        let (width, height) = (width.max(0), height.max(0));
+        let x0 = self.view.0 - width / 2;
+        let y0 = self.view.1 - height / 2;
+        let mut dots = vec![None; (width * height) as usize];
+        let Layout { a, b, c, all_layers } = self.layout;
+
+        let layers: Vec<i32> = if all_layers {
+            (0..self.cm.config.depth.max(1) as i32).collect()
+        } else {
+            vec![self.cursor.2]
+        };
+
+        for r in y0..y0 + height {
+            for &s in &layers {
+                let base = b * r + c * s;
+                let q_min = (x0 - base).div_euclid(a);
+                let q_max = (x0 + width - base).div_euclid(a);
+                for q in q_min..=q_max {
+                    let (x, y) = self.layout.dot(q, r, s);
+                    if x >= x0 && x < x0 + width {
+                        let idx = ((y - y0) * width + (x - x0)) as usize;
+                        dots[idx] = Some((self.cm.get_cell(q, r, s), s));
+                    }
+                }
+            }
+        }
+        (dots, x0, y0)
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (cols, rows) = terminal::size()?;
+        self.render(out, cols, rows)
+    }
+
+    /// Draws a frame for a `cols` x `rows` terminal: the grid, then the
+    /// status and help lines. Terminals too small for the grid get the
+    /// status lines only.
+    pub fn render(&self, out: &mut impl Write, cols: u16, rows: u16) -> io::Result<()> {
+        let (cols, rows) = (cols as i32, rows.saturating_sub(2) as i32);
+        if rows > 0 && cols > 0 {
+            self.render_grid(out, cols, rows)?;
+        }
+
+        let c = &self.cm.config;
+        let status = format!(
+            "gen {}  pop {}  {}  {} {} r{}  {}  cursor ({},{},{})  {}  {}ms  {}",
+            self.cm.generation,
+            self.cm.for_each_cell().len() / 4,
+            if self.playing { "▶" } else { "⏸" },
+            c.shape,
+            c.neighbor_type,
+            c.range,
+            c.topology_type,
+            self.cursor.0,
+            self.cursor.1,
+            self.cursor.2,
+            c.rule,
+            self.delay.as_millis(),
+            self.message,
+        );
+        queue!(
+            out,
+            cursor::MoveTo(0, rows as u16),
+            Print(truncate(&status, cols as usize)),
+            terminal::Clear(ClearType::UntilNewLine),
+            cursor::MoveTo(0, rows as u16 + 1),
+            SetForegroundColor(Color::DarkGrey),
+            Print(truncate(HELP, cols as usize)),
+            ResetColor,
+            terminal::Clear(ClearType::UntilNewLine),
+        )?;
+        out.flush()
+    }
+
+    fn render_grid(&self, out: &mut impl Write, cols: i32, rows: i32) -> io::Result<()> {
+        let (dx, dy) = self.dots_per_char();
+        let gw = self.glyph_width();
+        let (width, height) = ((cols / gw) * dx, rows * dy);
+
+        let (dots, x0, y0) = self.dot_buffer(width, height);
+        let palette = palette_for_depth(self.cm.palette_states().max(self.brush as usize));
+        let (cx, cy) = self.layout.dot(self.cursor.0, self.cursor.1, self.cursor.2);
+        let cursor_char = ((cx - x0).div_euclid(dx), (cy - y0).div_euclid(dy));
+
+        for row in 0..rows {
+            queue!(out, cursor::MoveTo(0, row as u16))?;
+            for col in 0..cols / gw {
+                let dot = |ix: i32, iy: i32| -> Option<(u32, i32)> {
+                    let (x, y) = (col * dx + ix, row * dy + iy);
+                    dots[(y * width + x) as usize]
+                };
+
+                let (glyph, state) = match self.zoom {
+                    Zoom::Glyph => match dot(0, 0) {
+                        Some((v, s)) => (self.cell_glyph(v, s), v),
+                        None => (" ".repeat(gw as usize), 0),
+                    },
+                    Zoom::HalfBlock => {
+                        let top = dot(0, 0).is_some_and(|d| d.0 != 0);
+                        let bottom = dot(0, 1).is_some_and(|d| d.0 != 0);
+                        let g = match (top, bottom) {
+                            (true, true) => "█",
+                            (true, false) => "▀",
+                            (false, true) => "▄",
+                            _ => " ",
+                        };
+                        (g.to_string(), top as u32 | bottom as u32)
+                    }
+                    Zoom::Braille => {
+                        const BITS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+                        let mut bits = 0;
+                        for (ix, column) in BITS.iter().enumerate() {
+                            for (iy, bit) in column.iter().enumerate() {
+                                if dot(ix as i32, iy as i32).is_some_and(|d| d.0 != 0) {
+                                    bits |= bit;
+                                }
+                            }
+                        }
+                        let g = char::from_u32(0x2800 + bits).unwrap();
+                        (g.to_string(), (bits != 0) as u32)
+                    }
+                };
+
+                let color = if state == 0 {
+                    Color::DarkGrey
+                } else {
+                    let i = (state as usize).min(palette.len() / 3 - 1) * 3;
+                    Color::Rgb { r: palette[i], g: palette[i + 1], b: palette[i + 2] }
+                };
+                let background = if (col, row) == cursor_char { Color::DarkBlue } else { Color::Reset };
+                queue!(out, SetForegroundColor(color), SetBackgroundColor(background), Print(glyph))?;
+            }
+            queue!(out, ResetColor, terminal::Clear(ClearType::UntilNewLine))?;
+        }
+        Ok(())
+    }
+
+    fn cell_glyph(&self, value: u32, s: i32) -> String {
+        let live = value != 0;
+        match self.cm.config.shape.as_str() {
+            "hexagon" => if live { "⬢" } else { "⬡" }.to_string(),
+            "triangle" => match (s, live) {
+                (0, true) => "◣",
+                (0, false) => "◺",
+                (_, true) => "◥",
+                (_, false) => "◹",
+            }
+            .to_string(),
+            "rhombus" => if live { "◆" } else { "◇" }.to_string(),
+            shape if tiling(shape).is_some() => if live { "●" } else { "·" }.to_string(),
+            _ => if live { "██" } else { "··" }.to_string(),
+        }
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
@@ -1,5 +1,11 @@
 use wasm_bindgen::prelude::*;
 use crate::CellManager;
+use crate::cell_manager::{CycleState, GraphManager, Neighborhood, PasteMode, Selection};
+use crate::cell_manager::coords::{self, DoubledKind, OffsetKind};
+use crate::cell_manager::drawing::{circle_cells, line_cells, polygon_cells};
+use crate::cell_manager::tilings::tiling;
+use crate::cell_manager::timeline::DEFAULT_TIMELINE;
+use crate::cell_manager::voxels::{write_obj, write_vox};
 
 #[wasm_bindgen]
 pub struct WasmCellManager {
@@ -10,9 +16,10 @@ pub struct WasmCellManager {
 impl WasmCellManager {
     #[wasm_bindgen(constructor)]
     pub fn new(width: usize, height: usize, depth: usize, chunk_size: Option<usize>) -> WasmCellManager {
-        WasmCellManager {
-            inner: CellManager::new(width, height, depth, chunk_size),
-        }
+        let mut inner = CellManager::new(width, height, depth, chunk_size);
+        let (keyframe_interval, retention) = DEFAULT_TIMELINE;
+        inner.set_timeline(keyframe_interval, retention);
+        WasmCellManager { inner }
     }
 
     pub fn set_cell(&mut self, q: i32, r: i32, s: i32, value: u32) {
@@ -31,6 +38,10 @@ impl WasmCellManager {
         self.inner.count_live_neighbors(q, r, s)
     }
 
+    pub fn count_weighted_neighbors(&self, q: i32, r: i32, s: i32) -> f32 {
+        self.inner.count_weighted_neighbors(q, r, s)
+    }
+
     pub fn get_neighbors(&self, q: i32, r: i32, s: i32) -> Vec<i32> {
         self.inner.get_neighbors(q, r, s)
     }
@@ -52,12 +63,617 @@ impl WasmCellManager {
         self.inner.change_grid_properties(shape, neighbor_type, range, topology_type);
     }
 
+    /// Neighbor type as stored, with custom specs in their one-line form.
+    pub fn get_neighbor_type(&self) -> String {
+        self.inner.config.neighbor_type.clone()
+    }
+
+    /// Flat `[s, dq, dr, ds, ...]` for every offset of every layer.
+    pub fn get_neighborhood_offsets(&self) -> Vec<i32> {
+        let layers = &self.inner.neighborhood().adj_neighbors;
+        (0..layers.len())
+            .flat_map(|s| layers[s].iter().flat_map(move |&(dq, dr, ds)| [s as i32, dq, dr, ds]))
+            .collect()
+    }
+
+    /// `[duplicates, one-way offsets, rotations, reflections (0/1),
+    /// rotations of the tiling]`, see `NeighborhoodReport`.
+    pub fn get_neighborhood_report(&self) -> Vec<i32> {
+        let report = self.inner.neighborhood().report();
+        vec![
+            report.duplicates as i32,
+            report.one_way.len() as i32,
+            report.rotations as i32,
+            report.reflections as i32,
+            report.full_rotations as i32,
+        ]
+    }
+
+    /// `C<n>` or `D<n>`, e.g. `D4` for a square Moore neighborhood.
+    pub fn get_symmetry_group(&self) -> String {
+        self.inner.neighborhood().report().symmetry_group()
+    }
+
+    /// Weights in the order of [`WasmCellManager::get_neighborhood_offsets`].
+    pub fn get_neighborhood_weights(&self) -> Vec<f32> {
+        self.inner.neighborhood().weights.concat()
+    }
+
+    /// `uniform`, `gaussian[:sigma]`, `ring[:radius[,width]]` or
+    /// `inverse[:power]`; false when the name does not parse.
+    pub fn set_kernel(&mut self, kernel: &str) -> bool {
+        self.inner.set_kernel(kernel)
+    }
+
     pub fn get_bounds(&self) -> Vec<i32> {
         self.inner.get_bounds().to_vec()
     }
 
-    pub fn floodfill(&mut self) {
-        self.inner.floodfill();
+    /// MagicaVoxel `.vox` bytes of the live cells; empty when they span
+    /// more than 256 on an axis.
+    pub fn export_vox(&self) -> Vec<u8> {
+        write_vox(&self.inner.for_each_cell()).unwrap_or_default()
+    }
+
+    /// Wavefront `.obj` isosurface mesh of the live cells.
+    pub fn export_obj(&self) -> String {
+        write_obj(&self.inner.for_each_cell())
+    }
+
+    /// Fills the region containing `(q, r, s)` with `value`; returns the
+    /// number of cells changed, 0 when an empty region is too large to fill.
+    pub fn bucket_fill(&mut self, q: i32, r: i32, s: i32, value: u32) -> usize {
+        self.inner.bucket_fill(q, r, s, value).unwrap_or(0)
+    }
+
+    /// Component id map of the cells in `state`, as `[q, r, s, id]` per
+    /// cell; empty when state 0 regions are too large to label.
+    pub fn label_components(&self, state: u32) -> Vec<i32> {
+        self.inner.label_components(Some(state)).map(|map| map.to_flat()).unwrap_or_default()
+    }
+
+    /// `[id, size, min_q, max_q, min_r, max_r]` per component of `state`.
+    pub fn get_components(&self, state: u32) -> Vec<i32> {
+        self.inner
+            .label_components(Some(state))
+            .unwrap_or_default()
+            .components
+            .iter()
+            .flat_map(|c| {
+                let [min_q, max_q, min_r, max_r] = c.bounds;
+                [c.id as i32, c.size as i32, min_q, max_q, min_r, max_r]
+            })
+            .collect()
+    }
+
+    /// Morphology with a structuring element of this grid's shape;
+    /// `op` is dilate, erode, open, close or boundary. Returns the number
+    /// of cells changed, or -1 for an unknown op.
+    pub fn morphology(&mut self, op: String, kind: String, range: i32, value: u32) -> i32 {
+        let element = self.inner.structuring_element(&kind, range);
+        let changed = match op.as_str() {
+            "dilate" => self.inner.dilate(&element, value),
+            "erode" => self.inner.erode(&element),
+            "open" => self.inner.open(&element, value),
+            "close" => self.inner.close(&element, value),
+            "boundary" => self.inner.extract_boundary(&element),
+            _ => return -1,
+        };
+        changed as i32
+    }
+
+    /// `[q, r, s, distance]` per live cell.
+    pub fn distance_transform(&self, kind: String, range: i32) -> Vec<i32> {
+        let element = self.inner.structuring_element(&kind, range);
+        let mut cells: Vec<_> = self.inner.distance_transform(&element).into_iter().collect();
+        cells.sort_unstable();
+        cells
+            .into_iter()
+            .flat_map(|((q, r, s), d)| [q, r, s, d as i32])
+            .collect()
+    }
+
+    pub fn select_rect(&mut self, min_q: i32, max_q: i32, min_r: i32, max_r: i32) -> usize {
+        self.inner.select(Selection::Rect([min_q, max_q, min_r, max_r]))
+    }
+
+    pub fn select_hex(&mut self, q: i32, r: i32, radius: i32) -> usize {
+        self.inner.select(Selection::Hex { q, r, radius })
+    }
+
+    /// `points` is `[x, y]` per vertex, in world units with a cell size of 1.
+    pub fn select_polygon(&mut self, points: Vec<f32>) -> usize {
+        let points = points.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        self.inner.select(Selection::Polygon(points))
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.inner.clear_selection();
+    }
+
+    /// `[q, r, s]` per selected cell.
+    pub fn get_selection_cells(&self) -> Vec<i32> {
+        self.inner
+            .selection_cells()
+            .into_iter()
+            .flat_map(|(q, r, s)| [q, r, s])
+            .collect()
+    }
+
+    pub fn copy(&mut self) -> usize {
+        self.inner.copy()
+    }
+
+    pub fn cut(&mut self) -> usize {
+        self.inner.cut()
+    }
+
+    /// `mode` is or, xor or overwrite. Returns the number of cells
+    /// changed, or -1 for an unknown mode.
+    pub fn paste(&mut self, q: i32, r: i32, mode: String) -> i32 {
+        match PasteMode::parse(&mode) {
+            Some(mode) => self.inner.paste(q, r, mode) as i32,
+            None => -1,
+        }
+    }
+
+    pub fn rotate_selection(&mut self, turns: i32) -> usize {
+        self.inner.rotate_selection(turns)
+    }
+
+    pub fn reflect_selection(&mut self) -> usize {
+        self.inner.reflect_selection()
+    }
+
+    pub fn translate_selection(&mut self, dq: i32, dr: i32) -> usize {
+        self.inner.translate_selection(dq, dr)
+    }
+
+    pub fn rotate_clipboard(&mut self, turns: i32) {
+        if let Some(clipboard) = self.inner.clipboard_mut() {
+            clipboard.rotate(turns);
+        }
+    }
+
+    pub fn reflect_clipboard(&mut self) {
+        if let Some(clipboard) = self.inner.clipboard_mut() {
+            clipboard.reflect();
+        }
+    }
+
+    /// `ends` is `[q0, r0, s0, q1, r1, s1]`.
+    pub fn draw_line(&mut self, ends: Vec<i32>, state: u32) -> usize {
+        match flat_to_cells(&ends)[..] {
+            [from, to] => self.inner.draw_line(from, to, state),
+            _ => 0,
+        }
+    }
+
+    pub fn draw_circle(&mut self, q: i32, r: i32, s: i32, radius: i32, filled: bool, state: u32) -> usize {
+        self.inner.draw_circle((q, r, s), radius, filled, state)
+    }
+
+    /// `vertices` is `[q, r, s]` per vertex.
+    pub fn draw_polygon(&mut self, vertices: Vec<i32>, filled: bool, state: u32) -> usize {
+        self.inner.draw_polygon(&flat_to_cells(&vertices), filled, state)
+    }
+
+    /// Cells `draw_line` would cover, as `[q, r, s]` per cell (for previews).
+    pub fn get_line_cells(&self, ends: Vec<i32>) -> Vec<i32> {
+        match flat_to_cells(&ends)[..] {
+            [from, to] => cells_to_flat(line_cells(&self.inner.config.shape, from, to)),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_circle_cells(&self, q: i32, r: i32, s: i32, radius: i32, filled: bool) -> Vec<i32> {
+        cells_to_flat(circle_cells(&self.inner.config.shape, (q, r, s), radius, filled))
+    }
+
+    pub fn get_polygon_cells(&self, vertices: Vec<i32>, filled: bool) -> Vec<i32> {
+        cells_to_flat(polygon_cells(&self.inner.config.shape, &flat_to_cells(&vertices), filled))
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.inner.undo()
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.inner.redo()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.inner.history().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.inner.history().can_redo()
+    }
+
+    /// Groups edits (e.g. one brush stroke) into a single undo step.
+    pub fn begin_transaction(&mut self, label: String) {
+        self.inner.begin_transaction(&label);
+    }
+
+    pub fn commit_transaction(&mut self) {
+        self.inner.commit_transaction();
+    }
+
+    pub fn set_history_budget(&mut self, bytes: usize) {
+        self.inner.set_history_budget(bytes);
+    }
+
+    pub fn set_rule(&mut self, rulestring: String) -> bool {
+        self.inner.set_rule(&rulestring)
+    }
+
+    pub fn step(&mut self, generations: usize) {
+        self.inner.step_n(generations);
+    }
+
+    pub fn get_generation(&self) -> u64 {
+        self.inner.generation
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        self.inner.step_back()
+    }
+
+    pub fn jump_to_generation(&mut self, generation: u64) -> bool {
+        self.inner.jump_to_generation(generation)
+    }
+
+    pub fn set_timeline(&mut self, keyframe_interval: u64, retention: u64) {
+        self.inner.set_timeline(keyframe_interval, retention);
+    }
+
+    /// `[earliest, latest]` restorable generations, empty before the first step.
+    pub fn get_timeline_range(&self) -> Vec<u64> {
+        self.inner.timeline().range().map_or(Vec::new(), |(first, last)| vec![first, last])
+    }
+
+    pub fn get_state_hash(&self) -> u64 {
+        self.inner.state_hash()
+    }
+
+    /// `[kind, start, period, dq, dr]` where kind is 0 running, 1 extinct,
+    /// 2 still, 3 oscillating, 4 moving.
+    pub fn get_cycle_info(&self) -> Vec<i32> {
+        cycle_info(self.inner.cycle_state())
+    }
+
+    pub fn get_stats_csv(&self) -> String {
+        self.inner.stats_csv()
+    }
+
+    pub fn set_stats_capacity(&mut self, capacity: usize) {
+        self.inner.set_stats_capacity(capacity);
+    }
+
+    /// Population of every stored generation, oldest first.
+    pub fn get_population_history(&self) -> Vec<u32> {
+        self.inner.stats().iter().map(|s| s.population as u32).collect()
+    }
+
+    /// Population of `state` (a species under species rules) in every
+    /// stored generation, oldest first.
+    pub fn get_state_history(&self, state: usize) -> Vec<u32> {
+        self.inner.stats().state_history(state).into_iter().map(|n| n as u32).collect()
+    }
+
+    /// Latest generation as `[generation, population, births, deaths,
+    /// min_q, max_q, min_r, max_r, active_chunks, state_1, state_2, ...]`.
+    /// Bounds are 0 when nothing is alive; empty before the first step.
+    pub fn get_latest_stats(&self) -> Vec<i32> {
+        let Some(s) = self.inner.latest_stats() else {
+            return Vec::new();
+        };
+        let [min_q, max_q, min_r, max_r] = s.bounds.unwrap_or([0; 4]);
+        let mut out = vec![
+            s.generation as i32,
+            s.population as i32,
+            s.births as i32,
+            s.deaths as i32,
+            min_q,
+            max_q,
+            min_r,
+            max_r,
+            s.active_chunks as i32,
+        ];
+        out.extend(s.per_state.iter().skip(1).map(|&n| n as i32));
+        out
+    }
+
+    // Worlds stacked on the grid; the grid itself is the world "main"
+    pub fn add_world(&mut self, name: String, rulestring: String) -> bool {
+        self.inner.add_world(&name, &rulestring)
+    }
+
+    pub fn remove_world(&mut self, name: String) -> bool {
+        self.inner.remove_world(&name)
+    }
+
+    pub fn get_world_names(&self) -> Vec<String> {
+        self.inner.world_names().into_iter().map(str::to_string).collect()
+    }
+
+    pub fn set_world_rule(&mut self, name: String, rulestring: String) -> bool {
+        self.inner.set_world_rule(&name, &rulestring)
+    }
+
+    pub fn set_world_cell(&mut self, name: String, q: i32, r: i32, s: i32, value: u32) -> bool {
+        self.inner.set_world_cell(&name, q, r, s, value)
+    }
+
+    pub fn get_world_cell(&self, name: String, q: i32, r: i32, s: i32) -> u32 {
+        self.inner.get_world_cell(&name, q, r, s).unwrap_or(0)
+    }
+
+    /// Live cells of a world as `[q, r, s, state, ...]`, empty for unknown names.
+    pub fn world_cells(&self, name: String) -> Vec<i32> {
+        self.inner.world_cells(&name).unwrap_or_default()
+    }
+
+    pub fn random_world_cells_seeded(&mut self, name: String, seed: u64, density: f32) -> bool {
+        let [min_q, max_q, min_r, max_r, _, _] = self.inner.get_bounds();
+        self.inner.random_world_cells_seeded(&name, seed, density, [min_q, max_q, min_r, max_r])
+    }
+
+    pub fn clear_world(&mut self, name: String) -> bool {
+        self.inner.clear_world(&name)
+    }
+
+    /// Couples world `source` into world `target`; empty when the coupling
+    /// was added, otherwise why not.
+    pub fn couple(&mut self, target: String, source: String, neighbor_type: String, weight: f32) -> String {
+        self.inner.couple(&target, &source, &neighbor_type, weight).err().unwrap_or_default()
+    }
+
+    pub fn clear_couplings(&mut self, target: String) -> bool {
+        self.inner.clear_couplings(&target)
+    }
+}
+
+/// Automaton on a graph, see `cell_manager::graph`. Nodes are numbered
+/// `0..node_count()`; an unreadable source gives an empty graph, and
+/// `graph_error` says why.
+#[wasm_bindgen]
+pub struct WasmGraphManager {
+    inner: GraphManager,
+}
+
+#[wasm_bindgen]
+impl WasmGraphManager {
+    /// Generated graph, e.g. `watts_strogatz:1000,6,0.1`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(spec: &str, seed: u64) -> WasmGraphManager {
+        WasmGraphManager { inner: GraphManager::generate(spec, seed).unwrap_or_else(|_| empty_graph()) }
+    }
+
+    pub fn from_edge_list(text: &str) -> WasmGraphManager {
+        WasmGraphManager { inner: GraphManager::from_edge_list(text).unwrap_or_else(|_| empty_graph()) }
+    }
+
+    pub fn to_edge_list(&self) -> String {
+        self.inner.to_edge_list()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.inner.node_count()
+    }
+
+    /// Flat `[x, y]` layout position of every node.
+    pub fn get_positions(&self) -> Vec<f32> {
+        (0..self.inner.node_count())
+            .filter_map(|n| self.inner.position(n))
+            .flat_map(|(x, y)| [x, y])
+            .collect()
+    }
+
+    /// Flat `[a, b]` pairs, each edge once.
+    pub fn get_edges(&self) -> Vec<u32> {
+        let mut edges = Vec::new();
+        for a in 0..self.inner.node_count() {
+            for &b in self.inner.get_neighbors(a).iter().filter(|&&b| a < b) {
+                edges.extend([a as u32, b as u32]);
+            }
+        }
+        edges
+    }
+
+    pub fn get_neighbors(&self, node: usize) -> Vec<u32> {
+        self.inner.get_neighbors(node).iter().map(|&n| n as u32).collect()
+    }
+
+    pub fn set_cell(&mut self, node: usize, value: u32) {
+        self.inner.set_cell(node, value);
+    }
+
+    pub fn get_cell(&self, node: usize) -> u32 {
+        self.inner.get_cell(node)
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Live nodes as `[node, 0, 0, state]`, like `WasmCellManager::for_each_cell`.
+    pub fn for_each_cell(&self) -> Vec<i32> {
+        self.inner.for_each_cell()
+    }
+
+    pub fn random_cells_seeded(&mut self, seed: u64, density: f32) {
+        self.inner.random_cells_seeded(seed, density);
+    }
+
+    pub fn count_live_neighbors(&self, node: usize) -> u32 {
+        self.inner.count_live_neighbors(node)
+    }
+
+    pub fn count_weighted_neighbors(&self, node: usize) -> f32 {
+        self.inner.count_weighted_neighbors(node)
+    }
+
+    pub fn set_rule(&mut self, rulestring: String) -> bool {
+        self.inner.set_rule(&rulestring)
+    }
+
+    pub fn set_kernel(&mut self, kernel: &str) -> bool {
+        self.inner.set_kernel(kernel)
+    }
+
+    pub fn step(&mut self, generations: usize) {
+        self.inner.step_n(generations);
     }
 
+    pub fn get_generation(&self) -> u64 {
+        self.inner.generation
+    }
+
+    pub fn get_state_hash(&self) -> u64 {
+        self.inner.state_hash()
+    }
+
+    /// Same layout as `WasmCellManager::get_cycle_info`.
+    pub fn get_cycle_info(&self) -> Vec<i32> {
+        cycle_info(self.inner.cycle_state())
+    }
+
+    pub fn get_stats_csv(&self) -> String {
+        self.inner.stats_csv()
+    }
+
+    pub fn get_population_history(&self) -> Vec<u32> {
+        self.inner.stats().iter().map(|s| s.population as u32).collect()
+    }
+}
+
+/// Why a graph spec (or, with `edge_list`, an edge list) is rejected;
+/// empty when it is valid.
+#[wasm_bindgen]
+pub fn graph_error(source: &str, edge_list: bool) -> String {
+    let graph = match edge_list {
+        true => GraphManager::from_edge_list(source),
+        false => GraphManager::generate(source, 0),
+    };
+    graph.err().unwrap_or_default()
+}
+
+fn empty_graph() -> GraphManager {
+    GraphManager::from_edge_list("").unwrap()
+}
+
+fn cycle_info(state: CycleState) -> Vec<i32> {
+    match state {
+        CycleState::Running => vec![0, 0, 0, 0, 0],
+        CycleState::Extinct { since } => vec![1, since as i32, 0, 0, 0],
+        CycleState::Still { since } => vec![2, since as i32, 1, 0, 0],
+        CycleState::Oscillating { start, period } => vec![3, start as i32, period as i32, 0, 0],
+        CycleState::Moving { start, period, dq, dr } => vec![4, start as i32, period as i32, dq, dr],
+    }
+}
+
+// Coordinate helpers shared by the tiles; `size` is the tile's cell size
+// (`cellSize` or `radius`)
+
+#[wasm_bindgen]
+pub fn pixel_to_cell(shape: &str, x: f32, y: f32, size: f32) -> Vec<i32> {
+    let (q, r, s) = coords::pixel_to_cell(shape, x / size, y / size);
+    vec![q, r, s]
+}
+
+#[wasm_bindgen]
+pub fn cell_to_pixel(shape: &str, q: i32, r: i32, s: i32, size: f32) -> Vec<f32> {
+    let (x, y) = coords::cell_center(shape, q, r, s);
+    vec![x * size, y * size]
+}
+
+/// `[x, y]` per corner.
+#[wasm_bindgen]
+pub fn cell_polygon(shape: &str, q: i32, r: i32, s: i32, size: f32) -> Vec<f32> {
+    coords::cell_polygon(shape, q, r, s)
+        .into_iter()
+        .flat_map(|(x, y)| [x * size, y * size])
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn round_cell(shape: &str, q: f32, r: f32) -> Vec<i32> {
+    let (q, r, s) = coords::round_cell(shape, q, r);
+    vec![q, r, s]
+}
+
+#[wasm_bindgen]
+pub fn hex_to_cube(q: i32, r: i32) -> Vec<i32> {
+    let (x, y, z) = coords::axial_to_cube(q, r);
+    vec![x, y, z]
+}
+
+#[wasm_bindgen]
+pub fn hex_distance(dq: i32, dr: i32) -> i32 {
+    coords::hex_distance(dq, dr)
+}
+
+/// `kind` is odd-r, even-r, odd-q or even-q; empty for anything else.
+#[wasm_bindgen]
+pub fn hex_to_offset(q: i32, r: i32, kind: &str) -> Vec<i32> {
+    OffsetKind::parse(kind).map_or(Vec::new(), |kind| {
+        let (col, row) = coords::axial_to_offset(q, r, kind);
+        vec![col, row]
+    })
+}
+
+#[wasm_bindgen]
+pub fn offset_to_hex(col: i32, row: i32, kind: &str) -> Vec<i32> {
+    OffsetKind::parse(kind).map_or(Vec::new(), |kind| {
+        let (q, r) = coords::offset_to_axial(col, row, kind);
+        vec![q, r]
+    })
+}
+
+/// `kind` is width or height; empty for anything else.
+#[wasm_bindgen]
+pub fn hex_to_doubled(q: i32, r: i32, kind: &str) -> Vec<i32> {
+    DoubledKind::parse(kind).map_or(Vec::new(), |kind| {
+        let (col, row) = coords::axial_to_doubled(q, r, kind);
+        vec![col, row]
+    })
+}
+
+#[wasm_bindgen]
+pub fn doubled_to_hex(col: i32, row: i32, kind: &str) -> Vec<i32> {
+    DoubledKind::parse(kind).map_or(Vec::new(), |kind| {
+        let (q, r) = coords::doubled_to_axial(col, row, kind);
+        vec![q, r]
+    })
+}
+
+/// Unit cell of one of the `tilings` for the renderer, in world units:
+/// `[ax, ay, bx, by]` lattice vectors, then each tile `s` as its corner
+/// count followed by `x, y` pairs. Empty for other shapes.
+#[wasm_bindgen]
+pub fn tiling_unit_cell(shape: &str) -> Vec<f32> {
+    tiling(shape).map_or(Vec::new(), |t| {
+        let mut out = vec![t.a.0, t.a.1, t.b.0, t.b.1];
+        for tile in &t.tiles {
+            out.push(tile.len() as f32);
+            out.extend(tile.iter().flat_map(|&(x, y)| [x, y]));
+        }
+        out
+    })
+}
+
+/// Why a neighbor type (name or custom spec) is rejected for `shape`;
+/// empty when it is valid.
+#[wasm_bindgen]
+pub fn neighborhood_error(shape: &str, neighbor_type: &str) -> String {
+    Neighborhood::parse(shape, neighbor_type, 1).err().unwrap_or_default()
+}
+
+fn flat_to_cells(flat: &[i32]) -> Vec<(i32, i32, i32)> {
+    flat.chunks_exact(3).map(|c| (c[0], c[1], c[2])).collect()
+}
+
+fn cells_to_flat(cells: Vec<(i32, i32, i32)>) -> Vec<i32> {
+    cells.into_iter().flat_map(|(q, r, s)| [q, r, s]).collect()
 }
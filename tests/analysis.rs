@@ -0,0 +1,60 @@
+use cell_manager::cell_manager::analysis::{DEFAULT_MAX_PERIOD, ObjectClass, classify_object, classify_objects, split_objects};
+
+mod common;
+use common::life;
+
+fn cells(points: &[(i32, i32)]) -> Vec<(i32, i32, i32, u32)> {
+    points.iter().map(|&(q, r)| (q, r, 0, 1)).collect()
+}
+
+const BLOCK: [(i32, i32); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+const BLINKER: [(i32, i32); 3] = [(0, 0), (1, 0), (2, 0)];
+const GLIDER: [(i32, i32); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+const BEEHIVE: [(i32, i32); 6] = [(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)];
+
+#[test]
+fn common_objects_get_their_apgcodes() {
+    let cm = life(64, "none");
+    let classify = |points: &[(i32, i32)]| classify_object(&cm, &cells(points), DEFAULT_MAX_PERIOD);
+
+    assert_eq!(classify(&BLOCK), (ObjectClass::StillLife, "xs4_33".to_string()));
+    assert_eq!(classify(&BEEHIVE), (ObjectClass::StillLife, "xs6_696".to_string()));
+    assert_eq!(classify(&BLINKER), (ObjectClass::Oscillator { period: 2 }, "xp2_7".to_string()));
+    assert_eq!(classify(&GLIDER), (ObjectClass::Spaceship { period: 4, dq: 1, dr: 1 }, "xq4_153".to_string()));
+    assert_eq!(classify(&[(0, 0), (3, 3)]).0, ObjectClass::Dies);
+    // A pre-block settles into a block, so it is not an object on its own
+    assert_eq!(classify(&[(0, 0), (1, 0), (0, 1)]).0, ObjectClass::Unstable);
+}
+
+#[test]
+fn apgcodes_ignore_orientation_and_phase() {
+    let cm = life(64, "none");
+    let code = |points: Vec<(i32, i32)>| classify_object(&cm, &cells(&points), DEFAULT_MAX_PERIOD).1;
+    let glider = code(GLIDER.to_vec());
+
+    // Flipped and turned gliders, and a later phase of the original
+    assert_eq!(code(GLIDER.iter().map(|&(q, r)| (-q, r)).collect()), glider);
+    assert_eq!(code(GLIDER.iter().map(|&(q, r)| (r, -q)).collect()), glider);
+    let mut later = life(64, "none");
+    GLIDER.iter().for_each(|&(q, r)| later.set_cell(q, r, 0, 1));
+    later.step_n(2);
+    let phase: Vec<(i32, i32)> = later.for_each_cell().chunks(4).map(|c| (c[0], c[1])).collect();
+    assert_eq!(code(phase), glider);
+
+    assert_eq!(code(BLINKER.iter().map(|&(q, r)| (r, q)).collect()), "xp2_7");
+}
+
+#[test]
+fn separated_objects_are_classified_apart() {
+    let mut cm = life(64, "none");
+    for (points, (dq, dr)) in [(&BLOCK[..], (-20, -20)), (&BLINKER[..], (10, -20)), (&GLIDER[..], (0, 10))] {
+        points.iter().for_each(|&(q, r)| cm.set_cell(q + dq, r + dr, 0, 1));
+    }
+    let mut sizes: Vec<usize> = split_objects(&cm).iter().map(Vec::len).collect();
+    sizes.sort();
+    assert_eq!(sizes, [3, 4, 5]);
+
+    let mut codes: Vec<String> = classify_objects(&cm, DEFAULT_MAX_PERIOD).into_iter().map(|o| o.apgcode).collect();
+    codes.sort();
+    assert_eq!(codes, ["xp2_7", "xq4_153", "xs4_33"]);
+}
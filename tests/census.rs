@@ -0,0 +1,55 @@
+use cell_manager::census::{CensusOptions, run_census, soup_manager};
+
+mod common;
+use common::{life, sorted};
+
+fn options(threads: usize) -> CensusOptions {
+    CensusOptions { soups: 8, seed: 100, soup_size: 8, max_generations: 2000, threads, ..CensusOptions::default() }
+}
+
+#[test]
+fn census_does_not_depend_on_the_thread_count() {
+    let template = life(64, "none");
+    let single = run_census(&template, &options(1));
+    let parallel = run_census(&template, &options(4));
+    assert_eq!(single.report(&template, &options(1)), parallel.report(&template, &options(4)));
+
+    assert_eq!(single.soups, 8);
+    assert!(single.unstabilised.is_empty(), "{:?}", single.unstabilised);
+    assert!(single.entries.windows(2).all(|w| w[0].count >= w[1].count));
+    assert!(single.entries.iter().any(|e| e.apgcode == "xs4_33"));
+    for entry in &single.entries {
+        assert!(entry.seeds.iter().all(|s| (100..108).contains(s)), "{}", entry.apgcode);
+        assert!(entry.seeds.windows(2).all(|w| w[0] < w[1]));
+        assert!(entry.seeds.len() <= entry.count);
+    }
+}
+
+#[test]
+fn soups_are_reproducible_from_their_seed() {
+    let template = life(64, "none");
+    let options = options(1);
+    let soup = sorted(soup_manager(&template, &options, 7).for_each_cell());
+    assert_eq!(sorted(soup_manager(&template, &options, 7).for_each_cell()), soup);
+    assert_ne!(sorted(soup_manager(&template, &options, 8).for_each_cell()), soup);
+
+    // Every cell inside the 8 x 8 soup region
+    assert!(soup.iter().all(|c| (-4..4).contains(&c[0]) && (-4..4).contains(&c[1])));
+}
+
+#[test]
+fn report_lists_objects_rare_finds_and_unsettled_soups() {
+    let template = life(64, "none");
+    let options = CensusOptions { max_generations: 3, rare_threshold: 1000, ..options(2) };
+    let census = run_census(&template, &options);
+    assert_eq!(census.unstabilised.len(), 8);
+
+    let report = census.report(&template, &options);
+    let lines: Vec<&str> = report.lines().collect();
+    assert!(lines[0].starts_with("# census shape=square neighbor_type=moore range=1 rule=B3/S23"));
+    assert!(lines[1].starts_with("# soups=8 seeds=100..108 soup_size=8"));
+    assert_eq!(lines[2], "# unstabilised soups: 8 (seeds 100 101 102 103 104 105 106 107)");
+    assert_eq!(lines[3], "apgcode,class,count,seeds");
+    assert_eq!(lines.len(), 4 + census.entries.len() + 1 + census.rare(1000).count());
+    assert_eq!(lines[4 + census.entries.len()], "# rare finds (seen at most 1000 times)");
+}
@@ -0,0 +1,20 @@
+// Fixtures shared by the integration tests; each test crate uses only some
+#![allow(dead_code)]
+
+use cell_manager::CellManager;
+
+/// Square Moore grid of `width` x `width` cells running Life (B3/S23).
+pub fn life(width: usize, topology: &str) -> CellManager {
+    let mut cm = CellManager::new(width, width, 1, None);
+    cm.change_grid_properties("square".to_string(), "moore".to_string(), 1, topology.to_string());
+    assert!(cm.set_rule("B3/S23"));
+    cm
+}
+
+/// `[q, r, s, state]` rows of `cells` (as from `for_each_cell`), sorted so
+/// grids can be compared whatever order they list their cells in.
+pub fn sorted(cells: Vec<i32>) -> Vec<Vec<i32>> {
+    let mut cells: Vec<Vec<i32>> = cells.chunks(4).map(|c| c.to_vec()).collect();
+    cells.sort();
+    cells
+}
@@ -0,0 +1,101 @@
+use cell_manager::CellManager;
+
+fn grid(width: usize, neighbor_type: &str, topology: &str) -> CellManager {
+    let mut cm = CellManager::new(width, width, 1, None);
+    cm.change_grid_properties("square".to_string(), neighbor_type.to_string(), 1, topology.to_string());
+    cm
+}
+
+fn fill(cm: &mut CellManager, cells: &[(i32, i32)], state: u32) {
+    for &(q, r) in cells {
+        cm.set_cell(q, r, 0, state);
+    }
+}
+
+// Outline of the square from (-2, -2) to (2, 2)
+fn ring() -> Vec<(i32, i32)> {
+    let square = (-2..=2).flat_map(|q| (-2..=2).map(move |r| (q, r)));
+    square.filter(|&(q, r): &(i32, i32)| q.abs() == 2 || r.abs() == 2).collect()
+}
+
+#[test]
+fn components_report_sizes_and_bounding_boxes() {
+    let mut cm = grid(32, "moore", "none");
+    fill(&mut cm, &[(-10, -10), (-9, -10), (-10, -9), (-9, -9)], 1);
+    fill(&mut cm, &[(0, 0), (1, 1), (2, 2)], 1);
+    fill(&mut cm, &[(5, -3), (5, -2)], 2);
+
+    let map = cm.label_components(None).unwrap();
+    let summary: Vec<(usize, u32, [i32; 4])> = map.components.iter().map(|c| (c.size, c.state, c.bounds)).collect();
+    assert_eq!(summary, [(4, 1, [-10, -9, -10, -9]), (3, 1, [0, 2, 0, 2]), (2, 2, [5, 5, -3, -2])]);
+    assert_eq!(map.label(1, 1, 0), Some(2));
+    assert_eq!(map.label(3, 3, 0), None);
+    assert_eq!(map.cells(3), [(5, -3, 0), (5, -2, 0)]);
+
+    // Only orthogonal steps connect under von Neumann, and a state is picked alone
+    let mut cm = grid(32, "vonNeumann", "none");
+    fill(&mut cm, &[(0, 0), (1, 1), (2, 2), (2, 3)], 1);
+    fill(&mut cm, &[(0, 1)], 2);
+    let sizes: Vec<usize> = cm.label_components(Some(1)).unwrap().components.iter().map(|c| c.size).collect();
+    assert_eq!(sizes, [1, 1, 2]);
+    // Every live state together, (0, 1) joins (0, 0) and (1, 1)
+    assert_eq!(cm.label_components(None).unwrap().components[0].size, 3);
+}
+
+#[test]
+fn components_join_across_wrapped_edges() {
+    let mut cm = grid(16, "vonNeumann", "torus");
+    fill(&mut cm, &[(-8, 0), (7, 0), (7, 1)], 1);
+    let map = cm.label_components(Some(1)).unwrap();
+    assert_eq!(map.components.len(), 1);
+    assert_eq!(map.components[0].bounds, [-8, 7, 0, 1]);
+
+    cm.change_grid_properties("square".to_string(), "vonNeumann".to_string(), 1, "none".to_string());
+    assert_eq!(cm.label_components(Some(1)).unwrap().components.len(), 2);
+}
+
+#[test]
+fn bucket_fill_stops_at_walls_and_undoes_in_one_step() {
+    let mut cm = grid(16, "vonNeumann", "none");
+    fill(&mut cm, &ring(), 1);
+
+    let empty = cm.label_components(Some(0)).unwrap();
+    let mut sizes: Vec<usize> = empty.components.iter().map(|c| c.size).collect();
+    sizes.sort();
+    assert_eq!(sizes, [9, 256 - 25]);
+
+    assert_eq!(cm.bucket_fill(0, 0, 0, 2), Ok(9));
+    assert_eq!(cm.get_cell(1, -1, 0), 2);
+    assert_eq!(cm.get_cell(3, 0, 0), 0);
+    assert_eq!(cm.bucket_fill(1, 1, 0, 2), Ok(0));
+    // Refilling the wall recolours all of it
+    assert_eq!(cm.bucket_fill(2, 0, 0, 3), Ok(16));
+    assert_eq!(cm.component_at(-2, -2, 0).unwrap().len(), 16);
+
+    assert!(cm.undo());
+    assert!(cm.undo());
+    assert_eq!(cm.get_cell(0, 0, 0), 0);
+    assert_eq!(cm.get_cell(2, 0, 0), 1);
+    // Outside the grid there is nothing to fill
+    assert_eq!(cm.bucket_fill(100, 0, 0, 1), Ok(0));
+}
+
+#[test]
+fn empty_regions_of_huge_grids_stay_near_the_live_cells() {
+    let mut cm = grid(1 << 16, "moore", "none");
+    fill(&mut cm, &ring(), 1);
+    // The inside, plus the outside within one step of the ring's bounding box
+    assert_eq!(cm.empty_region(None), Ok([-3, 3, -3, 3, 0, 0]));
+    assert_eq!(cm.bucket_fill(0, 0, 0, 2), Ok(9));
+    // From -3 to 21 on both axes, less the ring and its inside
+    assert_eq!(cm.bucket_fill(20, 20, 0, 2), Ok(25 * 25 - 25));
+    // What is left is the band one step around the filled square
+    let empty = cm.label_components(Some(0)).unwrap();
+    assert_eq!(empty.components.len(), 1);
+    assert_eq!(empty.components[0].size, 27 * 27 - 25 * 25);
+
+    cm.set_cell(30_000, 30_000, 0, 1);
+    assert!(cm.empty_region(None).is_err());
+    assert!(cm.bucket_fill(-5, -5, 0, 2).is_err());
+    assert_eq!(cm.get_cell(-5, -5, 0), 0);
+}
@@ -0,0 +1,88 @@
+use cell_manager::cell_manager::coords::{
+    DoubledKind, OffsetKind, axial_to_cube, axial_to_doubled, axial_to_offset, cell_center, cell_polygon,
+    cube_to_axial, doubled_to_axial, hex_distance, hex_round, offset_to_axial, pixel_to_cell, polygon_contains,
+};
+
+const SHAPES: [(&str, i32); 8] = [
+    ("square", 1),
+    ("hexagon", 1),
+    ("triangle", 2),
+    ("rhombus", 3),
+    ("truncated_square", 2),
+    ("trihexagonal", 3),
+    ("snub_square", 6),
+    ("cairo", 4),
+];
+
+fn cells(layers: i32) -> impl Iterator<Item = (i32, i32, i32)> {
+    (-6..=6).flat_map(move |q| (-6..=6).flat_map(move |r| (0..layers).map(move |s| (q, r, s))))
+}
+
+#[test]
+fn centers_pick_their_own_cell() {
+    for (shape, layers) in SHAPES {
+        for (q, r, s) in cells(layers) {
+            let (x, y) = cell_center(shape, q, r, s);
+            assert_eq!(pixel_to_cell(shape, x, y), (q, r, s), "{shape} {q} {r} {s}");
+        }
+    }
+}
+
+#[test]
+fn polygons_surround_their_cell() {
+    for (shape, layers) in SHAPES {
+        for (q, r, s) in cells(layers) {
+            let (x, y) = cell_center(shape, q, r, s);
+            let polygon = cell_polygon(shape, q, r, s);
+            assert!(polygon_contains(&polygon, (x, y)), "{shape} {q} {r} {s}");
+
+            // Just inside every corner is still the same cell
+            for (px, py) in polygon {
+                let inside = (px * 0.9 + x * 0.1, py * 0.9 + y * 0.1);
+                assert_eq!(pixel_to_cell(shape, inside.0, inside.1), (q, r, s), "{shape} {q} {r} {s}");
+            }
+        }
+    }
+}
+
+#[test]
+fn hex_coordinates_round_trip() {
+    let offsets = [OffsetKind::OddR, OffsetKind::EvenR, OffsetKind::OddQ, OffsetKind::EvenQ];
+    for (q, r, _) in cells(1) {
+        let (x, y, z) = axial_to_cube(q, r);
+        assert_eq!(x + y + z, 0);
+        assert_eq!(cube_to_axial(x, y, z), (q, r));
+
+        for kind in offsets {
+            let (col, row) = axial_to_offset(q, r, kind);
+            assert_eq!(offset_to_axial(col, row, kind), (q, r));
+        }
+        for kind in [DoubledKind::Width, DoubledKind::Height] {
+            let (col, row) = axial_to_doubled(q, r, kind);
+            assert_eq!((col + row) % 2, 0);
+            assert_eq!(doubled_to_axial(col, row, kind), (q, r));
+        }
+    }
+}
+
+#[test]
+fn offset_rows_are_staggered() {
+    // Row 1 is pushed right in odd-r, so axial (0, 1) sits in column 0
+    assert_eq!(axial_to_offset(0, 1, OffsetKind::OddR), (0, 1));
+    assert_eq!(axial_to_offset(0, 1, OffsetKind::EvenR), (1, 1));
+    assert_eq!(axial_to_offset(-1, 2, OffsetKind::OddR), (0, 2));
+    assert_eq!(axial_to_offset(1, 0, OffsetKind::OddQ), (1, 0));
+    assert_eq!(axial_to_offset(1, 0, OffsetKind::EvenQ), (1, 1));
+}
+
+#[test]
+fn hex_rounding_and_distance() {
+    assert_eq!(hex_round(0.6, 0.1), (1, 0));
+    assert_eq!(hex_round(0.1, -0.2), (0, 0));
+    assert_eq!(hex_round(2.6, -1.1), (3, -1));
+    assert_eq!(hex_distance(3, -1), 3);
+    assert_eq!(hex_distance(-2, -2), 4);
+    for (q, r, _) in cells(1) {
+        assert_eq!(hex_distance(q, r), hex_distance(-q, -r));
+    }
+}
@@ -0,0 +1,98 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::coords::{cell_polygon, hex_distance};
+use cell_manager::cell_manager::drawing::{circle_cells, line_cells, polygon_cells};
+
+type Cell = (i32, i32, i32);
+
+// Whether two cells have two corners in common
+fn share_edge(shape: &str, a: Cell, b: Cell) -> bool {
+    let corners = cell_polygon(shape, b.0, b.1, b.2);
+    let shared = cell_polygon(shape, a.0, a.1, a.2)
+        .into_iter()
+        .filter(|&(x, y)| corners.iter().any(|&(u, v)| (x - u).abs() < 1e-3 && (y - v).abs() < 1e-3))
+        .count();
+    shared >= 2
+}
+
+fn ends(cells: &[Cell]) -> (Cell, Cell) {
+    (cells[0], cells[cells.len() - 1])
+}
+
+#[test]
+fn square_lines_follow_bresenham() {
+    let line = line_cells("square", (0, 0, 0), (5, 2, 0));
+    assert_eq!(line, [(0, 0, 0), (1, 0, 0), (2, 1, 0), (3, 1, 0), (4, 2, 0), (5, 2, 0)]);
+    assert_eq!(line_cells("square", (2, 2, 0), (2, 2, 0)), [(2, 2, 0)]);
+
+    for (q, r) in [(7, -3), (-5, -9), (0, 6), (-8, 1), (4, 4)] {
+        let cells = line_cells("square", (1, 1, 0), (q, r, 0));
+        assert_eq!(ends(&cells), ((1, 1, 0), (q, r, 0)));
+        // One cell per step along the longer axis, each a king's move apart
+        assert_eq!(cells.len() as i32, (q - 1).abs().max((r - 1).abs()) + 1);
+        for pair in cells.windows(2) {
+            assert_eq!((pair[1].0 - pair[0].0).abs().max((pair[1].1 - pair[0].1).abs()), 1);
+        }
+    }
+}
+
+#[test]
+fn hex_lines_step_to_neighbours() {
+    for (q, r) in [(5, -2), (-4, -3), (0, 7), (6, -6), (-3, 8)] {
+        let cells = line_cells("hexagon", (0, 0, 0), (q, r, 0));
+        assert_eq!(ends(&cells), ((0, 0, 0), (q, r, 0)));
+        assert_eq!(cells.len() as i32, hex_distance(q, r) + 1);
+        for pair in cells.windows(2) {
+            assert_eq!(hex_distance(pair[1].0 - pair[0].0, pair[1].1 - pair[0].1), 1);
+        }
+    }
+}
+
+#[test]
+fn triangle_and_rhombus_lines_cross_edges() {
+    for shape in ["triangle", "rhombus"] {
+        for from in [(0, 0, 0), (-3, 2, 1)] {
+            for q in -6..=6_i32 {
+                for r in -6..=6 {
+                    let to = (q, r, (q + r).rem_euclid(if shape == "triangle" { 2 } else { 3 }));
+                    let cells = line_cells(shape, from, to);
+                    assert_eq!(ends(&cells), (from, to));
+                    for pair in cells.windows(2) {
+                        assert!(share_edge(shape, pair[0], pair[1]), "{shape} {from:?} -> {to:?}: {pair:?}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn circles_and_polygons_cover_their_area() {
+    assert_eq!(circle_cells("square", (3, 3, 0), 0, true), [(3, 3, 0)]);
+    // Radius 1.5 around a square: the 3x3 block
+    let disk = circle_cells("square", (0, 0, 0), 1, true);
+    assert_eq!(disk.len(), 9);
+    assert_eq!(circle_cells("square", (0, 0, 0), 1, false).len(), 8);
+    assert_eq!(circle_cells("hexagon", (0, 0, 0), 2, true).len(), 19);
+    assert_eq!(circle_cells("hexagon", (0, 0, 0), 2, false).len(), 12);
+
+    let square = [(0, 0, 0), (4, 0, 0), (4, 4, 0), (0, 4, 0)];
+    assert_eq!(polygon_cells("square", &square, false).len(), 16);
+    assert_eq!(polygon_cells("square", &square, true).len(), 25);
+}
+
+#[test]
+fn drawing_writes_only_changed_cells_as_one_undo_step() {
+    let mut cm = CellManager::new(16, 16, 1, None);
+    cm.change_grid_properties("square".to_string(), "moore".to_string(), 1, "none".to_string());
+    cm.set_cell(2, 1, 0, 1);
+    assert_eq!(cm.draw_line((0, 0, 0), (4, 2, 0), 1), 4);
+    assert_eq!(cm.for_each_cell().len(), 5 * 4);
+    assert_eq!(cm.history().undo_label(), Some("line"));
+    assert!(cm.undo());
+    assert_eq!(cm.for_each_cell(), [2, 1, 0, 1]);
+
+    // Cells off a bounded grid are skipped
+    assert_eq!(cm.draw_line((5, 0, 0), (12, 0, 0), 1), 3);
+    assert_eq!(cm.draw_circle((0, 0, 0), 1, false, 2), 8);
+    assert_eq!(cm.get_cell(0, 0, 0), 0);
+}
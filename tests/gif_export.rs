@@ -0,0 +1,97 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::gif_export::{FrameRenderer, GifCrop, GifOptions, MAX_FRAME_PIXELS};
+
+mod common;
+use common::life;
+
+// Square Life grid holding a horizontal blinker around the origin
+fn blinker(width: usize) -> CellManager {
+    let mut cm = life(width, "none");
+    for q in -1..=1 {
+        cm.set_cell(q, 0, 0, 1);
+    }
+    cm
+}
+
+// Width, height and indexed pixels of every frame
+fn decode(bytes: &[u8]) -> (u16, u16, Vec<Vec<u8>>) {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(bytes).unwrap();
+    let (width, height) = (decoder.width(), decoder.height());
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push(frame.buffer.to_vec());
+    }
+    (width, height, frames)
+}
+
+#[test]
+fn blinker_exports_one_frame_per_recorded_generation() {
+    let mut cm = blinker(16);
+    let options = GifOptions { generations: 4, frame_delay: 5, cell_size: 2, ..GifOptions::default() };
+    let mut bytes = Vec::new();
+    cm.export_gif(&options, &mut bytes).unwrap();
+    assert!(bytes.starts_with(b"GIF89a"));
+    assert_eq!(cm.generation, 4);
+
+    let (width, height, frames) = decode(&bytes);
+    assert_eq!((width, height), (32, 32));
+    assert_eq!(frames.len(), 5);
+    // Three cells of 2x2 pixels lit in every phase
+    for frame in &frames {
+        assert_eq!(frame.iter().filter(|&&p| p == 1).count(), 12);
+    }
+    assert_ne!(frames[0], frames[1]);
+    assert_eq!(frames[0], frames[2]);
+
+    let skipping = GifOptions { generations: 4, frame_skip: 1, ..options };
+    let mut bytes = Vec::new();
+    cm.export_gif(&skipping, &mut bytes).unwrap();
+    let (_, _, frames) = decode(&bytes);
+    // Generations 4, 6 and 8, all in the horizontal phase
+    assert_eq!(frames.len(), 3);
+    assert!(frames.windows(2).all(|w| w[0] == w[1]));
+}
+
+#[test]
+fn live_bounds_crop_follows_the_pattern() {
+    let cm = blinker(16);
+    let snapshots = vec![cm.for_each_cell()];
+    assert_eq!(cm.gif_region(&GifCrop::LiveBounds(0), &snapshots), [-1, 1, 0, 0]);
+    assert_eq!(cm.gif_region(&GifCrop::LiveBounds(2), &snapshots), [-3, 3, -2, 2]);
+    assert_eq!(cm.gif_region(&GifCrop::LiveBounds(2), &[]), [-8, 7, -8, 7]);
+
+    let options = GifOptions { crop: GifCrop::LiveBounds(1), cell_size: 1, ..GifOptions::default() };
+    let mut bytes = Vec::new();
+    cm.write_gif_frames(&snapshots, &options, &mut bytes).unwrap();
+    let (width, height, frames) = decode(&bytes);
+    assert_eq!((width, height), (5, 3));
+    assert_eq!(frames[0], [0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn oversized_frames_are_refused() {
+    let side = (MAX_FRAME_PIXELS as f64).sqrt() as i32;
+    assert!(FrameRenderer::new("square", 1, [0, side, 0, side], 1).is_err());
+    assert!(FrameRenderer::new("square", 1, [0, 70_000, 0, 0], 1).is_err());
+    assert!(FrameRenderer::new("square", 1, [0, 9, 0, 9], usize::MAX).is_err());
+
+    // The full grid is too large, its live cells are not
+    let cm = blinker(100_000);
+    let snapshots = vec![cm.for_each_cell()];
+    let full = GifOptions { cell_size: 1, ..GifOptions::default() };
+    assert!(cm.write_gif_frames(&snapshots, &full, &mut Vec::new()).is_err());
+    let cropped = GifOptions { crop: GifCrop::LiveBounds(4), ..full };
+    assert!(cm.write_gif_frames(&snapshots, &cropped, &mut Vec::new()).is_ok());
+}
+
+#[test]
+fn states_past_the_palette_share_its_last_colour() {
+    let renderer = FrameRenderer::new("square", 1000, [0, 2, 0, 0], 1).unwrap();
+    assert_eq!(renderer.render(&[0, 0, 0, 7, 1, 0, 0, 255, 2, 0, 0, 300]), [7, 255, 255]);
+
+    // Below the palette size, states past the depth take the last state's colour
+    let renderer = FrameRenderer::new("square", 3, [0, 1, 0, 0], 1).unwrap();
+    assert_eq!(renderer.render(&[0, 0, 0, 2, 1, 0, 0, 9]), [2, 3]);
+}
@@ -0,0 +1,97 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::{CycleState, GraphManager};
+
+const SIDE: i32 = 12;
+
+// Edge list of the Moore neighborhood on a SIDE x SIDE torus, node q + r * SIDE
+fn torus_edges() -> String {
+    let node = |q: i32, r: i32| q.rem_euclid(SIDE) + r.rem_euclid(SIDE) * SIDE;
+    let mut text = String::new();
+    for r in 0..SIDE {
+        for q in 0..SIDE {
+            for (dq, dr) in [(1, -1), (1, 0), (1, 1), (0, 1)] {
+                text.push_str(&format!("{} {}\n", node(q, r), node(q + dq, r + dr)));
+            }
+        }
+    }
+    text
+}
+
+#[test]
+fn torus_graph_matches_the_lattice() {
+    let mut graph = GraphManager::from_edge_list(&torus_edges()).unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (144, 576));
+
+    let mut cm = CellManager::new(SIDE as usize, SIDE as usize, 1, None);
+    cm.change_grid_properties("square".to_string(), "moore".to_string(), 1, "torus".to_string());
+    let [min_q, max_q, min_r, max_r, _, _] = cm.get_bounds();
+    cm.random_cells_seeded(3, 0.4, [min_q, max_q, min_r, max_r]);
+    let label = |c: &[i32]| ((c[0] - min_q) + (c[1] - min_r) * SIDE).to_string();
+    let as_labels = |cells: Vec<i32>| {
+        let mut labels: Vec<String> = cells.chunks(4).map(label).collect();
+        labels.sort();
+        labels
+    };
+
+    for c in cm.for_each_cell().chunks(4) {
+        graph.set_cell(graph.node_index(&label(c)).unwrap(), 1);
+    }
+    for _ in 0..30 {
+        cm.step();
+        graph.step();
+        let mut live: Vec<&str> = graph.for_each_cell().chunks(4).map(|c| graph.label(c[0] as usize).unwrap()).collect();
+        live.sort();
+        assert_eq!(as_labels(cm.for_each_cell()), live);
+    }
+
+    // A lone blinker oscillates with period 2
+    graph.clear();
+    for label in ["13", "14", "15"] {
+        graph.set_cell(graph.node_index(label).unwrap(), 1);
+    }
+    graph.step_n(6);
+    assert!(matches!(graph.cycle_state(), CycleState::Oscillating { period: 2, .. }));
+    assert_eq!(graph.latest_stats().map(|s| s.population), Some(3));
+}
+
+#[test]
+fn edge_lists_parse_and_round_trip() {
+    let graph = GraphManager::from_edge_list("# roads\na b 2.5\nb c\nc a # loop\nc c\na b\nlonely\n").unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (4, 3));
+    assert!(graph.get_neighbors(graph.node_index("lonely").unwrap()).is_empty());
+
+    let text = graph.to_edge_list();
+    let back = GraphManager::from_edge_list(&text).unwrap();
+    assert_eq!(back.to_edge_list(), text);
+
+    for bad in ["a b c d", "a b -1", "a b x"] {
+        assert!(GraphManager::from_edge_list(bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn generated_graphs_have_their_shape() {
+    let ws = GraphManager::watts_strogatz(200, 6, 0.2, 1);
+    assert_eq!(ws.edge_count(), 600);
+
+    let geometric = GraphManager::random_geometric(500, 1.5, 1);
+    for node in 0..geometric.node_count() {
+        let (x, y) = geometric.position(node).unwrap();
+        for &n in geometric.get_neighbors(node) {
+            let (nx, ny) = geometric.position(n).unwrap();
+            assert!((x - nx).hypot(y - ny) <= 1.5);
+        }
+    }
+
+    let mut penrose = GraphManager::generate("penrose:5", 0).unwrap();
+    assert!((0..penrose.node_count()).all(|n| (2..=7).contains(&penrose.get_neighbors(n).len())));
+    assert_eq!(penrose.node_index("42"), Some(42));
+    // Every edge is 1 long, so a kernel weighs them all alike
+    assert!(penrose.set_kernel("gaussian:1"));
+    let w = (-0.5f32).exp();
+    assert!((0..penrose.node_count()).all(|n| penrose.get_neighbor_weights(n).iter().all(|x| (x - w).abs() < 1e-4)));
+
+    for bad in ["penrose", "geometric:10", "ws:10,2,x", "lattice:3", "penrose:40", "geometric:1e12,1", "ws:1e12,4,0.1"] {
+        assert!(GraphManager::generate(bad, 0).is_err(), "{bad}");
+    }
+}
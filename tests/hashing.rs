@@ -0,0 +1,95 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::{CycleState, GridHash};
+
+mod common;
+use common::life;
+
+// Square Life grid holding `cells` (state 1), with the starting
+// generation recorded
+fn seeded(topology: &str, cells: &[(i32, i32)]) -> CellManager {
+    let mut cm = life(32, topology);
+    for &(q, r) in cells {
+        cm.set_cell(q, r, 0, 1);
+    }
+    cm.record_stats();
+    cm
+}
+
+const GLIDER: [(i32, i32); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+#[test]
+fn blinker_oscillates_with_period_2() {
+    let mut cm = seeded("none", &[(-1, 0), (0, 0), (1, 0)]);
+    cm.step();
+    assert_eq!(cm.cycle_state(), CycleState::Running);
+    cm.step();
+    assert_eq!(cm.cycle_state(), CycleState::Oscillating { start: 0, period: 2 });
+    // The start of a cycle that keeps going does not move
+    cm.step_n(5);
+    assert_eq!(cm.cycle_state(), CycleState::Oscillating { start: 0, period: 2 });
+}
+
+#[test]
+fn still_lifes_and_extinction_are_reported() {
+    let mut cm = seeded("none", &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    cm.step_n(3);
+    assert_eq!(cm.cycle_state(), CycleState::Still { since: 0 });
+
+    let mut cm = seeded("none", &[(0, 0), (5, 5)]);
+    cm.step_n(3);
+    assert_eq!(cm.cycle_state(), CycleState::Extinct { since: 1 });
+}
+
+#[test]
+fn glider_moves_one_cell_diagonally_every_4_generations() {
+    let mut cm = seeded("none", &GLIDER);
+    cm.step_n(3);
+    assert_eq!(cm.cycle_state(), CycleState::Running);
+    cm.step();
+    assert_eq!(cm.cycle_state(), CycleState::Moving { start: 0, period: 4, dq: 1, dr: 1 });
+    cm.step_n(8);
+    assert_eq!(cm.cycle_state(), CycleState::Moving { start: 0, period: 4, dq: 1, dr: 1 });
+
+    // Once around a 32 cell torus the glider is back where it started
+    let mut cm = seeded("torus", &GLIDER);
+    cm.set_hash_history_capacity(256);
+    cm.step_n(128);
+    assert!(matches!(cm.cycle_state(), CycleState::Oscillating { period: 128, .. }), "{:?}", cm.cycle_state());
+}
+
+#[test]
+fn incremental_hash_matches_a_fresh_one() {
+    let mut cm = seeded("torus", &[]);
+    // A fixed scatter over the whole torus
+    let mut seed: u32 = 4;
+    for q in -16..16 {
+        for r in -16..16 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            if (seed >> 16) % 20 < 7 {
+                cm.set_cell(q, r, 0, 1);
+            }
+        }
+    }
+    for _ in 0..10 {
+        cm.step();
+        assert_eq!(cm.state_hash(), GridHash::from_cells(&cm.for_each_cell()).zobrist);
+    }
+
+    cm.set_cell(3, 3, 0, 1 - cm.get_cell(3, 3, 0));
+    cm.batch_update(vec![(0, 0, 0, 1), (1, 0, 0, 0)], None);
+    assert_eq!(cm.state_hash(), GridHash::from_cells(&cm.for_each_cell()).zobrist);
+    cm.clear();
+    assert_eq!(cm.state_hash(), GridHash::default().zobrist);
+}
+
+#[test]
+fn edits_restart_cycle_detection() {
+    let mut cm = seeded("none", &[(-1, 0), (0, 0), (1, 0)]);
+    cm.step_n(2);
+    assert!(matches!(cm.cycle_state(), CycleState::Oscillating { .. }));
+    cm.set_cell(10, 10, 0, 1);
+    cm.step_n(2);
+    assert_eq!(cm.cycle_state(), CycleState::Running);
+    cm.step();
+    assert_eq!(cm.cycle_state(), CycleState::Oscillating { start: 3, period: 2 });
+}
@@ -0,0 +1,112 @@
+use std::mem::size_of;
+
+use cell_manager::cell_manager::cellmanager::CellChange;
+
+mod common;
+use common::{life, sorted};
+
+#[test]
+fn every_edit_undoes_and_redoes() {
+    let mut cm = life(16, "none");
+    let mut snapshots = vec![sorted(cm.for_each_cell())];
+    cm.set_cell(0, 0, 0, 1);
+    snapshots.push(sorted(cm.for_each_cell()));
+    cm.batch_update(vec![(1, 0, 0, 1), (2, 0, 0, 1), (0, 0, 0, 0)], None);
+    snapshots.push(sorted(cm.for_each_cell()));
+    cm.random_cells_seeded(3, 0.5, [-8, 7, -8, 7]);
+    snapshots.push(sorted(cm.for_each_cell()));
+    cm.clear();
+    snapshots.push(sorted(cm.for_each_cell()));
+
+    let labels = ["clear", "random_cells", "batch_update", "set_cell"];
+    for (i, label) in labels.iter().enumerate() {
+        assert_eq!(cm.history().undo_label(), Some(*label));
+        assert!(cm.undo());
+        assert_eq!(sorted(cm.for_each_cell()), snapshots[3 - i], "undo {label}");
+    }
+    assert!(!cm.undo());
+
+    for (i, label) in labels.iter().rev().enumerate() {
+        assert_eq!(cm.history().redo_label(), Some(*label));
+        assert!(cm.redo());
+        assert_eq!(sorted(cm.for_each_cell()), snapshots[i + 1], "redo {label}");
+    }
+    assert!(!cm.redo());
+}
+
+#[test]
+fn transactions_group_and_coalesce_writes() {
+    let mut cm = life(16, "none");
+    cm.begin_transaction("stroke");
+    cm.set_cell(0, 0, 0, 1);
+    cm.set_cell(0, 0, 0, 2);
+    cm.batch_update(vec![(1, 1, 0, 1), (2, 2, 0, 1)], None);
+    // Undo waits for the stroke to finish
+    assert!(!cm.undo());
+    cm.commit_transaction();
+
+    assert_eq!(cm.history().undo_label(), Some("stroke"));
+    assert_eq!(cm.history().memory_used(), 3 * size_of::<CellChange>() + "stroke".len());
+    assert!(cm.undo());
+    assert!(cm.for_each_cell().is_empty());
+    assert!(!cm.history().can_undo());
+
+    // Writes that end where they started leave nothing to undo
+    cm.begin_transaction("noop");
+    cm.set_cell(3, 3, 0, 1);
+    cm.set_cell(3, 3, 0, 0);
+    cm.commit_transaction();
+    assert_eq!(cm.history().undo_label(), None);
+    // and a new edit drops what could be redone
+    assert!(cm.history().can_redo());
+    cm.set_cell(4, 4, 0, 1);
+    assert!(!cm.history().can_redo());
+}
+
+#[test]
+fn budget_drops_the_oldest_transactions() {
+    let mut cm = life(16, "none");
+    let one = size_of::<CellChange>() + "set_cell".len();
+    cm.set_history_budget(3 * one);
+    for q in 0..5 {
+        cm.set_cell(q, 0, 0, 1);
+    }
+    assert_eq!(cm.history().memory_used(), 3 * one);
+    while cm.undo() {}
+    // The first two writes can no longer be reverted
+    assert_eq!(sorted(cm.for_each_cell()), [[0, 0, 0, 1], [1, 0, 0, 1]]);
+
+    // A single transaction over the budget is not kept at all
+    cm.set_history_budget(one);
+    cm.batch_update(vec![(5, 5, 0, 1), (6, 6, 0, 1)], None);
+    assert!(!cm.history().can_undo());
+    assert!(!cm.history().can_redo());
+    assert_eq!(cm.history().memory_used(), 0);
+}
+
+#[test]
+fn steps_and_jumps_clear_the_history() {
+    let mut cm = life(16, "none");
+    cm.set_timeline(64, 1024);
+    cm.batch_update(vec![(-1, 0, 0, 1), (0, 0, 0, 1), (1, 0, 0, 1)], None);
+    cm.step();
+    assert!(!cm.undo());
+    assert_eq!(cm.for_each_cell().len(), 12);
+
+    cm.set_cell(5, 5, 0, 1);
+    assert!(cm.history().can_undo());
+    assert!(cm.step_back());
+    assert!(!cm.history().can_undo());
+    assert!(!cm.undo());
+    assert_eq!(cm.get_cell(5, 5, 0), 0);
+
+    // A step in the middle of a transaction drops what it had recorded
+    cm.begin_transaction("stroke");
+    cm.set_cell(-5, -5, 0, 1);
+    cm.step();
+    cm.set_cell(4, 4, 0, 1);
+    cm.commit_transaction();
+    assert!(cm.undo());
+    assert_eq!(cm.get_cell(4, 4, 0), 0);
+    assert_eq!(cm.generation, 1);
+}
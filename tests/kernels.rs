@@ -0,0 +1,62 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::{Kernel, Neighborhood, Rule};
+
+fn soup(rule: &str, kernel: &str) -> CellManager {
+    let mut cm = CellManager::new(24, 24, 1, None);
+    cm.change_grid_properties("square".to_string(), "moore".to_string(), 1, "torus".to_string());
+    assert!(cm.set_rule(rule));
+    assert!(cm.set_kernel(kernel));
+    cm.random_cells_seeded(7, 0.4, [-12, 11, -12, 11]);
+    cm
+}
+
+#[test]
+fn uniform_weighted_rule_matches_the_plain_count() {
+    let mut plain = soup("B3/S23", "uniform");
+    let mut weighted = soup("W:B3/S2-3", "uniform");
+    for _ in 0..20 {
+        plain.step();
+        weighted.step();
+        assert_eq!(plain.for_each_cell(), weighted.for_each_cell());
+    }
+}
+
+#[test]
+fn kernels_weigh_by_distance() {
+    let gaussian = Kernel::parse("gaussian:1").unwrap();
+    assert_eq!(Kernel::parse(&gaussian.name()), Some(gaussian));
+    assert!(gaussian.weight(0.0) > gaussian.weight(1.0) && gaussian.weight(1.0) > gaussian.weight(2.0));
+
+    let ring = Kernel::parse("ring:2,0.5").unwrap();
+    assert!(ring.weight(2.0) > ring.weight(1.0) && ring.weight(2.0) > ring.weight(3.0));
+
+    let inverse = Kernel::parse("inverse:2").unwrap();
+    assert_eq!(inverse.weight(2.0), 0.25);
+    assert_eq!(inverse.weight(0.0), 1.0);
+
+    for bad in ["gaussian:0", "ring:1,-1", "uniform:2", "cone"] {
+        assert_eq!(Kernel::parse(bad), None, "{bad}");
+    }
+
+    // Edge neighbors are 1 apart on squares and hexagons alike
+    for (shape, kind) in [("square", "vonNeumann"), ("hexagon", "mask:.#.#./#.O.#/.#.#.")] {
+        let mut n = Neighborhood::new(shape, kind, 1);
+        n.set_kernel(gaussian);
+        for &w in n.get_neighbor_weights(0) {
+            assert!((w - gaussian.weight(1.0)).abs() < 1e-5, "{shape} {w}");
+        }
+    }
+}
+
+#[test]
+fn weighted_rules_parse_ranges_of_sums() {
+    let rule = Rule::parse("W:B0.5-1.25,3/S-1-0.5").unwrap();
+    assert!(rule.weighted);
+    assert_eq!(rule.birth_sums, vec![(0.5, 1.25), (3.0, 3.0)]);
+    assert_eq!(rule.survival_sums, vec![(-1.0, 0.5)]);
+    assert_eq!(rule.next_state_weighted(0, 1.0), 1);
+    assert_eq!(rule.next_state_weighted(0, 2.0), 0);
+    assert_eq!(rule.next_state_weighted(2, 0.25), 2);
+    assert_eq!(rule.next_state_weighted(1, 0.75), 0);
+    assert!(Rule::parse("W:B2-1").is_none());
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use cell_manager::CellManager;
+
+fn grid(shape: &str, width: usize) -> CellManager {
+    let mut cm = CellManager::new(width, width, 1, None);
+    cm.change_grid_properties(shape.to_string(), "moore".to_string(), 1, "none".to_string());
+    cm
+}
+
+fn square(cm: &mut CellManager, lo: i32, hi: i32) {
+    for q in lo..=hi {
+        for r in lo..=hi {
+            cm.set_cell(q, r, 0, 1);
+        }
+    }
+}
+
+fn live(cm: &CellManager) -> Vec<(i32, i32)> {
+    let mut cells: Vec<(i32, i32)> = cm.for_each_cell().chunks(4).map(|c| (c[0], c[1])).collect();
+    cells.sort();
+    cells
+}
+
+#[test]
+fn erosion_undoes_dilation_of_a_block() {
+    let mut cm = grid("square", 16);
+    square(&mut cm, 0, 1);
+    let block = live(&cm);
+    let moore = cm.structuring_element("moore", 1);
+
+    assert_eq!(cm.dilate(&moore, 1), 12);
+    assert_eq!(live(&cm).len(), 16);
+    assert_eq!(cm.erode(&moore), 12);
+    assert_eq!(live(&cm), block);
+
+    // Closing a block changes nothing overall, opening wipes it out
+    assert_eq!(cm.close(&moore, 1), 24);
+    assert_eq!(live(&cm), block);
+    assert_eq!(cm.open(&moore, 1), 4);
+    assert!(live(&cm).is_empty());
+    // Each operation is a single undo step
+    assert!(cm.undo());
+    assert_eq!(live(&cm), block);
+}
+
+#[test]
+fn opening_and_closing_clean_up_squares() {
+    let mut cm = grid("square", 16);
+    square(&mut cm, -2, 2);
+    let full = live(&cm);
+    let moore = cm.structuring_element("moore", 1);
+
+    cm.set_cell(0, 0, 0, 0);
+    cm.set_cell(5, -6, 0, 1);
+    cm.close(&moore, 1);
+    // The hole is filled, and the speck has grown and shrunk back
+    assert_eq!(live(&cm), [full.clone(), vec![(5, -6)]].concat());
+    cm.open(&moore, 1);
+    assert_eq!(live(&cm), full);
+}
+
+#[test]
+fn elements_follow_the_neighborhood() {
+    let mut cm = grid("square", 16);
+    cm.set_cell(0, 0, 0, 1);
+    assert_eq!(cm.dilate(&cm.structuring_element("vonNeumann", 1), 2), 4);
+    assert_eq!(cm.get_cell(1, 0, 0), 2);
+    assert_eq!(cm.get_cell(1, 1, 0), 0);
+    cm.clear();
+    cm.set_cell(0, 0, 0, 1);
+    assert_eq!(cm.dilate(&cm.structuring_element("moore", 2), 1), 24);
+
+    let mut cm = grid("hexagon", 16);
+    cm.set_cell(0, 0, 0, 1);
+    assert_eq!(cm.dilate(&cm.structuring_element("asterix", 1), 1), 6);
+    assert_eq!(cm.dilate(&cm.structuring_element("asterix", 1), 1), 12);
+}
+
+#[test]
+fn boundaries_and_distances_peel_layers() {
+    let mut cm = grid("square", 16);
+    square(&mut cm, -2, 2);
+    let moore = cm.structuring_element("moore", 1);
+
+    let mut layers: HashMap<u32, usize> = HashMap::new();
+    for d in cm.distance_transform(&moore).into_values() {
+        *layers.entry(d).or_default() += 1;
+    }
+    assert_eq!(layers, HashMap::from([(1, 16), (2, 8), (3, 1)]));
+    assert_eq!(cm.boundary(&moore).len(), 16);
+
+    assert_eq!(cm.extract_boundary(&moore), 9);
+    assert_eq!(live(&cm).len(), 16);
+    assert_eq!(cm.get_cell(0, 0, 0), 0);
+}
+
+#[test]
+fn cells_off_a_bounded_grid_do_not_erode() {
+    let mut cm = grid("square", 4);
+    square(&mut cm, -2, 1);
+    let moore = cm.structuring_element("moore", 1);
+    assert!(cm.boundary(&moore).is_empty());
+    assert_eq!(cm.erode(&moore), 0);
+    assert_eq!(live(&cm).len(), 16);
+}
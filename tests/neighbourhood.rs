@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+
+use cell_manager::CellManager;
+use cell_manager::cell_manager::Neighborhood;
+use cell_manager::cell_manager::pattern_io::{parse_cell_list, write_cell_list};
+use cell_manager::cell_manager::coords::{cell_polygon, hex_distance, shape_layers, triangle_vertices};
+use cell_manager::cell_manager::selection::rotate_cell;
+use cell_manager::cell_manager::tilings::TILINGS;
+
+type Cell = (i32, i32, i32);
+
+// Cells around `(q, r, s)` under a neighborhood's offsets
+fn neighbors_of(n: &Neighborhood, (q, r, s): Cell) -> HashSet<Cell> {
+    n.get_neighbor_offsets(s)
+        .iter()
+        .map(|&(dq, dr, ds)| (q + dq, r + dr, s + ds))
+        .collect()
+}
+
+#[test]
+fn triangle_moore_range_1_is_the_12_corner_neighbors() {
+    let n = Neighborhood::new("triangle", "moore", 1);
+    assert_eq!(n.adj_neighbors.len(), 2);
+
+    for s in 0..2 {
+        let cell = (0, 0, s);
+        let offsets = n.get_neighbor_offsets(s);
+        assert_eq!(offsets.len(), 12);
+        assert!(!offsets.contains(&(0, 0, 0)));
+
+        let corners = triangle_vertices(0, 0, s);
+        let neighbors = neighbors_of(&n, cell);
+        assert_eq!(neighbors.len(), 12, "duplicate offsets");
+        for &(q, r, t) in &neighbors {
+            assert!((0..2).contains(&t));
+            assert!(
+                triangle_vertices(q, r, t).iter().any(|v| corners.contains(v)),
+                "{:?} does not touch {:?}",
+                (q, r, t),
+                cell
+            );
+        }
+    }
+}
+
+#[test]
+fn triangle_moore_grows_by_corner_steps() {
+    let mut previous = Neighborhood::new("triangle", "moore", 1);
+    for range in 2..=5 {
+        let n = Neighborhood::new("triangle", "moore", range);
+        for s in 0..2 {
+            let inner = neighbors_of(&previous, (0, 0, s));
+            let outer = neighbors_of(&n, (0, 0, s));
+            assert!(inner.is_subset(&outer), "range {range} layer {s}");
+            assert!(!outer.contains(&(0, 0, s)));
+            assert_eq!(outer.len(), n.get_neighbor_offsets(s).len(), "duplicate offsets");
+        }
+        previous = n;
+    }
+}
+
+#[test]
+fn triangle_moore_is_symmetric_under_120_degree_rotation() {
+    for range in 1..=5 {
+        let n = Neighborhood::new("triangle", "moore", range);
+        for (q, r, s) in [(0, 0, 0), (0, 0, 1), (2, -1, 0), (-3, 4, 1)] {
+            let cell = (q, r, s);
+            let rotated: HashSet<Cell> = neighbors_of(&n, cell)
+                .into_iter()
+                .map(|c| rotate_cell("triangle", c, 1))
+                .collect();
+            assert_eq!(rotated, neighbors_of(&n, rotate_cell("triangle", cell, 1)), "range {range} {cell:?}");
+        }
+    }
+}
+
+#[test]
+fn custom_masks_and_offset_lists() {
+    let hex = Neighborhood::from_mask("hexagon", ".#.#.\n#.O.#\n.#.#.").unwrap();
+    let hex_ring: HashSet<Cell> = [(1, 0, 0), (1, -1, 0), (0, -1, 0), (-1, 0, 0), (-1, 1, 0), (0, 1, 0)].into();
+    assert_eq!(neighbors_of(&hex, (0, 0, 0)), hex_ring);
+    assert_eq!(hex.chosen_type, "mask:.#.#./#.O.#/.#.#.");
+    assert!(hex.is_custom());
+
+    let square = Neighborhood::from_mask("square", "#..\n.O.\n..#").unwrap();
+    assert_eq!(neighbors_of(&square, (0, 0, 0)), [(-1, -1, 0), (1, 1, 0)].into());
+    let listed = Neighborhood::from_offsets("square", &[(-1, -1, 0), (1, 1, 0)]).unwrap();
+    assert_eq!(listed.adj_neighbors, square.adj_neighbors);
+
+    let triangle = Neighborhood::from_parity_offsets("triangle", &[vec![(0, 0, 1)], vec![(0, 0, -1)]]).unwrap();
+    assert_eq!(neighbors_of(&triangle, (2, 3, 1)), [(2, 3, 0)].into());
+
+    for (shape, spec) in [
+        ("hexagon", "mask:#O"),
+        ("square", "mask:#.#"),
+        ("square", "mask:O#/#O"),
+        ("square", "offsets:1,x"),
+        ("triangle", "offsets:0,0,1"),
+        ("rhombus", "parity:1,0|0,1"),
+        ("square", "no-such-kind"),
+        ("triangle", "moore0"),
+    ] {
+        assert!(Neighborhood::parse(shape, spec, 1).is_err(), "{shape} {spec}");
+    }
+}
+
+#[test]
+fn custom_neighborhoods_save_with_the_grid() {
+    let mut cm = CellManager::new(8, 8, 2, None);
+    let spec = "parity: 0,0,1; 1,0,1 | 0,0,-1; -1,0,-1";
+    cm.change_grid_properties("triangle".to_string(), spec.to_string(), 1, "torus".to_string());
+    assert_eq!(cm.config.neighbor_type, "parity:0,0,1;1,0,1|0,0,-1;-1,0,-1");
+
+    let pattern = parse_cell_list(&write_cell_list(&cm)).unwrap();
+    let saved = pattern.property("neighbor_type").unwrap();
+    let loaded = Neighborhood::parse("triangle", saved, 1).unwrap();
+    assert_eq!(loaded.adj_neighbors, cm.neighborhood().adj_neighbors);
+}
+
+// Offsets of a single-layer neighborhood, checked for duplicates and the centre
+fn offset_set(n: &Neighborhood) -> HashSet<Cell> {
+    let offsets = n.get_neighbor_offsets(0);
+    let set: HashSet<Cell> = offsets.iter().copied().collect();
+    assert_eq!(set.len(), offsets.len(), "{} {} has duplicate offsets", n.chosen_type, n.range);
+    assert!(!set.contains(&(0, 0, 0)));
+    set
+}
+
+#[test]
+fn square_circular_counts_for_ranges_1_to_5() {
+    for (range, count) in (1..=5).zip([8, 20, 36, 68, 96]) {
+        let offsets = offset_set(&Neighborhood::new("square", "circular", range));
+        assert_eq!(offsets.len(), count, "range {range}");
+
+        let moore = offset_set(&Neighborhood::new("square", "moore", range));
+        let von_neumann = offset_set(&Neighborhood::new("square", "vonNeumann", range));
+        assert!(von_neumann.is_subset(&offsets) && offsets.is_subset(&moore), "range {range}");
+        for &(dq, dr, _) in &offsets {
+            assert!(((dq * dq + dr * dr) as f32).sqrt() <= range as f32 + 0.5);
+        }
+    }
+}
+
+#[test]
+fn hexagon_disks_and_rings_for_ranges_1_to_5() {
+    let adjacent: HashSet<Cell> = [(1, 0, 0), (1, -1, 0), (0, -1, 0), (-1, 0, 0), (-1, 1, 0), (0, 1, 0)].into();
+    assert_eq!(offset_set(&Neighborhood::new("hexagon", "hexagonal", 1)), adjacent);
+    assert_eq!(offset_set(&Neighborhood::new("hexagon", "ring", 1)), adjacent);
+
+    let mut inner: HashSet<Cell> = HashSet::new();
+    for range in 1..=5 {
+        let disk = offset_set(&Neighborhood::new("hexagon", "hexagonal", range));
+        let ring = offset_set(&Neighborhood::new("hexagon", "ring", range));
+        assert_eq!(disk.len() as i32, 3 * range * (range + 1), "range {range}");
+        assert_eq!(ring.len() as i32, 6 * range, "range {range}");
+        for &(dq, dr, _) in &ring {
+            assert_eq!(hex_distance(dq, dr), range);
+        }
+        assert_eq!(inner.union(&ring).copied().collect::<HashSet<Cell>>(), disk);
+        inner = disk;
+    }
+}
+
+#[test]
+fn built_in_neighborhoods_are_mutual_and_symmetric() {
+    for shape in ["square", "hexagon", "triangle", "rhombus"].into_iter().chain(TILINGS) {
+        for &kind in Neighborhood::kinds_for_shape(shape) {
+            for range in 1..=3 {
+                let report = Neighborhood::new(shape, kind, range).report();
+                assert_eq!(report.duplicates, 0, "{shape} {kind} {range}");
+                if kind == "tripod" {
+                    assert!(!report.is_reciprocal());
+                    assert_eq!(report.symmetry_group(), "D3");
+                } else {
+                    assert!(report.is_reciprocal(), "{shape} {kind} {range}: {:?}", report.one_way);
+                    assert!(report.is_isotropic(), "{shape} {kind} {range}: {}", report.symmetry_group());
+                }
+            }
+        }
+    }
+    let report = Neighborhood::new("square", "moore", 1).report();
+    assert_eq!((report.symmetry_group(), report.full_rotations), ("D4".to_string(), 4));
+}
+
+#[test]
+fn validation_drops_duplicates_and_finds_one_way_offsets() {
+    let mut n = Neighborhood::from_offsets("square", &[(1, 0, 0), (-1, 0, 0), (1, 0, 0), (0, 1, 0)]).unwrap();
+    let report = n.validate();
+    assert_eq!(report.duplicates, 1);
+    assert_eq!(n.get_neighbor_offsets(0), &[(1, 0, 0), (-1, 0, 0), (0, 1, 0)]);
+    assert_eq!(n.get_neighbor_weights(0).len(), 3);
+    assert_eq!(report.one_way, vec![(0, (0, 1, 0))]);
+    assert_eq!(report.symmetry_group(), "D1");
+    assert_eq!(n.validate().duplicates, 0);
+
+    // A triangle seeing its right-hand neighbor without being seen back
+    let parity = Neighborhood::from_parity_offsets("triangle", &[vec![(0, 0, 1)], vec![(0, 0, -1), (1, 0, -1)]]).unwrap();
+    assert_eq!(parity.report().one_way, vec![(1, (1, 0, -1))]);
+}
+
+// Corners of a rhombus, snapped so shared corners compare equal
+fn rhombus_corners((q, r, s): Cell) -> HashSet<(i32, i32)> {
+    cell_polygon("rhombus", q, r, s)
+        .into_iter()
+        .map(|(x, y)| ((x * 1000.0).round() as i32, (y * 1000.0).round() as i32))
+        .collect()
+}
+
+#[test]
+fn rhombus_neighborhoods_follow_the_tile_geometry() {
+    let qbert = Neighborhood::new("rhombus", "Qbert", 1);
+    let moore = Neighborhood::new("rhombus", "moore", 1);
+    let von_neumann = Neighborhood::new("rhombus", "vonNeumann", 1);
+    for s in 0..3 {
+        let cell = (0, 0, s);
+        let corners = rhombus_corners(cell);
+        let shared = |c: &Cell| rhombus_corners(*c).intersection(&corners).count();
+
+        assert_eq!(neighbors_of(&qbert, cell), neighbors_of(&moore, cell));
+        assert_eq!(moore.get_neighbor_offsets(s).len(), 10);
+        assert!(neighbors_of(&moore, cell).iter().all(|c| shared(c) >= 1));
+        assert_eq!(von_neumann.get_neighbor_offsets(s).len(), 4);
+        assert!(neighbors_of(&von_neumann, cell).iter().all(|c| shared(c) == 2));
+    }
+
+    for kind in ["vonNeumann", "moore"] {
+        for range in 2..=4 {
+            let inner = Neighborhood::new("rhombus", kind, range - 1);
+            let outer = Neighborhood::new("rhombus", kind, range);
+            for s in 0..3 {
+                assert!(neighbors_of(&inner, (0, 0, s)).is_subset(&neighbors_of(&outer, (0, 0, s))));
+                assert_eq!(outer.get_neighbor_offsets(s).len(), outer.get_neighbor_offsets(0).len());
+            }
+        }
+    }
+}
+
+#[test]
+fn tiling_neighborhoods_share_edges_and_corners() {
+    let counts: [(&str, &[usize], &[usize]); 4] = [
+        ("truncated_square", &[8, 4], &[8, 4]),
+        ("trihexagonal", &[6, 3, 3], &[12, 6, 6]),
+        ("snub_square", &[4, 4, 3, 3, 3, 3], &[12, 12, 9, 9, 9, 9]),
+        ("cairo", &[5, 5, 5, 5], &[7, 7, 7, 7]),
+    ];
+    for (shape, edges, corners) in counts {
+        assert_eq!(shape_layers(shape) as usize, edges.len());
+        let von_neumann = Neighborhood::new(shape, "vonNeumann", 1);
+        let moore = Neighborhood::new(shape, "moore", 1);
+        for s in 0..shape_layers(shape) {
+            let polygon = cell_polygon(shape, 0, 0, s);
+            let shared = |&(q, r, t): &Cell| {
+                let other = cell_polygon(shape, q, r, t);
+                polygon.iter().filter(|p| other.iter().any(|o| (o.0 - p.0).hypot(o.1 - p.1) < 1e-3)).count()
+            };
+            let edge_cells = neighbors_of(&von_neumann, (0, 0, s));
+            let corner_cells = neighbors_of(&moore, (0, 0, s));
+            assert_eq!(edge_cells.len(), edges[s as usize], "{shape} {s}");
+            assert_eq!(corner_cells.len(), corners[s as usize], "{shape} {s}");
+            assert!(edge_cells.iter().all(|c| shared(c) == 2), "{shape} {s}");
+            assert!(corner_cells.iter().all(|c| shared(c) >= 1), "{shape} {s}");
+            assert!(edge_cells.is_subset(&corner_cells));
+        }
+    }
+}
+
+#[test]
+fn cube_neighborhoods_fill_faces_edges_and_balls() {
+    let counts = [
+        ("vonNeumann", [6, 24, 62]),
+        ("faceEdge", [18, 92, 262]),
+        ("moore", [26, 124, 342]),
+        ("spherical", [18, 80, 178]),
+    ];
+    for (kind, by_range) in counts {
+        for (range, expected) in (1..).zip(by_range) {
+            let n = Neighborhood::parse("cube", kind, range).unwrap();
+            assert_eq!(n.get_neighbor_offsets(7).len(), expected, "{kind} {range}");
+            let report = n.report();
+            assert!(report.is_reciprocal() && report.is_isotropic(), "{kind} {range}");
+        }
+    }
+    let moore = Neighborhood::new("cube", "moore", 1);
+    assert!(moore.get_neighbor_offsets(0).iter().all(|&(dq, dr, ds)| dq.abs().max(dr.abs()).max(ds.abs()) == 1));
+}
@@ -0,0 +1,109 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::gif_export::GifCrop;
+use cell_manager::cell_manager::pattern_io::{
+    MAX_PATTERN_SIZE, parse_cell_list, parse_pattern, parse_plaintext, parse_rle, write_cell_list,
+};
+use cell_manager::native_interface::{StopCondition, parse_run_args};
+
+fn sorted(mut cells: Vec<(i32, i32, i32, u32)>) -> Vec<(i32, i32, i32, u32)> {
+    cells.sort();
+    cells
+}
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+#[test]
+fn rle_and_plaintext_are_centred_on_the_origin() {
+    let glider = parse_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$\n3o!").unwrap();
+    assert_eq!(glider.rule.as_deref(), Some("B3/S23"));
+    assert_eq!(sorted(glider.cells), [(-1, 1, 0, 1), (0, -1, 0, 1), (0, 1, 0, 1), (1, 0, 0, 1), (1, 1, 0, 1)]);
+
+    // Letters are states, and `.` is dead like `b`
+    let multistate = parse_rle("x = 4, y = 1\nA.2C!").unwrap();
+    assert_eq!(multistate.cells, [(-2, 0, 0, 1), (0, 0, 0, 3), (1, 0, 0, 3)]);
+
+    let blinker = parse_plaintext("!Name: Blinker\n.O.\n.O.\n.O.\n").unwrap();
+    assert_eq!(blinker.cells, [(0, -1, 0, 1), (0, 0, 0, 1), (0, 1, 0, 1)]);
+    assert!(parse_plaintext(".O.\n.X.\n").is_err());
+
+    // The format is picked from the content
+    assert_eq!(parse_pattern("#C comment\nx = 1, y = 1\no!").unwrap().cells, [(0, 0, 0, 1)]);
+    assert_eq!(parse_pattern("!Name: dot\nO\n").unwrap().cells, [(0, 0, 0, 1)]);
+    assert_eq!(parse_pattern("3 4\n").unwrap().cells, [(3, 4, 0, 1)]);
+}
+
+#[test]
+fn cell_lists_carry_the_grid_configuration() {
+    let pattern = parse_cell_list("# shape=hexagon rule=B2/S34\n1 2 0 3\n\n4,5\n-1 -2 1\n").unwrap();
+    assert_eq!(pattern.rule.as_deref(), Some("B2/S34"));
+    assert_eq!(pattern.property("shape"), Some("hexagon"));
+    assert_eq!(pattern.property("range"), None);
+    assert_eq!(pattern.cells, [(1, 2, 0, 3), (4, 5, 0, 1), (-1, -2, 1, 1)]);
+
+    let mut cm = CellManager::new(16, 16, 1, None);
+    cm.change_grid_properties("hexagon".to_string(), "tripod".to_string(), 1, "torus".to_string());
+    assert!(cm.set_rule("B2/S34"));
+    cm.batch_update(vec![(0, 0, 0, 1), (3, -2, 0, 2), (-8, 7, 0, 3)], None);
+    let loaded = parse_cell_list(&write_cell_list(&cm)).unwrap();
+    let cells: Vec<_> = cm.for_each_cell().chunks(4).map(|c| (c[0], c[1], c[2], c[3] as u32)).collect();
+    assert_eq!(sorted(loaded.cells.clone()), sorted(cells));
+    assert_eq!(loaded.rule.as_deref(), Some("B2/S34"));
+    assert_eq!(loaded.property("neighbor_type"), Some("tripod"));
+    assert_eq!(loaded.property("topology"), Some("torus"));
+}
+
+#[test]
+fn malformed_patterns_are_errors() {
+    // Runs that would overflow the position or allocate past the size limit
+    assert!(parse_rle("x = 3, y = 1\n2147483647b2o!").is_err());
+    assert!(parse_rle("x = 3, y = 1\n2147483647$2o!").is_err());
+    assert!(parse_rle("x = 3, y = 1\n2000000000o!").is_err());
+    assert!(parse_rle("x = 3, y = 1\n99999999999o!").is_err());
+    assert!(parse_rle(&format!("x = 1, y = 1\n{}b{}o!", MAX_PATTERN_SIZE - 1, 2)).is_err());
+    assert_eq!(parse_rle(&format!("x = 1, y = 1\n{}bo!", MAX_PATTERN_SIZE - 1)).unwrap().cells.len(), 1);
+    assert!(parse_rle("x = 3, y = 1\nboz!").is_err());
+
+    // Values that do not fit a coordinate or state name their line
+    let err = parse_cell_list("0 0\n2 3000000000\n").unwrap_err();
+    assert!(err.contains("line 2"), "{err}");
+    let err = parse_cell_list("0 0 0 -1\n").unwrap_err();
+    assert!(err.contains("line 1"), "{err}");
+    assert!(parse_cell_list("0 0 0 4294967296\n").is_err());
+    assert!(parse_cell_list("1 2 3 4 5\n").is_err());
+    assert!(parse_cell_list("1 two\n").is_err());
+}
+
+#[test]
+fn run_arguments_fill_the_options() {
+    let opts = parse_run_args(&args(
+        "--width 32 --rule B36/S23 --random --generations 50 --until population<5 --crop live:2 --world prey:B3/S23",
+    ))
+    .unwrap();
+    assert_eq!(opts.width, Some(32));
+    assert_eq!(opts.rule.as_deref(), Some("B36/S23"));
+    assert!(opts.random);
+    assert_eq!(opts.generations, 50);
+    assert_eq!(opts.until, Some(StopCondition::PopulationBelow(5)));
+    assert!(matches!(opts.gif_options.crop, GifCrop::LiveBounds(2)));
+    assert_eq!(opts.worlds, [("prey".to_string(), "B3/S23".to_string())]);
+
+    let defaults = parse_run_args(&[]).unwrap();
+    assert_eq!((defaults.generations, defaults.until, defaults.width), (100, None, None));
+
+    for bad in ["--width", "--width -3", "--generations many", "--until forever", "--bogus 1", "--world nameless"] {
+        assert!(parse_run_args(&args(bad)).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn stop_conditions_parse() {
+    assert_eq!(StopCondition::parse("extinct"), Ok(StopCondition::Extinct));
+    assert_eq!(StopCondition::parse("static"), Ok(StopCondition::Static));
+    assert_eq!(StopCondition::parse("cycle"), Ok(StopCondition::Cycle));
+    assert_eq!(StopCondition::parse("population>10"), Ok(StopCondition::PopulationAbove(10)));
+    assert_eq!(StopCondition::parse("population< 3"), Ok(StopCondition::PopulationBelow(3)));
+    assert!(StopCondition::parse("population<x").is_err());
+    assert!(StopCondition::parse("population=3").is_err());
+}
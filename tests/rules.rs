@@ -0,0 +1,61 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::Rule;
+use cell_manager::cell_manager::rules::MAX_NEIGHBOR_COUNT;
+
+mod common;
+use common::sorted;
+
+// Next generation computed cell by cell, looking forwards from every cell
+fn brute_force_step(cm: &CellManager) -> Vec<i32> {
+    let [min_q, max_q, min_r, max_r, min_s, max_s] = cm.get_bounds();
+    let mut next = Vec::new();
+    for q in min_q..=max_q {
+        for r in min_r..=max_r {
+            for s in min_s..=max_s {
+                let state = cm.get_rule().next_state(cm.get_cell(q, r, s), cm.count_live_neighbors(q, r, s));
+                if state != 0 {
+                    next.extend([q, r, s, state as i32]);
+                }
+            }
+        }
+    }
+    next
+}
+
+#[test]
+fn asymmetric_neighborhoods_step_across_flipped_seams() {
+    for topology in ["torus", "klein_bottle", "cross_surface", "sphere"] {
+        let mut cm = CellManager::new(12, 12, 1, None);
+        cm.change_grid_properties("hexagon".to_string(), "tripod".to_string(), 1, topology.to_string());
+        assert!(cm.set_rule("B1/S12"));
+        // A fixed scatter of cells, reaching every edge
+        let mut seed: u32 = 5;
+        for q in -6..6 {
+            for r in -6..6 {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                if (seed >> 16).is_multiple_of(10) {
+                    cm.set_cell(q, r, 0, 1);
+                }
+            }
+        }
+        for generation in 0..8 {
+            let expected = sorted(brute_force_step(&cm));
+            cm.step();
+            assert_eq!(sorted(cm.for_each_cell()), expected, "{topology} generation {generation}");
+        }
+    }
+}
+
+#[test]
+fn neighbor_count_ranges_are_bounded() {
+    let rule = Rule::parse("B3,2-4,3/S5-5").unwrap();
+    assert_eq!(rule.birth, [2, 3, 4]);
+    assert_eq!(rule.survival, [5]);
+    assert!(Rule::parse("B3/S5-3").is_none());
+
+    let top = format!("B0-{MAX_NEIGHBOR_COUNT}/S");
+    assert_eq!(Rule::parse(&top).unwrap().birth.len(), MAX_NEIGHBOR_COUNT as usize + 1);
+    for huge in ["B0-4000000000/S23", "B3/S0-99999999", "B3/S-1", "B3/S2,70000"] {
+        assert!(Rule::parse(huge).is_none(), "{huge}");
+    }
+}
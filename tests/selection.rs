@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use cell_manager::CellManager;
+use cell_manager::cell_manager::coords::{cell_center, hex_distance, polygon_contains, shape_layers, triangle_vertices};
+use cell_manager::cell_manager::selection::{MAX_SELECTION_SPAN, reflect_cell, rotate_cell};
+use cell_manager::cell_manager::{Clipboard, PasteMode, Selection};
+
+type Cell = (i32, i32, i32);
+
+fn grid(shape: &str, width: usize, topology: &str) -> CellManager {
+    let mut cm = CellManager::new(width, width, shape_layers(shape) as usize, None);
+    cm.change_grid_properties(shape.to_string(), "vonNeumann".to_string(), 1, topology.to_string());
+    cm
+}
+
+fn live(cm: &CellManager) -> Vec<(i32, i32, i32, i32)> {
+    let mut cells: Vec<_> = cm.for_each_cell().chunks(4).map(|c| (c[0], c[1], c[2], c[3])).collect();
+    cells.sort();
+    cells
+}
+
+// Clockwise as drawn, with y pointing down
+fn turn((x, y): (f32, f32), degrees: f32) -> (f32, f32) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+#[test]
+fn paste_modes_combine_with_the_grid() {
+    // Clipboard row: live, dead, live (state 2)
+    let clipboard = Clipboard {
+        shape: "square".to_string(),
+        cells: vec![(0, 0, 0, 1), (1, 0, 0, 0), (2, 0, 0, 2)],
+    };
+    let setup = || {
+        let mut cm = grid("square", 16, "none");
+        cm.batch_update(vec![(0, 0, 0, 3), (1, 0, 0, 3)], None);
+        cm.set_clipboard(clipboard.clone());
+        cm
+    };
+
+    let mut cm = setup();
+    assert_eq!(cm.paste(0, 0, PasteMode::Or), 2);
+    assert_eq!(live(&cm), [(0, 0, 0, 1), (1, 0, 0, 3), (2, 0, 0, 2)]);
+
+    let mut cm = setup();
+    assert_eq!(cm.paste(0, 0, PasteMode::Xor), 2);
+    assert_eq!(live(&cm), [(1, 0, 0, 3), (2, 0, 0, 2)]);
+
+    let mut cm = setup();
+    assert_eq!(cm.paste(0, 0, PasteMode::Overwrite), 3);
+    assert_eq!(live(&cm), [(0, 0, 0, 1), (2, 0, 0, 2)]);
+    assert_eq!(cm.selection_cells(), [(0, 0, 0), (1, 0, 0), (2, 0, 0)]);
+    // One undo step per paste
+    assert!(cm.undo());
+    assert_eq!(live(&cm), [(0, 0, 0, 3), (1, 0, 0, 3)]);
+
+    // Shapes must match
+    let mut cm = grid("hexagon", 16, "none");
+    cm.set_clipboard(clipboard);
+    assert_eq!(cm.paste(0, 0, PasteMode::Or), 0);
+    assert!(live(&cm).is_empty());
+}
+
+#[test]
+fn cut_and_paste_move_cells_across_a_torus_edge() {
+    let mut cm = grid("square", 16, "torus");
+    cm.batch_update(vec![(6, 0, 0, 1), (7, 0, 0, 1), (7, 1, 0, 2)], None);
+    cm.select(Selection::Rect([6, 7, 0, 1]));
+    assert_eq!(cm.cut(), 3);
+    assert!(live(&cm).is_empty());
+
+    // The right column lands past the edge and wraps to q = -8
+    assert_eq!(cm.paste(7, 0, PasteMode::Or), 3);
+    assert_eq!(live(&cm), [(-8, 0, 0, 1), (-8, 1, 0, 2), (7, 0, 0, 1)]);
+    assert_eq!(cm.translate_selection(-2, 3), 3);
+    assert_eq!(live(&cm), [(5, 3, 0, 1), (6, 3, 0, 1), (6, 4, 0, 2)]);
+}
+
+#[test]
+fn huge_selections_stay_within_the_grid() {
+    let huge = Selection::Rect([-100_000, 100_000, -100_000, 100_000]);
+    assert_eq!(grid("square", 16, "none").cells_in(&huge).len(), 256);
+    assert_eq!(grid("square", 16, "cylinder").cells_in(&huge).len(), 256);
+    // One period of a wrapping axis, starting at the low edge of the rect
+    let torus = grid("square", 16, "torus");
+    assert_eq!(torus.cells_in(&huge).len(), 256);
+    assert_eq!(torus.cells_in(&Selection::Rect([100, 300, 0, 0]))[..2], [(100, 0, 0), (101, 0, 0)]);
+
+    // Grids without edges stop at MAX_SELECTION_SPAN
+    let mut open = CellManager::new(3000, 3000, 1, None);
+    open.change_grid_properties("square".to_string(), "moore".to_string(), 1, "none".to_string());
+    assert_eq!(open.cells_in(&Selection::Rect([-100_000, 100_000, 0, 0])).len(), MAX_SELECTION_SPAN as usize);
+
+    for topology in ["none", "torus"] {
+        let cm = grid("hexagon", 16, topology);
+        for radius in [-1, i32::MAX, MAX_SELECTION_SPAN] {
+            assert!(cm.cells_in(&Selection::Hex { q: 0, r: 0, radius }).is_empty(), "{radius}");
+        }
+        assert!(cm.cells_in(&Selection::Hex { q: 0, r: 0, radius: 1000 }).len() <= 33 * 33, "{topology}");
+    }
+    let far = Selection::Hex { q: i32::MAX, r: i32::MIN, radius: 1000 };
+    assert!(grid("hexagon", 16, "none").cells_in(&far).is_empty());
+    let all = grid("hexagon", 16, "none").cells_in(&Selection::Hex { q: 0, r: 0, radius: 1000 });
+    assert_eq!(all.len(), 256);
+    open.change_grid_properties("hexagon".to_string(), "hexagonal".to_string(), 1, "none".to_string());
+    assert_eq!(open.cells_in(&Selection::Hex { q: 0, r: 0, radius: 2 }).len(), 19);
+}
+
+#[test]
+fn square_selections_turn_by_quarters() {
+    let mut cm = grid("square", 16, "none");
+    // An L: a column of three with a foot to the right
+    cm.batch_update(vec![(0, 0, 0, 1), (0, 1, 0, 1), (0, 2, 0, 1), (1, 2, 0, 1)], None);
+    cm.select(Selection::Rect([0, 1, 0, 2]));
+    assert_eq!(cm.rotate_selection(1), 4);
+    assert_eq!(live(&cm), [(0, 0, 0, 1), (0, 1, 0, 1), (1, 0, 0, 1), (2, 0, 0, 1)]);
+    cm.select(Selection::Rect([0, 2, 0, 1]));
+    cm.rotate_selection(3);
+    cm.reflect_selection();
+    assert_eq!(live(&cm), [(0, 2, 0, 1), (1, 0, 0, 1), (1, 1, 0, 1), (1, 2, 0, 1)]);
+}
+
+#[test]
+fn hexagons_turn_by_sixths_about_a_cell_centre() {
+    let cells: Vec<Cell> = (-3..=3).flat_map(|q| (-3..=3).map(move |r| (q, r, 0))).collect();
+    for &cell in &cells {
+        let turned = rotate_cell("hexagon", cell, 1);
+        let (x, y) = turn(cell_center("hexagon", cell.0, cell.1, 0), 60.0);
+        let (tx, ty) = cell_center("hexagon", turned.0, turned.1, 0);
+        assert!((x - tx).abs() < 1e-4 && (y - ty).abs() < 1e-4, "{cell:?} -> {turned:?}");
+        assert_eq!(hex_distance(turned.0, turned.1), hex_distance(cell.0, cell.1));
+        assert_eq!(rotate_cell("hexagon", cell, 6), cell);
+        assert_eq!(rotate_cell("hexagon", cell, -1), rotate_cell("hexagon", cell, 5));
+        assert_eq!(reflect_cell("hexagon", reflect_cell("hexagon", cell)), cell);
+    }
+
+    // A hex region keeps its shape and cells under a turn of the clipboard
+    let mut cm = grid("hexagon", 16, "none");
+    cm.set_cell(1, 0, 0, 1);
+    cm.set_cell(0, 0, 0, 2);
+    cm.select(Selection::Hex { q: 0, r: 0, radius: 1 });
+    cm.copy();
+    cm.clipboard_mut().unwrap().rotate(2);
+    let clipboard = cm.clipboard().unwrap();
+    assert_eq!(clipboard.cells.len(), 7);
+    assert_eq!(clipboard.population(), 2);
+}
+
+#[test]
+fn triangles_and_rhombi_turn_by_thirds() {
+    for q in -3..=3 {
+        for r in -3..=3 {
+            for s in 0..2 {
+                // A third of a turn about the origin vertex, on the vertex lattice
+                let turned = rotate_cell("triangle", (q, r, s), 1);
+                let expected: HashSet<(i32, i32)> =
+                    triangle_vertices(q, r, s).iter().map(|&(x, y)| (-y, x - y)).collect();
+                let actual: HashSet<(i32, i32)> = triangle_vertices(turned.0, turned.1, turned.2).into_iter().collect();
+                assert_eq!(actual, expected, "{:?}", (q, r, s));
+                assert_eq!(rotate_cell("triangle", (q, r, s), 3), (q, r, s));
+            }
+            for s in 0..3 {
+                let cell = (q, r, s);
+                let turned = rotate_cell("rhombus", cell, 1);
+                let (x, y) = turn(cell_center("rhombus", q, r, s), 120.0);
+                let (tx, ty) = cell_center("rhombus", turned.0, turned.1, turned.2);
+                assert!((x - tx).abs() < 1e-4 && (y - ty).abs() < 1e-4, "{cell:?} -> {turned:?}");
+                assert_eq!(rotate_cell("rhombus", cell, 3), cell);
+            }
+        }
+    }
+}
+
+#[test]
+fn polygons_select_cells_by_their_centre_on_every_shape() {
+    let polygon = vec![(21.0, 24.0), (37.5, 22.0), (38.0, 37.0), (24.0, 36.5)];
+    for shape in ["square", "hexagon", "triangle", "rhombus", "trihexagonal", "snub_square", "cairo"] {
+        let cm = grid(shape, 200, "none");
+        let mut expected = Vec::new();
+        for q in -100..100 {
+            for r in -100..100 {
+                for s in 0..shape_layers(shape) {
+                    if polygon_contains(&polygon, cell_center(shape, q, r, s)) {
+                        expected.push((q, r, s));
+                    }
+                }
+            }
+        }
+        expected.sort();
+        assert!(!expected.is_empty(), "{shape}");
+        assert_eq!(cm.cells_in(&Selection::Polygon(polygon.clone())), expected, "{shape}");
+    }
+}
@@ -0,0 +1,85 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::{Rule, Vote};
+
+mod common;
+use common::life;
+
+#[test]
+fn species_rules_parse_and_vote() {
+    let rule = Rule::parse("species:B3/S23/S23").unwrap();
+    let species = rule.species.as_ref().unwrap();
+    assert_eq!((rule.states, species.count(), species.vote), (3, 2, Vote::Majority));
+    assert_eq!(species.interaction, vec![vec![1.0; 2]; 2]);
+    assert!((1..3).all(|state| rule.is_alive(state)));
+
+    // Majority of three decides the species, a tie leaves the cell dead
+    assert_eq!(rule.next_state_species(0, &[1, 2], 0.0), 2);
+    assert_eq!(rule.next_state_species(0, &[2, 2], 0.0), 0);
+    assert_eq!(Rule::parse("species:B4/S/S").unwrap().next_state_species(0, &[2, 2], 0.0), 0);
+    // Every live neighbor counts for survival, whatever its species
+    assert_eq!(rule.next_state_species(1, &[0, 3], 0.0), 1);
+    assert_eq!(rule.next_state_species(2, &[3, 1], 0.0), 0);
+
+    // Predators (2) eat prey (1): each one takes a neighbor off the prey's
+    // count, and the weighted vote lets prey lose births next to them
+    let rule = Rule::parse("species:B3/S23/S234/I1,-1;1,1/weighted").unwrap();
+    assert_eq!(rule.next_state_species(1, &[3, 1], 0.0), 1);
+    assert_eq!(rule.next_state_species(1, &[2, 1], 0.0), 0);
+    assert_eq!(rule.next_state_species(2, &[2, 2], 0.0), 2);
+    assert_eq!(rule.next_state_species(0, &[2, 1], 0.0), 2);
+
+    for bad in ["species:B3", "species:S23/S23", "species:B3/S23/S2/I1,0", "species:B3/S2/S2/I1,x;0,1", "species:B3/S2/vote"] {
+        assert!(Rule::parse(bad).is_none(), "{bad}");
+    }
+}
+
+#[test]
+fn immigration_keeps_life_dynamics() {
+    // With every neighbor counting alike, colours do not change who lives
+    let mut species = life(24, "torus");
+    assert!(species.set_rule("species:B3/S23/S23"));
+    let mut plain = life(24, "torus");
+    let [min_q, max_q, min_r, max_r, _, _] = species.get_bounds();
+    species.random_cells_seeded(7, 0.4, [min_q, max_q, min_r, max_r]);
+    assert!(species.for_each_cell().chunks(4).any(|c| c[3] == 2));
+    for c in species.for_each_cell().chunks(4) {
+        plain.set_cell(c[0], c[1], c[2], 1);
+    }
+
+    species.set_stats_capacity(64);
+    for _ in 0..20 {
+        species.step();
+        plain.step();
+        let positions = |cm: &CellManager| {
+            let mut cells: Vec<Vec<i32>> = cm.for_each_cell().chunks(4).map(|c| c[..3].to_vec()).collect();
+            cells.sort();
+            cells
+        };
+        assert_eq!(positions(&species), positions(&plain));
+    }
+
+    // Species populations add up to the population of every generation
+    let (ones, twos) = (species.stats().state_history(1), species.stats().state_history(2));
+    let totals: Vec<usize> = species.stats().iter().map(|s| s.population).collect();
+    assert_eq!(ones.iter().zip(&twos).map(|(a, b)| a + b).collect::<Vec<_>>(), totals);
+}
+
+#[test]
+fn interaction_matrix_separates_colonies() {
+    // Each species only counts its own kind: a mixed block breaks up
+    let mut cm = life(24, "torus");
+    assert!(cm.set_rule("species:B3/S23/S23/I1,0;0,1"));
+    for (q, r, state) in [(0, 0, 1), (1, 0, 1), (0, 1, 2), (1, 1, 2)] {
+        cm.set_cell(q, r, 0, state);
+    }
+    assert_eq!(cm.count_species_neighbors(0, 0, 0), vec![1, 2]);
+    cm.step();
+    assert!(cm.for_each_cell().is_empty());
+
+    // A one-species block is still stable
+    for (q, r) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        cm.set_cell(q, r, 0, 2);
+    }
+    cm.step_n(3);
+    assert_eq!(cm.latest_stats().map(|s| s.per_state.clone()), Some(vec![0, 0, 4]));
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use cell_manager::CellManager;
+use cell_manager::cell_manager::GenerationStats;
+
+mod common;
+use common::life;
+
+fn states(cells: &[i32]) -> HashMap<(i32, i32, i32), i32> {
+    cells.chunks(4).map(|c| ((c[0], c[1], c[2]), c[3])).collect()
+}
+
+#[test]
+fn blinker_steps_record_births_deaths_and_bounds() {
+    let mut cm = life(16, "none");
+    cm.config.chunk_size = 4;
+    for q in -1..=1 {
+        cm.set_cell(q, 0, 0, 1);
+    }
+    cm.record_stats();
+    let start = cm.latest_stats().unwrap().clone();
+    assert_eq!((start.generation, start.population, start.births, start.deaths), (0, 3, 0, 0));
+    assert_eq!(start.bounds, Some([-1, 1, 0, 0]));
+    // q = -1 lies in the chunk left of q = 0 and 1
+    assert_eq!(start.active_chunks, 2);
+
+    cm.step();
+    let stats = cm.latest_stats().unwrap();
+    assert_eq!((stats.generation, stats.population, stats.births, stats.deaths), (1, 3, 2, 2));
+    assert_eq!(stats.per_state, [0, 3]);
+    assert_eq!(stats.bounds, Some([0, 0, -1, 1]));
+    assert_eq!(stats.active_chunks, 2);
+    assert_eq!(cm.stats().len(), 2);
+}
+
+#[test]
+fn step_stats_match_a_fresh_count() {
+    let mut cm = CellManager::new(24, 24, 1, Some(8));
+    cm.change_grid_properties("hexagon".to_string(), "moore".to_string(), 1, "torus".to_string());
+    // Decaying states, so cells pass through state 2 before dying
+    assert!(cm.set_rule("23/2/3/M"));
+    cm.random_cells_seeded(11, 0.3, [-12, 11, -12, 11]);
+
+    for _ in 0..12 {
+        let before = states(&cm.for_each_cell());
+        cm.step();
+        let live = cm.for_each_cell();
+        let after = states(&live);
+        let stats = cm.latest_stats().unwrap().clone();
+
+        let fresh = GenerationStats::from_cells(cm.generation, &live, 8, 1);
+        assert_eq!(stats.population, fresh.population);
+        assert_eq!(stats.per_state, fresh.per_state);
+        assert_eq!(stats.bounds, fresh.bounds);
+        assert_eq!(stats.active_chunks, fresh.active_chunks);
+        assert_eq!(stats.births, after.keys().filter(|c| !before.contains_key(c)).count());
+        assert_eq!(stats.deaths, before.keys().filter(|c| !after.contains_key(c)).count());
+    }
+    assert!(cm.stats().state_history(2).iter().any(|&n| n > 0));
+}
+
+#[test]
+fn ring_buffer_keeps_the_latest_generations() {
+    let mut cm = life(16, "none");
+    cm.set_stats_capacity(3);
+    for q in -1..=1 {
+        cm.set_cell(q, 0, 0, 1);
+    }
+    cm.step_n(5);
+    let generations: Vec<u64> = cm.stats().iter().map(|s| s.generation).collect();
+    assert_eq!(generations, [3, 4, 5]);
+    assert!(cm.stats().get(2).is_none());
+    assert_eq!(cm.stats().state_history(1), [3, 3, 3]);
+
+    let csv = cm.stats_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "generation,population,births,deaths,min_q,max_q,min_r,max_r,active_chunks,state_1");
+    assert_eq!(lines[1], "3,3,2,2,0,0,-1,1,2,3");
+    assert_eq!(lines.len(), 4);
+
+    cm.clear();
+    cm.step();
+    assert!(cm.stats_csv().ends_with("6,0,0,0,,,,,0,0\n"));
+}
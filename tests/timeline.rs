@@ -0,0 +1,89 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::GridHash;
+
+mod common;
+use common::{life, sorted};
+
+fn soup(interval: u64, retention: u64) -> CellManager {
+    let mut cm = life(24, "torus");
+    cm.set_timeline(interval, retention);
+    cm.random_cells_seeded(9, 0.4, [-12, 11, -12, 11]);
+    cm
+}
+
+// Cells of generations 0..=n
+fn run(cm: &mut CellManager, n: usize) -> Vec<Vec<Vec<i32>>> {
+    let mut snapshots = vec![sorted(cm.for_each_cell())];
+    for _ in 0..n {
+        cm.step();
+        snapshots.push(sorted(cm.for_each_cell()));
+    }
+    snapshots
+}
+
+#[test]
+fn jumps_match_stepping_from_the_start() {
+    let mut cm = soup(8, 1000);
+    let snapshots = run(&mut cm, 40);
+    assert_eq!(cm.timeline().range(), Some((0, 40)));
+    assert!(cm.jump_to_generation(17));
+    // Stats of the generations jumped over are dropped
+    assert_eq!(cm.stats().latest().map(|s| s.generation), Some(17));
+
+    for generation in [17, 0, 40, 8, 9, 31, 39, 1] {
+        assert!(cm.jump_to_generation(generation));
+        assert_eq!(cm.generation, generation);
+        assert_eq!(sorted(cm.for_each_cell()), snapshots[generation as usize], "generation {generation}");
+        assert_eq!(cm.state_hash(), GridHash::from_cells(&cm.for_each_cell()).zobrist);
+    }
+    assert!(!cm.jump_to_generation(41));
+
+    // Stepping on from a jump retraces the run
+    assert!(cm.jump_to_generation(20));
+    assert_eq!(run(&mut cm, 5), snapshots[20..=25]);
+    assert_eq!(cm.timeline().range(), Some((0, 25)));
+
+    while cm.step_back() {}
+    assert_eq!(cm.generation, 0);
+    assert_eq!(sorted(cm.for_each_cell()), snapshots[0]);
+}
+
+#[test]
+fn retention_limits_how_far_back_a_jump_reaches() {
+    let mut cm = soup(4, 10);
+    let snapshots = run(&mut cm, 50);
+    assert_eq!(cm.timeline().range(), Some((40, 50)));
+    assert!(!cm.jump_to_generation(39));
+    assert!(cm.jump_to_generation(43));
+    assert_eq!(sorted(cm.for_each_cell()), snapshots[43]);
+
+    cm.set_timeline(4, 0);
+    assert_eq!(cm.timeline().range(), None);
+    assert_eq!(cm.timeline().memory_used(), 0);
+    cm.step();
+    assert!(!cm.step_back());
+}
+
+#[test]
+fn recording_is_off_until_turned_on() {
+    let mut cm = CellManager::new(24, 24, 1, None);
+    cm.random_cells_seeded(9, 0.4, [-12, 11, -12, 11]);
+    cm.step_n(5);
+    assert_eq!(cm.timeline().range(), None);
+    assert_eq!(cm.timeline().memory_used(), 0);
+    assert!(!cm.step_back());
+}
+
+#[test]
+fn edits_branch_the_recorded_run() {
+    let mut cm = soup(8, 1000);
+    let snapshots = run(&mut cm, 10);
+    cm.set_cell(0, 0, 0, 1 - cm.get_cell(0, 0, 0));
+    let edited = sorted(cm.for_each_cell());
+    cm.step_n(3);
+
+    assert!(cm.jump_to_generation(10));
+    assert_eq!(sorted(cm.for_each_cell()), edited);
+    assert!(cm.jump_to_generation(9));
+    assert_eq!(sorted(cm.for_each_cell()), snapshots[9]);
+}
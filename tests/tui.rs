@@ -0,0 +1,86 @@
+use cell_manager::CellManager;
+use cell_manager::tui::{Layout, TuiApp};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn viewer(shape: &str) -> TuiApp {
+    let mut cm = CellManager::new(32, 32, 1, None);
+    cm.change_grid_properties(shape.to_string(), "vonNeumann".to_string(), 1, "none".to_string());
+    TuiApp::new(cm)
+}
+
+fn press(app: &mut TuiApp, keys: &str) -> bool {
+    keys.chars().all(|c| app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)))
+}
+
+#[test]
+fn layouts_stagger_hexagons_and_interleave_layers() {
+    assert_eq!(Layout::for_shape("square").dot(3, -2, 0), (3, -2));
+    // Odd hex rows sit half a cell to the right
+    let hex = Layout::for_shape("hexagon");
+    assert_eq!(hex.dot(0, 0, 0), (0, 0));
+    assert_eq!(hex.dot(0, 1, 0), (1, 1));
+    assert_eq!(hex.dot(1, 0, 0), (2, 0));
+    let triangle = Layout::for_shape("triangle");
+    assert_eq!((triangle.dot(1, 0, 0), triangle.dot(1, 0, 1)), ((2, 0), (3, 0)));
+    assert_eq!(Layout::for_shape("rhombus").dot(1, 1, 2), (13, 1));
+}
+
+#[test]
+fn dot_buffers_centre_on_the_view() {
+    let mut app = viewer("square");
+    press(&mut app, "e");
+    let (dots, x0, y0) = app.dot_buffer(8, 4);
+    assert_eq!((x0, y0), (-4, -2));
+    assert_eq!(dots.len(), 32);
+    assert_eq!(dots[2 * 8 + 4], Some((1, 0)));
+    assert_eq!(dots.iter().filter(|d| d.is_some_and(|(v, _)| v != 0)).count(), 1);
+
+    // Hexagons only fill every other dot of a row
+    let (dots, _, _) = viewer("hexagon").dot_buffer(8, 4);
+    assert_eq!(dots.iter().filter(|d| d.is_some()).count(), 16);
+
+    assert!(app.dot_buffer(-5, 3).0.is_empty());
+    assert!(app.dot_buffer(0, 0).0.is_empty());
+}
+
+#[test]
+fn keys_move_the_cursor_and_edit_cells() {
+    let mut app = viewer("square");
+    assert!(press(&mut app, "llj"));
+    assert_eq!(app.cursor(), (2, 1, 0));
+    press(&mut app, "e");
+    assert_eq!(app.cm().get_cell(2, 1, 0), 1);
+    press(&mut app, "3h");
+    assert_eq!(app.cm().get_cell(2, 1, 0), 3);
+    assert_eq!(app.cursor(), (1, 1, 0));
+    press(&mut app, "uu");
+    assert_eq!(app.cm().get_cell(2, 1, 0), 0);
+    press(&mut app, "U");
+    assert_eq!(app.cm().get_cell(2, 1, 0), 1);
+
+    // The view follows a cursor that leaves the screen
+    assert_eq!(app.view(), (0, 0));
+    press(&mut app, &"l".repeat(200));
+    assert!(app.view().0 > 0 && app.view().0 <= 201, "{:?}", app.view());
+
+    assert!(!press(&mut app, "q"));
+}
+
+#[test]
+fn tiny_terminals_only_get_the_status_lines() {
+    let mut app = viewer("square");
+    press(&mut app, "e");
+    for (cols, rows) in [(0, 0), (1, 1), (40, 1), (40, 2), (1, 10)] {
+        let mut out = Vec::new();
+        app.render(&mut out, cols, rows).unwrap();
+    }
+
+    // The cursor cell is highlighted once the grid has room
+    let highlighted = |rows: u16| {
+        let mut out = Vec::new();
+        app.render(&mut out, 40, rows).unwrap();
+        String::from_utf8(out).unwrap().contains("\x1b[48;5;4m")
+    };
+    assert!(!highlighted(2));
+    assert!(highlighted(10));
+}
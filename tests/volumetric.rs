@@ -0,0 +1,73 @@
+use cell_manager::CellManager;
+use cell_manager::cell_manager::Rule;
+use cell_manager::cell_manager::voxels::{write_obj, write_vox};
+
+fn cube(side: usize, topology: &str) -> CellManager {
+    let mut cm = CellManager::new(side, side, side, None);
+    cm.change_grid_properties("cube".to_string(), "vonNeumann".to_string(), 1, topology.to_string());
+    cm
+}
+
+#[test]
+fn volumetric_rules_decay_and_pick_their_neighborhood() {
+    let rule = Rule::parse("9-26/5-7,12-13,15/5/M").unwrap();
+    assert_eq!(rule.survival, (9..=26).collect::<Vec<_>>());
+    assert_eq!(rule.birth, vec![5, 6, 7, 12, 13, 15]);
+    assert_eq!((rule.states, rule.neighborhood), (5, Some("moore")));
+
+    // Born, fails to survive, then decays 2 -> 3 -> 4 -> dead
+    let rule = Rule::parse("4/4/5/N").unwrap();
+    assert_eq!(rule.next_state(0, 4), 1);
+    assert_eq!(rule.next_state(1, 4), 1);
+    assert_eq!(rule.next_state(1, 3), 2);
+    assert_eq!([2, 3, 4].map(|s| rule.next_state(s, 4)), [3, 4, 0]);
+    assert!(rule.is_alive(1) && !rule.is_alive(2));
+    for bad in ["4/4/1/M", "4/4/5/X", "4/4/5"] {
+        assert!(Rule::parse(bad).is_none(), "{bad}");
+    }
+
+    let mut cm = cube(8, "none");
+    assert!(cm.set_rule("4/4/5/M"));
+    assert_eq!(cm.config.neighbor_type, "moore");
+    assert_eq!(cm.neighborhood().get_neighbor_offsets(0).len(), 26);
+}
+
+#[test]
+fn torus_wraps_the_z_axis() {
+    let mut cm = cube(6, "torus");
+    let [min_q, _, min_r, _, _, max_s] = cm.get_bounds();
+    assert_eq!(cm.wrap_cell(min_q, min_r, max_s + 1), Some((min_q, min_r, 0)));
+
+    // The six face neighbors of a top cell are born, one across the seam
+    assert!(cm.set_rule("B1/S"));
+    cm.set_cell(0, 0, max_s, 1);
+    cm.step();
+    assert_eq!(cm.get_cell(0, 0, 0), 1);
+    assert_eq!(cm.for_each_cell().len() / 4, 6);
+
+    let mut finite = cube(6, "finite");
+    assert_eq!(finite.wrap_cell(0, 0, max_s + 1), None);
+    assert!(finite.set_rule("B1/S"));
+    finite.set_cell(0, 0, max_s, 1);
+    finite.step();
+    assert_eq!(finite.for_each_cell().len() / 4, 5);
+}
+
+#[test]
+fn exports_voxels_and_closed_meshes() {
+    let cells = [3, -2, 5, 1, 4, -2, 5, 2];
+    let vox = write_vox(&cells).unwrap();
+    assert_eq!(&vox[..4], b"VOX ");
+    // SIZE 2 x 1 x 1, then two voxels moved to the origin
+    assert_eq!(&vox[20..24], b"SIZE");
+    assert_eq!(vox[32..44], [2, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+    assert_eq!(vox[vox.len() - 8..], [0, 0, 0, 1, 1, 0, 0, 2]);
+    assert!(write_vox(&[0, 0, 0, 1, 300, 0, 0, 1]).is_err());
+
+    // A lone cell gives a closed octahedron-like cell: 8 vertices, 6 quads
+    let obj = write_obj(&[0, 0, 0, 1]);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6);
+    let two = write_obj(&cells);
+    assert_eq!(two.lines().filter(|l| l.starts_with("f ")).count(), 10);
+}
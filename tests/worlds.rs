@@ -0,0 +1,84 @@
+use cell_manager::cell_manager::CycleState;
+
+mod common;
+use common::life;
+
+#[test]
+fn resource_world_feeds_births() {
+    // Three neighbors only give a birth (B4) on top of food, which never changes
+    let mut cm = life(16, "none");
+    assert!(cm.set_rule("B4/S34"));
+    assert!(cm.add_world("food", "B/S012345678"));
+    cm.couple("main", "food", "offsets:0,0", 1.0).unwrap();
+    for q in -1..=1 {
+        cm.set_cell(q, -1, 0, 1);
+    }
+    assert!(cm.set_world_cell("food", 0, 0, 0, 1));
+    assert_eq!(cm.count_live_neighbors(0, 0, 0), 3);
+    assert_eq!(cm.count_weighted_neighbors(0, 0, 0), 4.0);
+
+    cm.step();
+    assert_eq!(cm.get_cell(0, 0, 0), 1);
+    assert_eq!(cm.get_cell(0, -2, 0), 0);
+    assert_eq!(cm.world_cells("food"), Some(vec![0, 0, 0, 1]));
+
+    // A negative weight makes food poison the same spot
+    assert!(cm.clear_couplings("main"));
+    cm.couple("main", "food", "offsets:0,0", -1.0).unwrap();
+    cm.clear();
+    for q in -1..=1 {
+        cm.set_cell(q, -1, 0, 1);
+    }
+    cm.set_cell(-1, 0, 0, 1);
+    cm.step();
+    assert_eq!(cm.get_cell(0, 0, 0), 0);
+}
+
+#[test]
+fn worlds_step_together_and_share_cycles() {
+    let mut cm = life(16, "none");
+    assert!(cm.add_world("life", "B3/S23"));
+    // A block on the main grid, a blinker in the other world, not coupled
+    for (q, r) in [(-5, -5), (-4, -5), (-5, -4), (-4, -4)] {
+        cm.set_cell(q, r, 0, 1);
+    }
+    for q in 2..=4 {
+        cm.set_world_cell("life", q, 3, 0, 1);
+    }
+    cm.record_stats();
+    cm.step_n(4);
+    assert_eq!(cm.for_each_cell().len() / 4, 4);
+    assert_eq!(cm.get_world_cell("life", 3, 2, 0), Some(0));
+    assert!(matches!(cm.cycle_state(), CycleState::Oscillating { period: 2, .. }));
+    cm.step();
+    assert_eq!(cm.get_world_cell("life", 3, 2, 0), Some(1));
+
+    // Worlds follow the grid size
+    cm.resize(10, 10, 1);
+    assert_eq!(cm.world_cells("life").map(|c| c.len() / 4), Some(3));
+}
+
+#[test]
+fn worlds_are_named_and_couplings_checked() {
+    let mut cm = life(16, "none");
+    assert!(cm.add_world("a", "B2/S"));
+    assert!(cm.add_world("b", "B36/S23"));
+    for (name, rule) in [("main", "B3/S23"), ("a", "B1/S1"), ("", "B3/S23"), ("c", "nonsense")] {
+        assert!(!cm.add_world(name, rule), "{name}");
+    }
+    assert_eq!(cm.world_names(), vec!["main", "a", "b"]);
+    assert_eq!(cm.get_world_rule("b").map(|r| r.rulestring.as_str()), Some("B36/S23"));
+
+    cm.couple("a", "b", "vonNeumann", 0.5).unwrap();
+    cm.couple("main", "b", "moore", 1.0).unwrap();
+    assert!(cm.couple("main", "c", "moore", 1.0).is_err());
+    assert!(cm.couple("main", "a", "hexagonal", 1.0).is_err());
+    assert!(cm.couple("main", "a", "offsets:1", 1.0).is_err());
+
+    // Couplings touching a removed world go, the others are renumbered
+    assert!(cm.remove_world("a"));
+    assert!(!cm.remove_world("main"));
+    assert_eq!(cm.world_names(), vec!["main", "b"]);
+    let couplings: Vec<(usize, usize)> = cm.couplings().iter().map(|c| (c.target, c.source)).collect();
+    assert_eq!(couplings, vec![(0, 1)]);
+}